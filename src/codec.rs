@@ -0,0 +1,209 @@
+//! # Custom chunk codecs
+//! Support for chunk types this crate doesn't know, registered by downstream crates under their chunk type name.
+//! A codec supplies the four steps every built-in type goes through: decoding a chunk's data, building a struct
+//! from the chunk, building a chunk from the struct and encoding it again.
+//! Chunks and structs of registered types report `NuccChunkType::NuccChunkCustom` and are routed back to their codec
+//! by their Rust type, so several custom types can be registered at once. Only decoding is routed by name, so codecs
+//! that share their chunk and struct types with other codecs have to carry what they need in the chunk itself.
+//!
+use hashbrown::HashMap;
+use std::any::TypeId;
+use std::sync::{Arc, LazyLock, RwLock};
+
+// Re-exported so codecs can be written without depending on the same versions of these crates
+pub use bytes::Bytes;
+pub use indexmap::IndexMap;
+
+use super::error::{XfbinError, XfbinResult};
+use super::nucc::{
+    downcast_struct, NuccChunkConverter, NuccStruct, NuccStructConverter, NuccStructInfo, NuccStructReference, NuccUnknown,
+};
+use super::nucc_chunk::{NuccChunk, NuccChunkType};
+
+pub trait NuccChunkCodec: Send + Sync + 'static {
+    /// The decoded chunk, whose `chunk_type()` must be `NuccChunkType::NuccChunkCustom`
+    type Chunk: NuccChunk;
+
+    /// The struct scripts work with, whose `chunk_type()` must be `NuccChunkType::NuccChunkCustom`
+    type Struct: NuccStruct + Clone;
+
+    fn decode(&self, data: Bytes, version: u16) -> XfbinResult<Self::Chunk>;
+    fn encode(&self, chunk: Self::Chunk) -> XfbinResult<Vec<u8>>;
+
+    /// Build the struct from its chunk. Indices into the page's tables should be resolved here rather than kept,
+    /// as the tables can be reordered before writing. The struct info is filled in by the caller.
+    fn to_struct(
+        &self,
+        chunk: Self::Chunk,
+        struct_infos: &[NuccStructInfo],
+        struct_references: &[NuccStructReference],
+    ) -> XfbinResult<Self::Struct>;
+
+    fn to_chunk(
+        &self,
+        nucc_struct: Self::Struct,
+        struct_info_map: &IndexMap<NuccStructInfo, u32>,
+        struct_reference_map: &IndexMap<NuccStructReference, u32>,
+    ) -> XfbinResult<Self::Chunk>;
+
+    /// Whether the codec owns `nucc_struct`, for codecs that share their struct type with other codecs
+    fn handles(&self, _nucc_struct: &Self::Struct) -> bool {
+        true
+    }
+}
+
+/// `NuccChunkCodec` with the associated types erased, so codecs for different types can share the registry
+trait DynCodec: Send + Sync {
+    fn decode(&self, data: Bytes, version: u16) -> XfbinResult<Box<dyn NuccChunk>>;
+    fn encode(&self, chunk: Box<dyn NuccChunk>) -> XfbinResult<Vec<u8>>;
+    fn to_struct(&self, converter: NuccStructConverter) -> XfbinResult<Box<dyn NuccStruct>>;
+    fn to_chunk(&self, converter: NuccChunkConverter) -> XfbinResult<Box<dyn NuccChunk>>;
+    fn clone_struct(&self, nucc_struct: &dyn NuccStruct) -> Box<dyn NuccStruct>;
+    fn handles(&self, nucc_struct: &dyn NuccStruct) -> bool;
+}
+
+impl<C: NuccChunkCodec> DynCodec for C {
+    fn decode(&self, data: Bytes, version: u16) -> XfbinResult<Box<dyn NuccChunk>> {
+        Ok(Box::new(NuccChunkCodec::decode(self, data, version)?))
+    }
+
+    fn encode(&self, chunk: Box<dyn NuccChunk>) -> XfbinResult<Vec<u8>> {
+        NuccChunkCodec::encode(self, downcast_custom_chunk::<C::Chunk>(chunk)?)
+    }
+
+    fn to_struct(&self, converter: NuccStructConverter) -> XfbinResult<Box<dyn NuccStruct>> {
+        let chunk = downcast_custom_chunk::<C::Chunk>(converter.nucc_chunk)?;
+
+        Ok(Box::new(NuccChunkCodec::to_struct(
+            self,
+            chunk,
+            &converter.struct_infos,
+            &converter.struct_references,
+        )?))
+    }
+
+    fn to_chunk(&self, converter: NuccChunkConverter) -> XfbinResult<Box<dyn NuccChunk>> {
+        let nucc_struct = downcast_struct::<C::Struct>(converter.nucc_struct, NuccChunkType::NuccChunkCustom)?;
+
+        Ok(Box::new(NuccChunkCodec::to_chunk(
+            self,
+            nucc_struct,
+            &converter.struct_info_map,
+            &converter.struct_reference_map,
+        )?))
+    }
+
+    fn clone_struct(&self, nucc_struct: &dyn NuccStruct) -> Box<dyn NuccStruct> {
+        match nucc_struct.downcast_ref::<C::Struct>() {
+            Some(nucc_struct) => Box::new(nucc_struct.clone()),
+            None => unreachable!("the codec registry routed a struct to the wrong codec"),
+        }
+    }
+
+    fn handles(&self, nucc_struct: &dyn NuccStruct) -> bool {
+        nucc_struct
+            .downcast_ref::<C::Struct>()
+            .is_some_and(|nucc_struct| NuccChunkCodec::handles(self, nucc_struct))
+    }
+}
+
+fn downcast_custom_chunk<T: NuccChunk>(nucc_chunk: Box<dyn NuccChunk>) -> XfbinResult<T> {
+    let found = nucc_chunk.chunk_type();
+
+    nucc_chunk.downcast::<T>().map(|c| *c).map_err(|_| XfbinError::TypeMismatch {
+        expected: NuccChunkType::NuccChunkCustom,
+        found,
+    })
+}
+
+#[derive(Default)]
+struct Registry {
+    by_name: HashMap<String, Arc<dyn DynCodec>>,
+
+    /// Keyed by the `TypeId`s of both the chunk and the struct type of each codec
+    by_type: HashMap<TypeId, Arc<dyn DynCodec>>,
+}
+
+static REGISTRY: LazyLock<RwLock<Registry>> = LazyLock::new(Default::default);
+
+/// Decode chunks whose type is `chunk_type` with `codec`, replacing any codec registered for it before.
+/// Codecs are only used for chunk types that `NuccChunkType` doesn't cover.
+pub fn register_codec<C: NuccChunkCodec>(chunk_type: &str, codec: C) {
+    let codec: Arc<dyn DynCodec> = Arc::new(codec);
+    let mut registry = REGISTRY.write().unwrap();
+
+    registry.by_name.insert(chunk_type.to_string(), codec.clone());
+    registry.by_type.insert(TypeId::of::<C::Chunk>(), codec.clone());
+    registry.by_type.insert(TypeId::of::<C::Struct>(), codec);
+}
+
+pub fn is_registered(chunk_type: &str) -> bool {
+    REGISTRY.read().unwrap().by_name.contains_key(chunk_type)
+}
+
+fn by_name(chunk_type: &str) -> Option<Arc<dyn DynCodec>> {
+    REGISTRY.read().unwrap().by_name.get(chunk_type).cloned()
+}
+
+fn by_type(type_id: TypeId) -> XfbinResult<Arc<dyn DynCodec>> {
+    REGISTRY
+        .read()
+        .unwrap()
+        .by_type
+        .get(&type_id)
+        .cloned()
+        .ok_or_else(|| XfbinError::UnsupportedType(NuccChunkType::NuccChunkCustom.to_string()))
+}
+
+/// Decode with the codec registered for `chunk_type`, or `None` if there isn't one
+pub(crate) fn decode(data: Bytes, chunk_type: &str, version: u16) -> Option<XfbinResult<Box<dyn NuccChunk>>> {
+    by_name(chunk_type).map(|codec| codec.decode(data, version))
+}
+
+pub(crate) fn encode(chunk: Box<dyn NuccChunk>) -> XfbinResult<Vec<u8>> {
+    by_type(chunk.as_ref().as_any().type_id())?.encode(chunk)
+}
+
+pub(crate) fn to_struct(converter: NuccStructConverter) -> XfbinResult<Box<dyn NuccStruct>> {
+    by_type(converter.nucc_chunk.as_ref().as_any().type_id())?.to_struct(converter)
+}
+
+pub(crate) fn to_chunk(converter: NuccChunkConverter) -> XfbinResult<Box<dyn NuccChunk>> {
+    by_type(converter.nucc_struct.as_ref().as_any().type_id())?.to_chunk(converter)
+}
+
+pub(crate) fn clone_struct(nucc_struct: &dyn NuccStruct) -> Box<dyn NuccStruct> {
+    match by_type(nucc_struct.as_any().type_id()) {
+        Ok(codec) => codec.clone_struct(nucc_struct),
+        Err(_) => unreachable!("custom structs need a registered codec"),
+    }
+}
+
+/// Encode `nucc_struct` with its codec into the raw form it would take without one, resolving references against
+/// the given tables of its page. Custom structs are written to JSON this way, and read back as unknown structs.
+pub(crate) fn to_unknown(
+    nucc_struct: &dyn NuccStruct,
+    struct_info_map: IndexMap<NuccStructInfo, u32>,
+    struct_reference_map: IndexMap<NuccStructReference, u32>,
+) -> XfbinResult<NuccUnknown> {
+    let codec = by_type(nucc_struct.as_any().type_id())?;
+    let struct_info = nucc_struct.struct_info().clone();
+
+    let chunk = codec.to_chunk(NuccChunkConverter {
+        nucc_struct: codec.clone_struct(nucc_struct),
+        struct_info_map,
+        struct_reference_map,
+    })?;
+
+    Ok(NuccUnknown {
+        version: chunk.version(),
+        data: codec.encode(chunk)?.into(),
+        chunk_type: struct_info.chunk_type.clone(),
+        struct_info,
+    })
+}
+
+/// Whether `nucc_struct` belongs to the codec registered for `chunk_type`
+pub(crate) fn handles(chunk_type: &str, nucc_struct: &dyn NuccStruct) -> bool {
+    by_name(chunk_type).is_some_and(|codec| codec.handles(nucc_struct))
+}
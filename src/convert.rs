@@ -0,0 +1,369 @@
+//! # Track conversion
+//! Re-encoding of anm tracks in another key format. The source track is sampled on every frame and the samples
+//! are encoded in the target format, quantizing them for the short formats; Euler rotations can be turned into
+//! quaternions along the way. Each conversion reports the largest error it introduced, so formats can be picked
+//! per track by how much precision they cost.
+//! Dense tracks can also be reduced to linear keys on only the frames needed to stay within a tolerance.
+//!
+use super::error::{XfbinError, XfbinResult};
+use super::nucc::NuccAnm;
+use super::nucc_chunk::nucc_chunk_anm::{AnmTrack, NuccAnmKey, NuccAnmKeyFormat};
+use super::sample::{timing, Timing, TrackValue, OPACITY_SHORT_SCALE, QUATERNION_SHORT_SCALE, SCALE_SHORT_SCALE};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackConversion {
+    pub track: AnmTrack,
+
+    /// The largest difference of any component between the source and the converted track, or the largest angle
+    /// between their rotations in radians for quaternions, sampled on every frame and halfway between frames
+    pub max_error: f32,
+}
+
+/// How far a reduced track may stray from its source
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// The largest difference of any component of locations, scales, colors and other values
+    pub value: f32,
+
+    /// The largest angle between rotations, in radians
+    pub angle: f32,
+}
+
+/// What the keys of a format describe, which decides the formats it can be converted to
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ValueKind {
+    Float,
+    Vec2,
+    Vec3,
+
+    /// Stored as `Vec3`, in radians with X applied first, but can also become a quaternion
+    Euler,
+    Quaternion,
+}
+
+fn value_kind(key_format: &NuccAnmKeyFormat) -> Option<ValueKind> {
+    let kind = match key_format {
+        NuccAnmKeyFormat::FloatFixed
+        | NuccAnmKeyFormat::FloatLinear
+        | NuccAnmKeyFormat::FloatTable
+        | NuccAnmKeyFormat::FloatTableNoInterp
+        | NuccAnmKeyFormat::OpacityShortTable
+        | NuccAnmKeyFormat::OpacityShortTableNoInterp => ValueKind::Float,
+        NuccAnmKeyFormat::Vector2Fixed | NuccAnmKeyFormat::Vector2Linear => ValueKind::Vec2,
+        NuccAnmKeyFormat::Vector3Fixed
+        | NuccAnmKeyFormat::Vector3Linear
+        | NuccAnmKeyFormat::Vector3Bezier
+        | NuccAnmKeyFormat::Vector3Table
+        | NuccAnmKeyFormat::Vector3TableNoInterp
+        | NuccAnmKeyFormat::ScaleShortTable
+        | NuccAnmKeyFormat::ColorRGBTable => ValueKind::Vec3,
+        NuccAnmKeyFormat::EulerXYZFixed | NuccAnmKeyFormat::EulerInterpolated => ValueKind::Euler,
+        NuccAnmKeyFormat::QuaternionLinear
+        | NuccAnmKeyFormat::QuaternionTable
+        | NuccAnmKeyFormat::QuaternionShortTable
+        | NuccAnmKeyFormat::QuaternionShortTableNoInterp => ValueKind::Quaternion,
+        // The fixed point scale of this format isn't known, so its values can't be converted either way
+        NuccAnmKeyFormat::Vector3ShortLinear | NuccAnmKeyFormat::Unknown => return None,
+    };
+
+    Some(kind)
+}
+
+impl AnmTrack {
+    /// The track re-encoded in `key_format`, with a key on each of the first `frame_count` frames
+    /// (or a single one for fixed formats) and key frames counted in `frame_size` ticks per frame.
+    /// Keyed formats also get the key on frame -1 that ends their tracks.
+    /// Tracks convert between formats of the same kind of value, and from Euler rotations to quaternions.
+    /// `Vector3ShortLinear` stores fixed point values of an unknown scale, so it doesn't convert to or from anything.
+    pub fn convert(&self, key_format: NuccAnmKeyFormat, frame_count: usize, frame_size: u32) -> XfbinResult<TrackConversion> {
+        let unsupported = || XfbinError::UnsupportedConversion {
+            from: self.key_format.clone(),
+            to: key_format.clone(),
+        };
+
+        let (from_kind, to_kind) = value_kind(&self.key_format).zip(value_kind(&key_format)).ok_or_else(unsupported)?;
+
+        let to_quaternion = match (from_kind, to_kind) {
+            (ValueKind::Euler, ValueKind::Quaternion) => true,
+            (ValueKind::Euler, ValueKind::Vec3) | (ValueKind::Vec3, ValueKind::Euler) => false,
+            (from_kind, to_kind) if from_kind == to_kind => false,
+            _ => return Err(unsupported()),
+        };
+
+        // The source as the target format sees it
+        let sample_source = |frame: f32| {
+            self.sample(frame, frame_size).map(|value| match value {
+                TrackValue::Vec3(euler) if to_quaternion => TrackValue::Quaternion(euler_to_quaternion(euler)),
+                value => value,
+            })
+        };
+
+        let key_count = match key_format {
+            NuccAnmKeyFormat::Vector3Fixed
+            | NuccAnmKeyFormat::EulerXYZFixed
+            | NuccAnmKeyFormat::FloatFixed
+            | NuccAnmKeyFormat::Vector2Fixed => 1,
+            _ => frame_count,
+        };
+
+        let keys = encode_keys(
+            &key_format,
+            (0..key_count).map_while(|frame| Some((frame as i32 * frame_size.max(1) as i32, sample_source(frame as f32)?))),
+        );
+
+        let track = AnmTrack {
+            track_index: self.track_index,
+            key_format,
+            keys,
+        };
+
+        Ok(TrackConversion::measure(track, sample_source, frame_count, frame_size))
+    }
+
+    /// The track as keys of the linear format for its kind of value, on only the frames needed for linear
+    /// interpolation between them to stay within `tolerance` of the first `frame_count` frames, and the end key.
+    /// Euler rotations have no linear format, so they can't be reduced.
+    pub fn reduce(&self, tolerance: &Tolerance, frame_count: usize, frame_size: u32) -> XfbinResult<TrackConversion> {
+        let (key_format, max_error) = match value_kind(&self.key_format) {
+            Some(ValueKind::Float) => (NuccAnmKeyFormat::FloatLinear, tolerance.value),
+            Some(ValueKind::Vec2) => (NuccAnmKeyFormat::Vector2Linear, tolerance.value),
+            Some(ValueKind::Vec3) => (NuccAnmKeyFormat::Vector3Linear, tolerance.value),
+            Some(ValueKind::Quaternion) => (NuccAnmKeyFormat::QuaternionLinear, tolerance.angle),
+            Some(ValueKind::Euler) | None => {
+                return Err(XfbinError::UnsupportedConversion {
+                    from: self.key_format.clone(),
+                    to: NuccAnmKeyFormat::Vector3Linear,
+                })
+            }
+        };
+
+        let samples = (0..frame_count)
+            .map_while(|frame| self.sample(frame as f32, frame_size))
+            .collect::<Vec<TrackValue>>();
+
+        // Whether a segment between the samples at `start` and `end` stays close enough to the ones in between
+        let fits = |start: usize, end: usize| {
+            (start + 1..end).all(|frame| {
+                let t = (frame - start) as f32 / (end - start) as f32;
+                value_error(&samples[frame], &samples[start].interpolate(&samples[end], t)) <= max_error
+            })
+        };
+
+        // Each key is followed by the furthest one the segment to it still fits
+        let mut kept = Vec::new();
+        let mut start = 0;
+
+        if !samples.is_empty() {
+            kept.push(start);
+        }
+
+        while start + 1 < samples.len() {
+            let mut end = start + 1;
+
+            while end + 1 < samples.len() && fits(start, end + 1) {
+                end += 1;
+            }
+
+            kept.push(end);
+            start = end;
+        }
+
+        let keys = encode_keys(
+            &key_format,
+            kept.into_iter().map(|frame| (frame as i32 * frame_size.max(1) as i32, samples[frame])),
+        );
+
+        let track = AnmTrack {
+            track_index: self.track_index,
+            key_format,
+            keys,
+        };
+
+        Ok(TrackConversion::measure(track, |frame| self.sample(frame, frame_size), frame_count, frame_size))
+    }
+}
+
+impl TrackConversion {
+    fn measure(
+        track: AnmTrack,
+        sample_source: impl Fn(f32) -> Option<TrackValue>,
+        frame_count: usize,
+        frame_size: u32,
+    ) -> Self {
+        let max_error = (0..(frame_count * 2).saturating_sub(1))
+            .map(|half_frame| half_frame as f32 / 2.0)
+            .filter_map(|frame| Some(value_error(&sample_source(frame)?, &track.sample(frame, frame_size)?)))
+            .fold(0.0, f32::max);
+
+        Self { track, max_error }
+    }
+}
+
+impl NuccAnm {
+    /// `track` re-encoded in `key_format` over the whole length of the animation
+    pub fn convert_track(&self, track: &AnmTrack, key_format: NuccAnmKeyFormat) -> XfbinResult<TrackConversion> {
+        // Both ends of the animation get a key
+        let frame_count = self.length().ceil() as usize + 1;
+
+        track.convert(key_format, frame_count, self.frame_size)
+    }
+
+    /// `track` reduced to linear keys over the whole length of the animation
+    pub fn reduce_track(&self, track: &AnmTrack, tolerance: &Tolerance) -> XfbinResult<TrackConversion> {
+        track.reduce(tolerance, self.length().ceil() as usize + 1, self.frame_size)
+    }
+
+    /// Reduce the interpolated float table tracks of every entry, keeping the reduced track wherever it's smaller.
+    /// Short and color tables are left alone, as the linear formats would store their values as floats instead.
+    /// Returns how many tracks were replaced.
+    pub fn reduce_tables(&mut self, tolerance: &Tolerance) -> usize {
+        let frame_count = self.length().ceil() as usize + 1;
+        let frame_size = self.frame_size;
+        let mut replaced = 0;
+
+        for track in self.entries.iter_mut().flat_map(|entry| &mut entry.tracks) {
+            if !matches!(
+                track.key_format,
+                NuccAnmKeyFormat::FloatTable | NuccAnmKeyFormat::Vector3Table | NuccAnmKeyFormat::QuaternionTable
+            ) {
+                continue;
+            }
+
+            let Ok(reduced) = track.reduce(tolerance, frame_count, frame_size) else {
+                continue;
+            };
+
+            if track_size(&reduced.track) < track_size(track) {
+                *track = reduced.track;
+                replaced += 1;
+            }
+        }
+
+        replaced
+    }
+}
+
+fn track_size(track: &AnmTrack) -> usize {
+    track.key_format.size_per_frame() * track.keys.len()
+}
+
+/// `values` as keys of `key_format` at their frames in ticks, followed by the key on frame -1 that ends keyed tracks
+fn encode_keys(key_format: &NuccAnmKeyFormat, values: impl IntoIterator<Item = (i32, TrackValue)>) -> Vec<NuccAnmKey> {
+    let mut last = None;
+    let mut keys = values
+        .into_iter()
+        .map(|(frame, value)| {
+            last = Some(value);
+            encode_key(key_format, value, frame)
+        })
+        .collect::<Vec<NuccAnmKey>>();
+
+    if let (Some(Timing::Keyed { .. }), Some(value)) = (timing(key_format), last) {
+        keys.push(encode_key(key_format, value, -1));
+    }
+
+    keys
+}
+
+/// `value` as a key of `key_format`, at `frame` ticks for the formats that store it.
+/// `value` has to be of the kind `key_format` stores.
+fn encode_key(key_format: &NuccAnmKeyFormat, value: TrackValue, frame: i32) -> NuccAnmKey {
+    let values = value.components();
+    let short = |scale: f32| -> [i16; 4] {
+        std::array::from_fn(|i| {
+            let value = values.get(i).copied().unwrap_or_default() * scale;
+            value.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16
+        })
+    };
+    let float = |i: usize| values.get(i).copied().unwrap_or_default();
+
+    match key_format {
+        NuccAnmKeyFormat::FloatFixed | NuccAnmKeyFormat::FloatTable | NuccAnmKeyFormat::FloatTableNoInterp => {
+            NuccAnmKey::Float { values: float(0) }
+        }
+        NuccAnmKeyFormat::FloatLinear => NuccAnmKey::FloatLinear { frame, values: float(0) },
+        NuccAnmKeyFormat::OpacityShortTable | NuccAnmKeyFormat::OpacityShortTableNoInterp => {
+            NuccAnmKey::I16Vec { values: short(OPACITY_SHORT_SCALE)[0] }
+        }
+        NuccAnmKeyFormat::Vector2Fixed => NuccAnmKey::Vec2 { values: (float(0), float(1)) },
+        NuccAnmKeyFormat::Vector2Linear => NuccAnmKey::Vec2Linear {
+            frame,
+            values: (float(0), float(1)),
+        },
+        NuccAnmKeyFormat::Vector3Fixed
+        | NuccAnmKeyFormat::EulerXYZFixed
+        | NuccAnmKeyFormat::EulerInterpolated
+        | NuccAnmKeyFormat::Vector3Table
+        | NuccAnmKeyFormat::Vector3TableNoInterp => NuccAnmKey::Vec3 {
+            values: (float(0), float(1), float(2)),
+        },
+        NuccAnmKeyFormat::Vector3Linear | NuccAnmKeyFormat::Vector3Bezier => NuccAnmKey::Vec3Linear {
+            frame,
+            values: (float(0), float(1), float(2)),
+        },
+        NuccAnmKeyFormat::ScaleShortTable => {
+            let [x, y, z, _] = short(SCALE_SHORT_SCALE);
+            NuccAnmKey::I16Vec3 { values: (x, y, z) }
+        }
+        NuccAnmKeyFormat::ColorRGBTable => {
+            let [r, g, b] = [0, 1, 2].map(|i| (float(i) * 255.0).round().clamp(0.0, 255.0) as u8);
+            NuccAnmKey::Color { values: (r, g, b) }
+        }
+        NuccAnmKeyFormat::QuaternionTable => NuccAnmKey::Vec4 {
+            values: (float(0), float(1), float(2), float(3)),
+        },
+        NuccAnmKeyFormat::QuaternionLinear => NuccAnmKey::Vec4Linear {
+            frame,
+            values: (float(0), float(1), float(2), float(3)),
+        },
+        NuccAnmKeyFormat::QuaternionShortTable | NuccAnmKeyFormat::QuaternionShortTableNoInterp => {
+            let [x, y, z, w] = short(QUATERNION_SHORT_SCALE);
+            NuccAnmKey::ShortVec4 { values: (x, y, z, w) }
+        }
+        NuccAnmKeyFormat::Vector3ShortLinear | NuccAnmKeyFormat::Unknown => NuccAnmKey::Unknown {},
+    }
+}
+
+/// The quaternion (x, y, z, w) of a rotation about X, then Y, then Z
+fn euler_to_quaternion([x, y, z]: [f32; 3]) -> [f32; 4] {
+    let (sin_x, cos_x) = (x / 2.0).sin_cos();
+    let (sin_y, cos_y) = (y / 2.0).sin_cos();
+    let (sin_z, cos_z) = (z / 2.0).sin_cos();
+
+    [
+        sin_x * cos_y * cos_z - cos_x * sin_y * sin_z,
+        cos_x * sin_y * cos_z + sin_x * cos_y * sin_z,
+        cos_x * cos_y * sin_z - sin_x * sin_y * cos_z,
+        cos_x * cos_y * cos_z + sin_x * sin_y * sin_z,
+    ]
+}
+
+/// The largest difference of any component, or the angle between the rotations for quaternions
+fn value_error(expected: &TrackValue, actual: &TrackValue) -> f32 {
+    match (expected, actual) {
+        (TrackValue::Quaternion(expected), TrackValue::Quaternion(actual)) => rotation_angle(expected, actual),
+        _ => expected
+            .components()
+            .iter()
+            .zip(actual.components())
+            .map(|(expected, actual)| (expected - actual).abs())
+            .fold(0.0, f32::max),
+    }
+}
+
+/// The angle between two rotations in radians, from the distance between their unit quaternions,
+/// which stays precise for the small angles tolerances are about
+fn rotation_angle(from: &[f32; 4], to: &[f32; 4]) -> f32 {
+    let normalize = |q: &[f32; 4]| {
+        let length = q.iter().map(|value| value * value).sum::<f32>().sqrt();
+        q.map(|value| if length > 0.0 { value / length } else { value })
+    };
+
+    let (from, to) = (normalize(from), normalize(to));
+
+    // q and -q are the same rotation, so measure to whichever is closer
+    let sign = if (0..4).map(|i| from[i] * to[i]).sum::<f32>() < 0.0 { -1.0 } else { 1.0 };
+    let distance = (0..4).map(|i| (from[i] - to[i] * sign).powi(2)).sum::<f32>().sqrt();
+
+    4.0 * (distance / 2.0).min(1.0).asin()
+}
@@ -0,0 +1,501 @@
+//! # Diff
+//! Structural comparison of two documents. Structs are matched by their `NuccStructInfo`;
+//! matched structs are compared by their encoded payload, and anms field by field down to individual keys.
+//! The result can be inspected directly, or rendered as text with `Display`.
+//!
+use indexmap::{IndexMap, IndexSet};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::fmt;
+
+use super::error::XfbinResult;
+use super::nucc::{NuccAnm, NuccChunkConverter, NuccStruct, NuccStructInfo};
+use super::nucc_chunk::nucc_chunk_anm::{AnmCoord, AnmEntry, AnmTrack, EntryFormat, NuccAnmKey, NuccAnmKeyFormat};
+use super::nucc_chunk::{NuccChunk, NuccChunkType};
+use super::xfbin::{IndexTable, Xfbin, XfbinPage};
+
+/// Keys listed per track when rendering as text; the diff itself keeps all of them
+const RENDERED_KEYS: usize = 8;
+
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl DiffKind {
+    fn marker(&self) -> char {
+        match self {
+            Self::Added => '+',
+            Self::Removed => '-',
+            Self::Modified => '~',
+        }
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone, Default)]
+pub struct XfbinDiff {
+    pub version: Option<(u16, u16)>,
+    pub string_tables: Vec<StringTableDiff>,
+    pub structs: Vec<StructDiff>,
+}
+
+/// A string that's only in one of the files' chunk type, filepath or chunk name tables
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone)]
+pub struct StringTableDiff {
+    pub table: String,
+    pub kind: DiffKind,
+    pub value: String,
+}
+
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone)]
+pub struct StructDiff {
+    pub kind: DiffKind,
+    pub struct_info: NuccStructInfo,
+
+    /// Changed fields of a modified struct
+    pub fields: Vec<FieldDiff>,
+
+    /// Changed entries of a modified anm
+    pub entries: Vec<EntryDiff>,
+}
+
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone)]
+pub struct FieldDiff {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// Anm entries are matched by their coord
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone)]
+pub struct EntryDiff {
+    pub kind: DiffKind,
+    pub coord: AnmCoord,
+    pub entry_format: Option<(EntryFormat, EntryFormat)>,
+    pub tracks: Vec<TrackDiff>,
+}
+
+/// Tracks are matched by the track index in their header
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone)]
+pub struct TrackDiff {
+    pub kind: DiffKind,
+    pub track_index: u16,
+    pub key_format: Option<(NuccAnmKeyFormat, NuccAnmKeyFormat)>,
+
+    /// Keys that differ, are missing or were added, by position in the track
+    pub keys: Vec<KeyDiff>,
+}
+
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone)]
+pub struct KeyDiff {
+    pub key_index: usize,
+    pub old: Option<NuccAnmKey>,
+    pub new: Option<NuccAnmKey>,
+}
+
+impl XfbinDiff {
+    pub fn is_empty(&self) -> bool {
+        self.version.is_none() && self.string_tables.is_empty() && self.structs.is_empty()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl XfbinDiff {
+    #[pyo3(name = "is_empty")]
+    fn py_is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "XfbinDiff(version={:?}, string_tables={}, structs={})",
+            self.version,
+            self.string_tables.len(),
+            self.structs.len()
+        )
+    }
+
+    fn __str__(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Xfbin {
+    /// Compare this document, as the old side, against `other`
+    pub fn diff(&self, other: &Xfbin) -> XfbinResult<XfbinDiff> {
+        let mut diff = XfbinDiff {
+            version: (self.version != other.version).then_some((self.version, other.version)),
+            ..Default::default()
+        };
+
+        let old_tables = string_tables(self);
+        let new_tables = string_tables(other);
+        for ((table, old_values), (_, new_values)) in old_tables.iter().zip(&new_tables) {
+            let removed = old_values.difference(new_values).map(|value| (DiffKind::Removed, value));
+            let added = new_values.difference(old_values).map(|value| (DiffKind::Added, value));
+
+            diff.string_tables.extend(removed.chain(added).map(|(kind, value)| StringTableDiff {
+                table: table.to_string(),
+                kind,
+                value: value.clone(),
+            }));
+        }
+
+        let old_structs = structs_by_info(self);
+        let new_structs = structs_by_info(other);
+
+        for (struct_info, old_struct) in &old_structs {
+            match new_structs.get(struct_info) {
+                Some(new_struct) => {
+                    if let Some(struct_diff) = diff_struct(old_struct, new_struct)? {
+                        diff.structs.push(struct_diff);
+                    }
+                }
+                None => diff.structs.push(StructDiff::new(DiffKind::Removed, struct_info)),
+            }
+        }
+
+        for struct_info in new_structs.keys().filter(|struct_info| !old_structs.contains_key(*struct_info)) {
+            diff.structs.push(StructDiff::new(DiffKind::Added, struct_info));
+        }
+
+        Ok(diff)
+    }
+}
+
+impl StructDiff {
+    fn new(kind: DiffKind, struct_info: &NuccStructInfo) -> Self {
+        Self {
+            kind,
+            struct_info: struct_info.clone(),
+            fields: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl FieldDiff {
+    fn compare<T: PartialEq + fmt::Debug>(fields: &mut Vec<FieldDiff>, field: &str, old: &T, new: &T) {
+        if old != new {
+            fields.push(Self {
+                field: field.to_string(),
+                old: format!("{:?}", old),
+                new: format!("{:?}", new),
+            });
+        }
+    }
+
+    /// Compare lists, but only describe them by length
+    fn compare_list<T: PartialEq>(fields: &mut Vec<FieldDiff>, field: &str, old: &[T], new: &[T]) {
+        if old != new {
+            fields.push(Self {
+                field: field.to_string(),
+                old: format!("{} items", old.len()),
+                new: format!("{} items", new.len()),
+            });
+        }
+    }
+}
+
+/// The strings each of the file's string tables would hold, as the writer builds them
+fn string_tables(xfbin: &Xfbin) -> [(&'static str, IndexSet<String>); 3] {
+    let mut chunk_types = IndexSet::new();
+    let mut filepaths = IndexSet::new();
+    let mut chunk_names = IndexSet::new();
+
+    for page in &xfbin.pages {
+        let struct_infos = page
+            .struct_infos
+            .iter()
+            .chain(page.structs.iter().map(|nucc_struct| nucc_struct.struct_info()))
+            .chain(page.struct_references.iter().map(|reference| &reference.struct_info));
+
+        for struct_info in struct_infos {
+            chunk_types.insert(struct_info.chunk_type.clone());
+            filepaths.insert(struct_info.filepath.clone());
+            chunk_names.insert(struct_info.chunk_name.clone());
+        }
+
+        chunk_names.extend(page.struct_references.iter().map(|reference| reference.chunk_name.clone()));
+    }
+
+    [("chunk type", chunk_types), ("filepath", filepaths), ("chunk name", chunk_names)]
+}
+
+/// A struct, and the page it's written in
+type PageStruct<'a> = (&'a dyn NuccStruct, &'a XfbinPage);
+
+/// Structs keyed by struct info; only the first of several structs sharing one is compared
+fn structs_by_info(xfbin: &Xfbin) -> IndexMap<&NuccStructInfo, PageStruct<'_>> {
+    let mut structs = IndexMap::new();
+
+    for page in &xfbin.pages {
+        for nucc_struct in &page.structs {
+            structs.entry(nucc_struct.struct_info()).or_insert((nucc_struct.as_ref(), page));
+        }
+    }
+
+    structs
+}
+
+fn diff_struct(&(old, old_page): &PageStruct, &(new, new_page): &PageStruct) -> XfbinResult<Option<StructDiff>> {
+    let mut struct_diff = StructDiff::new(DiffKind::Modified, old.struct_info());
+
+    FieldDiff::compare(&mut struct_diff.fields, "type", &old.chunk_type(), &new.chunk_type());
+    FieldDiff::compare(&mut struct_diff.fields, "version", &old.version(), &new.version());
+
+    match (old.downcast_ref::<NuccAnm>(), new.downcast_ref::<NuccAnm>()) {
+        (Some(old), Some(new)) => diff_anm(old, new, &mut struct_diff),
+        _ => {
+            let old_payload = encode_payload(old, old_page)?;
+            let new_payload = encode_payload(new, new_page)?;
+
+            if old_payload != new_payload {
+                let first_difference = old_payload
+                    .iter()
+                    .zip(&new_payload)
+                    .position(|(old, new)| old != new)
+                    .unwrap_or(old_payload.len().min(new_payload.len()));
+
+                struct_diff.fields.push(FieldDiff {
+                    field: "payload".to_string(),
+                    old: format!("{} bytes", old_payload.len()),
+                    new: format!("{} bytes, differing from offset {:#x}", new_payload.len(), first_difference),
+                });
+            }
+        }
+    }
+
+    let modified = !struct_diff.fields.is_empty() || !struct_diff.entries.is_empty();
+
+    Ok(modified.then_some(struct_diff))
+}
+
+/// Encode a struct with its page's chunk maps and references, so structs that point at other structs
+/// are compared by the indices the writer would give them
+fn encode_payload(nucc_struct: &dyn NuccStruct, page: &XfbinPage) -> XfbinResult<Vec<u8>> {
+    let mut struct_infos = IndexTable::from_entries(page.struct_infos.clone());
+    for page_struct in &page.structs {
+        struct_infos.index_of(page_struct.struct_info());
+    }
+
+    let struct_references = IndexTable::from_entries(page.struct_references.clone());
+
+    let boxed = Box::<dyn NuccChunk>::try_from(NuccChunkConverter {
+        nucc_struct: nucc_struct.clone_boxed(),
+        struct_info_map: struct_infos.indices().clone(),
+        struct_reference_map: struct_references.indices().clone(),
+    })?;
+
+    NuccChunkType::write_data(boxed)
+}
+
+fn diff_anm(old: &NuccAnm, new: &NuccAnm, struct_diff: &mut StructDiff) {
+    let fields = &mut struct_diff.fields;
+
+    FieldDiff::compare(fields, "frame_count", &old.frame_count, &new.frame_count);
+    FieldDiff::compare(fields, "frame_size", &old.frame_size, &new.frame_size);
+    FieldDiff::compare(fields, "is_looped", &old.is_looped, &new.is_looped);
+    FieldDiff::compare_list(fields, "other_entries_indices", &old.other_entries_indices, &new.other_entries_indices);
+    FieldDiff::compare_list(fields, "unk_entry_indices", &old.unk_entry_indices, &new.unk_entry_indices);
+    FieldDiff::compare_list(fields, "clumps", &old.clumps, &new.clumps);
+    FieldDiff::compare_list(fields, "coord_parents", &old.coord_parents, &new.coord_parents);
+
+    let old_entries = entries_by_coord(&old.entries);
+    let new_entries = entries_by_coord(&new.entries);
+
+    for (coord, old_entry) in &old_entries {
+        let entry_diff = match new_entries.get(coord) {
+            Some(new_entry) => diff_entry(old_entry, new_entry),
+            None => Some(EntryDiff {
+                kind: DiffKind::Removed,
+                coord: old_entry.coord.clone(),
+                entry_format: None,
+                tracks: Vec::new(),
+            }),
+        };
+
+        struct_diff.entries.extend(entry_diff);
+    }
+
+    for (_, new_entry) in new_entries.iter().filter(|(coord, _)| !old_entries.contains_key(*coord)) {
+        struct_diff.entries.push(EntryDiff {
+            kind: DiffKind::Added,
+            coord: new_entry.coord.clone(),
+            entry_format: None,
+            tracks: Vec::new(),
+        });
+    }
+}
+
+fn entries_by_coord(entries: &[AnmEntry]) -> IndexMap<(i16, u16), &AnmEntry> {
+    let mut by_coord = IndexMap::new();
+
+    for entry in entries {
+        by_coord.entry((entry.coord.clump_index, entry.coord.coord_index)).or_insert(entry);
+    }
+
+    by_coord
+}
+
+fn diff_entry(old: &AnmEntry, new: &AnmEntry) -> Option<EntryDiff> {
+    let old_tracks = tracks_by_index(old);
+    let new_tracks = tracks_by_index(new);
+
+    let mut tracks = Vec::new();
+
+    for (track_index, old_track) in &old_tracks {
+        match new_tracks.get(track_index) {
+            Some(new_track) => tracks.extend(diff_track(*track_index, old_track, new_track)),
+            None => tracks.push(TrackDiff {
+                kind: DiffKind::Removed,
+                track_index: *track_index,
+                key_format: None,
+                keys: Vec::new(),
+            }),
+        }
+    }
+
+    for track_index in new_tracks.keys().filter(|track_index| !old_tracks.contains_key(*track_index)) {
+        tracks.push(TrackDiff {
+            kind: DiffKind::Added,
+            track_index: *track_index,
+            key_format: None,
+            keys: Vec::new(),
+        });
+    }
+
+    let entry_format = (old.entry_format != new.entry_format)
+        .then(|| (old.entry_format.clone(), new.entry_format.clone()));
+
+    (entry_format.is_some() || !tracks.is_empty()).then(|| EntryDiff {
+        kind: DiffKind::Modified,
+        coord: old.coord.clone(),
+        entry_format,
+        tracks,
+    })
+}
+
+fn tracks_by_index(entry: &AnmEntry) -> IndexMap<u16, &AnmTrack> {
+    entry.tracks.iter().map(|track| (track.track_index, track)).collect()
+}
+
+fn diff_track(track_index: u16, old_track: &AnmTrack, new_track: &AnmTrack) -> Option<TrackDiff> {
+    let key_format = (old_track.key_format != new_track.key_format)
+        .then(|| (old_track.key_format.clone(), new_track.key_format.clone()));
+
+    let key_count = old_track.keys.len().max(new_track.keys.len());
+    let keys = (0..key_count)
+        .filter_map(|key_index| {
+            let old = old_track.keys.get(key_index);
+            let new = new_track.keys.get(key_index);
+
+            (old != new).then(|| KeyDiff {
+                key_index,
+                old: old.cloned(),
+                new: new.cloned(),
+            })
+        })
+        .collect::<Vec<KeyDiff>>();
+
+    (key_format.is_some() || !keys.is_empty()).then_some(TrackDiff {
+        kind: DiffKind::Modified,
+        track_index,
+        key_format,
+        keys,
+    })
+}
+
+impl fmt::Display for XfbinDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((old, new)) = self.version {
+            writeln!(f, "~ version: {} -> {}", old, new)?;
+        }
+
+        for string in &self.string_tables {
+            writeln!(f, "{} {} \"{}\"", string.kind.marker(), string.table, string.value)?;
+        }
+
+        for struct_diff in &self.structs {
+            write!(f, "{}", struct_diff)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for StructDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let StructDiff { kind, struct_info, .. } = self;
+        writeln!(
+            f,
+            "{} {} ({}) {}",
+            kind.marker(),
+            struct_info.chunk_name,
+            struct_info.chunk_type,
+            struct_info.filepath
+        )?;
+
+        for field in &self.fields {
+            writeln!(f, "    {}: {} -> {}", field.field, field.old, field.new)?;
+        }
+
+        for entry in &self.entries {
+            write!(
+                f,
+                "    {} entry (clump {}, coord {})",
+                entry.kind.marker(),
+                entry.coord.clump_index,
+                entry.coord.coord_index
+            )?;
+
+            if let Some((old, new)) = &entry.entry_format {
+                write!(f, ", entry format {:?} -> {:?}", old, new)?;
+            }
+
+            writeln!(f)?;
+
+            for track in &entry.tracks {
+                write!(f, "        {} track {}", track.kind.marker(), track.track_index)?;
+
+                if let Some((old, new)) = &track.key_format {
+                    write!(f, ", key format {:?} -> {:?}", old, new)?;
+                }
+
+                if !track.keys.is_empty() {
+                    write!(f, ", {} keys differ", track.keys.len())?;
+                }
+
+                writeln!(f)?;
+
+                for key in track.keys.iter().take(RENDERED_KEYS) {
+                    writeln!(f, "            key {}: {} -> {}", key.key_index, render_key(&key.old), render_key(&key.new))?;
+                }
+
+                if track.keys.len() > RENDERED_KEYS {
+                    writeln!(f, "            ... and {} more", track.keys.len() - RENDERED_KEYS)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn render_key(key: &Option<NuccAnmKey>) -> String {
+    match key {
+        Some(key) => format!("{:?}", key),
+        None => "none".to_string(),
+    }
+}
@@ -0,0 +1,164 @@
+use thiserror::Error;
+
+use super::nucc::NuccStructInfo;
+use super::nucc_chunk::nucc_chunk_anm::{NuccAnmKey, NuccAnmKeyFormat};
+use super::nucc_chunk::NuccChunkType;
+
+pub type XfbinResult<T> = Result<T, XfbinError>;
+
+/// Every failure that can happen while reading or writing an xfbin.
+#[derive(Debug, Error)]
+pub enum XfbinError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("bad magic at offset {offset:#x}: expected \"NUCC\", found {found}")]
+    BadMagic { offset: u64, found: String },
+
+    #[error("parse error: {0}")]
+    Parse(binrw::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("{table} index {index} is out of range (length {len})")]
+    IndexOutOfRange {
+        table: &'static str,
+        index: usize,
+        len: usize,
+    },
+
+    #[error("failed to decode chunk {chunk_index} (name: \"{chunk_name}\", type: \"{chunk_type}\") at offset {offset:#x}: {source}")]
+    ChunkDecode {
+        chunk_index: usize,
+        chunk_name: String,
+        chunk_type: String,
+        offset: u64,
+        source: Box<XfbinError>,
+    },
+
+    #[error("failed to encode chunk (name: \"{chunk_name}\", type: \"{chunk_type}\"): {source}")]
+    ChunkEncode {
+        chunk_name: String,
+        chunk_type: String,
+        source: Box<XfbinError>,
+    },
+
+    #[error("unsupported chunk type: {0}")]
+    UnsupportedType(String),
+
+    #[error("expected a {expected} chunk, found {found}")]
+    TypeMismatch {
+        expected: NuccChunkType,
+        found: NuccChunkType,
+    },
+
+    #[error("entry {entry_index} track {track_index}: key {key_index} ({key:?}) can't be encoded as key format {key_format:?}")]
+    KeyFormatMismatch {
+        entry_index: usize,
+        track_index: usize,
+        key_index: usize,
+        key: NuccAnmKey,
+        key_format: NuccAnmKeyFormat,
+    },
+
+    #[error("JSON format version {found} isn't supported; versions 1 to {latest} can be read")]
+    UnsupportedFormatVersion { found: u32, latest: u32 },
+
+    #[error("entry {entry_index} track {track_index}: {key_count} keys of key format {key_format:?} don't fit in a track header")]
+    TrackTooLarge {
+        entry_index: usize,
+        track_index: usize,
+        key_count: usize,
+        key_format: NuccAnmKeyFormat,
+    },
+
+    #[error("can't convert a track from key format {from:?} to {to:?}")]
+    UnsupportedConversion {
+        from: NuccAnmKeyFormat,
+        to: NuccAnmKeyFormat,
+    },
+
+    #[error("the file is encrypted (flag {flag:#06x}) and no payload transform is registered for it")]
+    MissingTransform { flag: u16 },
+
+    #[error("the file is encrypted (flag {flag:#06x}), so it has to be decoded in memory; open it with open_xfbin_mmap or XfbinReader::from_bytes")]
+    EncryptedStream { flag: u16 },
+
+    #[error("payload transform failed: {0}")]
+    Transform(String),
+
+    #[error("chunk codec failed: {0}")]
+    Codec(String),
+
+    #[error("invalid pattern \"{pattern}\": {message}")]
+    InvalidPattern { pattern: String, message: String },
+
+    #[error("merge conflict: overlay {overlay_index} has a struct (name: \"{chunk_name}\", type: \"{chunk_type}\") that is already in the base")]
+    MergeConflict {
+        overlay_index: usize,
+        chunk_name: String,
+        chunk_type: String,
+    },
+}
+
+impl XfbinError {
+    pub fn index_out_of_range(table: &'static str, index: usize, len: usize) -> Self {
+        Self::IndexOutOfRange { table, index, len }
+    }
+
+    pub fn merge_conflict(overlay_index: usize, struct_info: &NuccStructInfo) -> Self {
+        Self::MergeConflict {
+            overlay_index,
+            chunk_name: struct_info.chunk_name.clone(),
+            chunk_type: struct_info.chunk_type.clone(),
+        }
+    }
+
+    pub fn invalid_pattern(pattern: &str, error: impl std::fmt::Display) -> Self {
+        Self::InvalidPattern {
+            pattern: pattern.to_string(),
+            message: error.to_string(),
+        }
+    }
+
+    pub fn chunk_decode(chunk_index: usize, struct_info: &NuccStructInfo, offset: u64, source: XfbinError) -> Self {
+        Self::ChunkDecode {
+            chunk_index,
+            chunk_name: struct_info.chunk_name.clone(),
+            chunk_type: struct_info.chunk_type.clone(),
+            offset,
+            source: Box::new(source),
+        }
+    }
+
+    pub fn chunk_encode(struct_info: &NuccStructInfo, source: XfbinError) -> Self {
+        Self::ChunkEncode {
+            chunk_name: struct_info.chunk_name.clone(),
+            chunk_type: struct_info.chunk_type.clone(),
+            source: Box::new(source),
+        }
+    }
+}
+
+impl From<binrw::Error> for XfbinError {
+    fn from(error: binrw::Error) -> Self {
+        match error {
+            binrw::Error::Io(error) => Self::Io(error),
+            binrw::Error::BadMagic { pos, found } => Self::BadMagic {
+                offset: pos,
+                found: format!("{:?}", found),
+            },
+            // Report the innermost error rather than the field backtrace binrw wraps it in
+            binrw::Error::Backtrace(backtrace) => Self::from(*backtrace.error),
+            error => Self::Parse(error),
+        }
+    }
+}
+
+/// Look up `index` in `items`, reporting which table was indexed if it is out of range.
+pub(crate) fn get_indexed<'a, T>(table: &'static str, items: &'a [T], index: usize) -> XfbinResult<&'a T> {
+    items
+        .get(index)
+        .ok_or_else(|| XfbinError::index_out_of_range(table, index, items.len()))
+}
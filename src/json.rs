@@ -0,0 +1,351 @@
+//! # JSON
+//! A stable JSON form of a document, for reviewing edits in version control or making them in a text editor.
+//! Every page, struct, anm entry, track and key is written out field by field, and importing it gives back the same document.
+//! Raw payloads are embedded as base64, or written to sidecar files next to the JSON when using `PayloadMode::Sidecar`.
+//!
+use base64::{engine::general_purpose::STANDARD, Engine};
+use indexmap::IndexMap;
+#[cfg(feature = "python")]
+use pyo3::pyclass;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::hash::Hash;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+use super::codec;
+use super::error::{XfbinError, XfbinResult};
+use super::nucc::*;
+use super::nucc_chunk::NuccChunkType;
+use super::nucc::vtable::vtable;
+use super::xfbin::{Xfbin, XfbinLayout, XfbinPage};
+
+/// Bumped whenever the schema changes in a way older readers can't follow
+pub const JSON_FORMAT_VERSION: u32 = 2;
+
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PayloadMode {
+    /// Base64 strings inside the JSON
+    #[default]
+    Inline,
+
+    /// Separate files in a `<name>.payloads` directory next to the JSON, referenced by relative path
+    Sidecar,
+}
+
+/// Serde adapter for raw payload fields, which are written as base64 strings
+pub(crate) mod payload {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer, T: AsRef<[u8]>>(data: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(data))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: From<Vec<u8>>>(deserializer: D) -> Result<T, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+
+        STANDARD
+            .decode(encoded)
+            .map(T::from)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct XfbinJson {
+    format_version: u32,
+    version: u16,
+    layout: Option<XfbinLayout>,
+    pages: Vec<XfbinPageJson>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct XfbinPageJson {
+    struct_infos: Vec<NuccStructInfo>,
+    struct_references: Vec<NuccStructReference>,
+    structs: Vec<NuccStructJson>,
+}
+
+/// A struct of any type, tagged with its chunk type under `"type"`
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct NuccStructJson(Value);
+
+impl TryFrom<&dyn NuccStruct> for NuccStructJson {
+    type Error = XfbinError;
+
+    fn try_from(nucc_struct: &dyn NuccStruct) -> XfbinResult<Self> {
+        Ok(Self((vtable(&nucc_struct.chunk_type())?.to_json)(nucc_struct)?))
+    }
+}
+
+impl TryFrom<NuccStructJson> for Box<dyn NuccStruct> {
+    type Error = XfbinError;
+
+    fn try_from(NuccStructJson(mut value): NuccStructJson) -> XfbinResult<Self> {
+        let tag = match value.as_object_mut().and_then(|fields| fields.remove("type")) {
+            Some(Value::String(tag)) => tag,
+            _ => return Err(json_error("expected a struct tagged with its chunk type")),
+        };
+
+        let chunk_type = NuccChunkType::from_str(&tag).map_err(|_| XfbinError::UnsupportedType(tag))?;
+        Ok((vtable(&chunk_type)?.from_json)(value)?)
+    }
+}
+
+fn to_value(xfbin: &Xfbin) -> XfbinResult<Value> {
+    let pages = xfbin
+        .pages
+        .iter()
+        .map(|page| {
+            Ok(XfbinPageJson {
+                struct_infos: page.struct_infos.clone(),
+                struct_references: page.struct_references.clone(),
+                structs: page
+                    .structs
+                    .iter()
+                    .map(|nucc_struct| match nucc_struct.chunk_type() {
+                        // Custom structs are kept in their encoded form, with references resolved against the page
+                        NuccChunkType::NuccChunkCustom => NuccStructJson::try_from(&codec::to_unknown(
+                            nucc_struct.as_ref(),
+                            first_indices(&page.struct_infos),
+                            first_indices(&page.struct_references),
+                        )? as &dyn NuccStruct),
+                        _ => NuccStructJson::try_from(nucc_struct.as_ref()),
+                    })
+                    .collect::<XfbinResult<Vec<_>>>()?,
+            })
+        })
+        .collect::<XfbinResult<Vec<_>>>()?;
+
+    Ok(serde_json::to_value(XfbinJson {
+        format_version: JSON_FORMAT_VERSION,
+        version: xfbin.version,
+        layout: xfbin.layout.clone(),
+        pages,
+    })?)
+}
+
+/// Each item mapped to the index of its first occurrence, like the writer's tables
+fn first_indices<T: Clone + Eq + Hash>(items: &[T]) -> IndexMap<T, u32> {
+    let mut indices = IndexMap::new();
+    for (index, item) in items.iter().enumerate() {
+        indices.entry(item.clone()).or_insert(index as u32);
+    }
+
+    indices
+}
+
+/// Fails for versions newer than this crate knows, which older ones can't be migrated from
+pub(crate) fn check_format_version(format_version: u32) -> XfbinResult<()> {
+    if !(1..=JSON_FORMAT_VERSION).contains(&format_version) {
+        return Err(XfbinError::UnsupportedFormatVersion {
+            found: format_version,
+            latest: JSON_FORMAT_VERSION,
+        });
+    }
+
+    Ok(())
+}
+
+/// Bring a struct written in `format_version` up to the current schema
+pub(crate) fn migrate_struct(nucc_struct: &mut Value, format_version: u32) {
+    if format_version >= 2 || nucc_struct["type"] != "nuccChunkAnm" {
+        return;
+    }
+
+    let Some(entries) = nucc_struct["entries"].as_array_mut() else {
+        return;
+    };
+
+    // Version 1 kept the headers next to the tracks; tracks now carry their own index and key format
+    for entry in entries.iter_mut().filter_map(Value::as_object_mut) {
+        let Some(Value::Array(track_headers)) = entry.remove("track_headers") else {
+            continue;
+        };
+
+        let Some(tracks) = entry.get_mut("tracks").and_then(Value::as_array_mut) else {
+            continue;
+        };
+
+        for (track_header, track) in track_headers.into_iter().zip(tracks.iter_mut().filter_map(Value::as_object_mut)) {
+            track.insert("track_index".to_string(), track_header["track_index"].clone());
+            track.insert("key_format".to_string(), track_header["key_format"].clone());
+        }
+    }
+}
+
+fn from_value(mut value: Value) -> XfbinResult<Xfbin> {
+    let format_version = value["format_version"].as_u64().unwrap_or_default() as u32;
+    check_format_version(format_version)?;
+
+    for_each_struct(&mut value, |_, _, nucc_struct| {
+        migrate_struct(nucc_struct, format_version);
+        Ok(())
+    })?;
+
+    let json = serde_json::from_value::<XfbinJson>(value)?;
+
+    let pages = json
+        .pages
+        .into_iter()
+        .map(|page| {
+            Ok(XfbinPage {
+                structs: page.structs.into_iter().map(Box::<dyn NuccStruct>::try_from).collect::<XfbinResult<_>>()?,
+                struct_infos: page.struct_infos,
+                struct_references: page.struct_references,
+            })
+        })
+        .collect::<XfbinResult<_>>()?;
+
+    Ok(Xfbin {
+        version: json.version,
+        pages,
+        layout: json.layout,
+    })
+}
+
+/// The document as pretty-printed JSON, with payloads inline
+pub fn to_json_string(xfbin: &Xfbin) -> XfbinResult<String> {
+    Ok(serde_json::to_string_pretty(&to_value(xfbin)?)?)
+}
+
+pub fn from_json_str(json: &str) -> XfbinResult<Xfbin> {
+    from_value(serde_json::from_str(json)?)
+}
+
+/// A single struct as pretty-printed JSON, tagged with its chunk type
+pub fn struct_to_json_string(nucc_struct: &dyn NuccStruct) -> XfbinResult<String> {
+    Ok(serde_json::to_string_pretty(&NuccStructJson::try_from(nucc_struct)?)?)
+}
+
+pub fn struct_from_json_str(json: &str) -> XfbinResult<Box<dyn NuccStruct>> {
+    serde_json::from_str::<NuccStructJson>(json)?.try_into()
+}
+
+/// A single struct written as part of a document in `format_version`
+pub(crate) fn struct_from_versioned_json_str(json: &str, format_version: u32) -> XfbinResult<Box<dyn NuccStruct>> {
+    let mut value = serde_json::from_str::<Value>(json)?;
+    migrate_struct(&mut value, format_version);
+
+    NuccStructJson(value).try_into()
+}
+
+pub(crate) fn write_json(xfbin: &Xfbin, filepath: &Path, payloads: PayloadMode) -> XfbinResult<()> {
+    let mut value = to_value(xfbin)?;
+
+    if payloads == PayloadMode::Sidecar {
+        let sidecar_dir = sidecar_dir(filepath);
+        fs::create_dir_all(&sidecar_dir)?;
+
+        for_each_payload(&mut value, |page_index, struct_index, nucc_struct| {
+            let chunk_name = nucc_struct["struct_info"]["chunk_name"].as_str().unwrap_or_default();
+            let name = format!("{:03}_{:03}_{}.bin", page_index, struct_index, sanitize(chunk_name));
+
+            let data = payload_bytes(&nucc_struct["data"])?;
+            fs::write(sidecar_dir.join(&name), data)?;
+
+            let relative = Path::new(sidecar_dir.file_name().unwrap_or_default()).join(name);
+            nucc_struct["data"] = Value::Object(Map::from_iter([(
+                "sidecar".to_string(),
+                Value::String(relative.to_string_lossy().replace('\\', "/")),
+            )]));
+
+            Ok(())
+        })?;
+    }
+
+    fs::write(filepath, serde_json::to_string_pretty(&value)?)?;
+
+    Ok(())
+}
+
+/// Read a document written by `write_json`, resolving sidecar payloads relative to the JSON file
+pub(crate) fn read_json(filepath: &Path) -> XfbinResult<Xfbin> {
+    let mut value = serde_json::from_slice::<Value>(&fs::read(filepath)?)?;
+    let base_dir = filepath.parent().map(Path::to_path_buf).unwrap_or_default();
+
+    for_each_payload(&mut value, |_, _, nucc_struct| {
+        if let Some(sidecar) = nucc_struct["data"]["sidecar"].as_str() {
+            // Sidecars are written next to the JSON, so anything reaching outside its directory isn't one of ours
+            let inside = Path::new(sidecar)
+                .components()
+                .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+
+            if !inside {
+                return Err(json_error(format!("sidecar path \"{}\" leaves the JSON file's directory", sidecar)));
+            }
+
+            let data = fs::read(base_dir.join(sidecar))?;
+            nucc_struct["data"] = Value::String(STANDARD.encode(data));
+        }
+
+        Ok(())
+    })?;
+
+    from_value(value)
+}
+
+fn sidecar_dir(filepath: &Path) -> PathBuf {
+    let stem = filepath.file_stem().unwrap_or_default().to_string_lossy();
+
+    filepath.with_file_name(format!("{}.payloads", stem))
+}
+
+/// Call `f` with every struct object that has a raw payload
+fn for_each_payload<F>(value: &mut Value, mut f: F) -> XfbinResult<()>
+where
+    F: FnMut(usize, usize, &mut Value) -> XfbinResult<()>,
+{
+    for_each_struct(value, |page_index, struct_index, nucc_struct| {
+        if nucc_struct.get("data").is_some() {
+            f(page_index, struct_index, nucc_struct)?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Call `f` with every struct object in the document
+fn for_each_struct<F>(value: &mut Value, mut f: F) -> XfbinResult<()>
+where
+    F: FnMut(usize, usize, &mut Value) -> XfbinResult<()>,
+{
+    let Some(pages) = value["pages"].as_array_mut() else {
+        return Ok(());
+    };
+
+    for (page_index, page) in pages.iter_mut().enumerate() {
+        let Some(structs) = page["structs"].as_array_mut() else {
+            continue;
+        };
+
+        for (struct_index, nucc_struct) in structs.iter_mut().enumerate() {
+            f(page_index, struct_index, nucc_struct)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn payload_bytes(value: &Value) -> XfbinResult<Vec<u8>> {
+    let encoded = value
+        .as_str()
+        .ok_or_else(|| json_error(format!("expected a base64 payload, found {}", value)))?;
+
+    STANDARD.decode(encoded).map_err(json_error)
+}
+
+fn json_error<T: std::fmt::Display>(message: T) -> XfbinError {
+    XfbinError::Json(serde::de::Error::custom(message))
+}
+
+/// `name` with anything that isn't safe in a file name replaced
+pub(crate) fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' })
+        .collect()
+}
@@ -1,23 +1,51 @@
+pub mod codec;
+pub mod convert;
+pub mod diff;
+pub mod error;
+pub mod json;
+pub mod merge;
 pub mod nucc;
 pub mod nucc_chunk;
+pub mod query;
+pub mod reference;
+pub mod sample;
+pub mod transform;
+pub mod unpack;
+pub mod validate;
 pub mod xfbin;
 pub mod xfbin_file;
-
-
-use pyo3::prelude::*;
-use pyo3::wrap_pyfunction;
-
-use binrw::{io::Cursor, BinReaderExt, BinWriterExt};
-use std::{fs, fs::File, io::Write};
-
-
-pub use xfbin::{Xfbin, XfbinPage};
+pub mod xfbin_reader;
+
+#[cfg(feature = "python")]
+pub mod python;
+
+
+use binrw::{io::Cursor, BinWriterExt};
+use bytes::Bytes;
+use memmap2::Mmap;
+use std::{fs, fs::File, io::{BufReader, Write}, path::Path};
+
+
+pub use codec::{register_codec, NuccChunkCodec};
+pub use convert::{Tolerance, TrackConversion};
+pub use diff::{DiffKind, XfbinDiff};
+pub use error::{XfbinError, XfbinResult};
+pub use json::{from_json_str, struct_from_json_str, struct_to_json_string, to_json_string, PayloadMode};
+pub use merge::{MergeAction, MergePolicy, MergeReport};
+pub use query::{Pattern, StructFilter};
+pub use reference::{ReferenceTarget, ResolvedReference};
+pub use sample::TrackValue;
+pub use transform::{register_transform, unregister_transform, PayloadTransform};
+pub use validate::{has_errors, Finding, Severity};
+pub use xfbin::{Xfbin, XfbinLayout, XfbinPage};
+pub use xfbin_reader::{XfbinChunkEntry, XfbinReader, XfbinSource};
 pub use nucc::{NuccStructInfo, NuccStructReference};
-pub use nucc::{NuccAnm, nucc_anm::Entry, nucc_anm::Track, NuccBinary, NuccCamera};
+pub use nucc::{NuccStruct, NuccAnm, NuccBinary, NuccCamera};
 
 pub use nucc_chunk::{
     NuccChunkAnm, nucc_chunk_anm::AnmClump, nucc_chunk_anm::AnmCoord, 
     nucc_chunk_anm::CoordParent, nucc_chunk_anm::EntryFormat,
+    nucc_chunk_anm::AnmEntry, nucc_chunk_anm::AnmTrack,
     nucc_chunk_anm::TrackHeader,
     nucc_chunk_anm::NuccAnmKeyFormat,
     nucc_chunk_anm::NuccAnmKey
@@ -26,33 +54,62 @@ pub use nucc_chunk::{
 use xfbin_file::XfbinFile;
 
 
-#[pyfunction]
-pub fn read_xfbin(filepath: &str) -> PyResult<Xfbin> {
+pub fn read_xfbin<P: AsRef<Path>>(filepath: P) -> XfbinResult<Xfbin> {
     let buffer = fs::read(filepath)?;
 
-    let xfbin = read_xfbin_buf(buffer);
+    read_xfbin_buf(buffer)
+}
 
-    Ok(xfbin.unwrap())
+pub fn read_xfbin_buf(buf: Vec<u8>) -> XfbinResult<Xfbin> {
+    Xfbin::try_from(read_xfbin_file(buf)?)
+}
+
+/// Parse a whole file, decoding its payload first if it's encrypted
+fn read_xfbin_file(buf: Vec<u8>) -> XfbinResult<XfbinFile> {
+    let (buf, encrypted) = transform::decode_file(buf.into())?;
+
+    let mut xfbin_file = XfbinFile::from_bytes(buf)?;
+    xfbin_file.header.encrypted = encrypted;
+
+    Ok(xfbin_file)
+}
 
+/// Open an xfbin for lazy reading; only the header and index are parsed until chunks are requested.
+/// Encrypted files are rejected with `XfbinError::EncryptedStream`; `open_xfbin_mmap` reads those.
+pub fn open_xfbin<P: AsRef<Path>>(filepath: P) -> XfbinResult<XfbinReader<BufReader<File>>> {
+    let file = File::open(filepath)?;
 
+    XfbinReader::new(BufReader::new(file))
 }
 
+/// Open an xfbin for lazy reading through a memory map.
+/// Raw payloads of the structs read from it are views into the mapped file rather than copies,
+/// except for encrypted files, which are decoded into memory first.
+pub fn open_xfbin_mmap<P: AsRef<Path>>(filepath: P) -> XfbinResult<XfbinReader<Cursor<Bytes>>> {
+    let file = File::open(filepath)?;
 
-#[pyfunction]
-pub fn read_xfbin_buf(buf: Vec<u8>) -> PyResult<Xfbin> {
-    let mut reader = std::io::Cursor::new(buf);
+    // Safety: the file must not be truncated or modified by another process while the map is alive
+    let mmap = unsafe { Mmap::map(&file)? };
 
-    let xfbin_file = reader
-        .read_be::<XfbinFile>().unwrap();
-        
+    XfbinReader::from_bytes(Bytes::from_owner(mmap))
+}
+
+pub fn read_xfbin_mmap<P: AsRef<Path>>(filepath: P) -> XfbinResult<Xfbin> {
+    open_xfbin_mmap(filepath)?.into_xfbin()
+}
 
-    Ok(xfbin_file.into())
+/// Check a file without loading it first, so files the readers reject can be diagnosed too
+pub fn validate_xfbin<P: AsRef<Path>>(filepath: P) -> XfbinResult<Vec<Finding>> {
+    let buffer = fs::read(filepath)?;
+
+    validate_xfbin_buf(buffer)
+}
 
-    
+pub fn validate_xfbin_buf(buf: Vec<u8>) -> XfbinResult<Vec<Finding>> {
+    Ok(validate::validate_xfbin_buf(buf))
 }
 
-#[pyfunction]
-pub fn write_xfbin(xfbin: Xfbin, filepath: &str) -> PyResult<()> {
+pub fn write_xfbin<P: AsRef<Path>>(xfbin: Xfbin, filepath: P) -> XfbinResult<()> {
     let buf = write_xfbin_buf(xfbin)?;
 
     let mut file = File::create(filepath)?;
@@ -60,46 +117,30 @@ pub fn write_xfbin(xfbin: Xfbin, filepath: &str) -> PyResult<()> {
     Ok(file.write_all(&buf)?)
 }
 
-#[pyfunction]
-pub fn write_xfbin_buf(xfbin: Xfbin) -> PyResult<Vec<u8>> {
+pub fn write_xfbin_buf(xfbin: Xfbin) -> XfbinResult<Vec<u8>> {
     let mut cursor = Cursor::new(Vec::new());
 
-    let xfbin_file = XfbinFile::from(xfbin.clone());
+    let xfbin_file = XfbinFile::try_from(xfbin)?;
 
-    cursor.write_be(&xfbin_file).unwrap();
+    cursor.write_be(&xfbin_file)?;
 
-    Ok(cursor.into_inner())
+    transform::encode_file(cursor.into_inner())
+}
 
-    
+/// Write `xfbin` as JSON, with raw payloads inline or in sidecar files next to `filepath`
+pub fn write_xfbin_json<P: AsRef<Path>>(xfbin: &Xfbin, filepath: P, payloads: PayloadMode) -> XfbinResult<()> {
+    json::write_json(xfbin, filepath.as_ref(), payloads)
+}
 
-    
+pub fn read_xfbin_json<P: AsRef<Path>>(filepath: P) -> XfbinResult<Xfbin> {
+    json::read_json(filepath.as_ref())
 }
 
-#[pymodule]
-fn xfbin_lib(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
-    m.add_function(wrap_pyfunction!(read_xfbin, m)?)?;
-    m.add_function(wrap_pyfunction!(read_xfbin_buf, m)?)?;
-    m.add_function(wrap_pyfunction!(write_xfbin, m)?)?;
-    m.add_function(wrap_pyfunction!(write_xfbin_buf, m)?)?;
-    m.add_class::<Xfbin>()?;
-    m.add_class::<XfbinPage>()?;
-    m.add_class::<NuccAnm>()?;
-    m.add_class::<NuccBinary>()?;
-    m.add_class::<NuccCamera>()?;
-    m.add_class::<NuccStructInfo>()?;
-    m.add_class::<NuccStructReference>()?;
-    m.add_class::<NuccChunkAnm>()?;
-    m.add_class::<AnmClump>()?;
-    m.add_class::<AnmCoord>()?;
-    m.add_class::<CoordParent>()?;
-    m.add_class::<EntryFormat>()?;
-    m.add_class::<Entry>()?;
-    m.add_class::<TrackHeader>()?;
-    m.add_class::<Track>()?;
-    m.add_class::<NuccAnmKeyFormat>()?;
-    m.add_class::<NuccAnmKey>()?;
-    
-
-    Ok(())
-   
-}
\ No newline at end of file
+/// Lay out `xfbin` as a directory tree with one folder per page and one file per struct
+pub fn unpack_to_dir<P: AsRef<Path>>(xfbin: &Xfbin, dirpath: P) -> XfbinResult<()> {
+    unpack::unpack(xfbin, dirpath.as_ref())
+}
+
+pub fn repack_from_dir<P: AsRef<Path>>(dirpath: P) -> XfbinResult<Xfbin> {
+    unpack::repack(dirpath.as_ref())
+}
@@ -0,0 +1,371 @@
+//! # xfbin
+//! Command-line access to the Rust API, for build scripts and anyone without Python.
+//! Exits with 0 on success, 1 when `validate` finds errors or `diff` finds differences, and 2 when a command fails.
+//!
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand};
+use itertools::Itertools;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use xfbin_lib::nucc::NuccUnknown;
+use xfbin_lib::*;
+
+#[derive(Parser)]
+#[command(name = "xfbin", version, about = "Inspect, edit and convert xfbin files")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print a summary of a file
+    Info { file: PathBuf },
+
+    /// List the structs of a file, one per line: page, index, chunk type, chunk name and filepath
+    List {
+        file: PathBuf,
+
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+
+    /// Unpack a file into a directory, or write a single struct with --name
+    Extract {
+        file: PathBuf,
+
+        /// Output directory, or output file with --name
+        output: PathBuf,
+
+        /// Write only the struct with this chunk name: raw bytes for binaries and unknown chunks, JSON otherwise
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Chunk type of the struct to extract, for names shared by several types
+        #[arg(long = "type", requires = "name")]
+        chunk_type: Option<String>,
+    },
+
+    /// Replace a struct with the contents of a file: raw bytes for binaries and unknown chunks, JSON otherwise
+    Replace {
+        file: PathBuf,
+
+        /// Chunk name of the struct to replace
+        name: String,
+        input: PathBuf,
+
+        /// Chunk type of the struct to replace, for names shared by several types
+        #[arg(long = "type")]
+        chunk_type: Option<String>,
+
+        /// Where to write the result; defaults to overwriting the file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Build a file from a directory written by `extract`
+    Repack { dir: PathBuf, output: PathBuf },
+
+    /// Print a file as JSON, or write it with --output
+    DumpJson {
+        file: PathBuf,
+
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Write raw payloads to files next to the output instead of inlining them as base64
+        #[arg(long, requires = "output")]
+        sidecar: bool,
+    },
+
+    /// Check a file for structural problems
+    Validate {
+        file: PathBuf,
+
+        /// Treat warnings as errors
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// Print the differences between two files
+    Diff { old: PathBuf, new: PathBuf },
+}
+
+#[derive(clap::Args)]
+struct FilterArgs {
+    /// Glob pattern for chunk names
+    #[arg(long)]
+    name: Option<String>,
+
+    /// Glob pattern for chunk types
+    #[arg(long = "type")]
+    chunk_type: Option<String>,
+
+    /// Glob pattern for filepaths
+    #[arg(long)]
+    path: Option<String>,
+}
+
+impl FilterArgs {
+    fn to_filter(&self) -> Result<StructFilter> {
+        let mut filter = StructFilter::new();
+
+        if let Some(name) = &self.name {
+            filter = filter.chunk_name(Pattern::glob(name)?);
+        }
+
+        if let Some(chunk_type) = &self.chunk_type {
+            filter = filter.chunk_type(Pattern::glob(chunk_type)?);
+        }
+
+        if let Some(path) = &self.path {
+            filter = filter.filepath(Pattern::glob(path)?);
+        }
+
+        Ok(filter)
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(exit_code) => exit_code,
+
+        // The reader went away, as with `xfbin dump-json file | head`
+        Err(e) if e.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe) => {
+            ExitCode::SUCCESS
+        }
+
+        Err(e) => {
+            eprintln!("error: {}", message(&e));
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// The error with its context, stopping at the first `XfbinError` since those already include their source
+fn message(error: &anyhow::Error) -> String {
+    let mut causes = Vec::new();
+
+    for cause in error.chain() {
+        causes.push(cause.to_string());
+
+        if cause.is::<XfbinError>() {
+            break;
+        }
+    }
+
+    causes.join(": ")
+}
+
+fn run(command: Command) -> Result<ExitCode> {
+    match command {
+        Command::Info { file } => info(&read(&file)?),
+        Command::List { file, filter } => list(&read(&file)?, &filter.to_filter()?),
+
+        Command::Extract {
+            file,
+            output,
+            name: None,
+            // clap rejects --type without --name
+            chunk_type: _,
+        } => unpack_to_dir(&read(&file)?, &output).with_context(|| format!("can't unpack to {}", output.display())),
+
+        Command::Extract {
+            file,
+            output,
+            name: Some(name),
+            chunk_type,
+        } => {
+            let xfbin = read(&file)?;
+            let nucc_struct = xfbin
+                .find(&exact_filter(&name, chunk_type.as_deref()))
+                .with_context(|| format!("no struct named \"{}\"", name))?;
+
+            fs::write(&output, payload_of(nucc_struct)?).with_context(|| format!("can't write {}", output.display()))
+        }
+
+        Command::Replace {
+            file,
+            name,
+            input,
+            chunk_type,
+            output,
+        } => {
+            let mut xfbin = read(&file)?;
+            replace(&mut xfbin, &exact_filter(&name, chunk_type.as_deref()), &input)?;
+
+            let output = output.unwrap_or(file);
+            write_xfbin(xfbin, &output).with_context(|| format!("can't write {}", output.display()))
+        }
+
+        Command::Repack { dir, output } => {
+            let xfbin = repack_from_dir(&dir).with_context(|| format!("can't repack {}", dir.display()))?;
+
+            write_xfbin(xfbin, &output).with_context(|| format!("can't write {}", output.display()))
+        }
+
+        Command::DumpJson {
+            file,
+            output,
+            sidecar,
+        } => {
+            let xfbin = read(&file)?;
+
+            match output {
+                Some(output) => {
+                    let payloads = if sidecar { PayloadMode::Sidecar } else { PayloadMode::Inline };
+
+                    write_xfbin_json(&xfbin, &output, payloads)
+                        .with_context(|| format!("can't write {}", output.display()))
+                }
+                None => {
+                    writeln!(io::stdout(), "{}", to_json_string(&xfbin)?)?;
+                    Ok(())
+                }
+            }
+        }
+
+        Command::Validate { file, strict } => return validate(&file, strict),
+        Command::Diff { old, new } => return diff(&read(&old)?, &read(&new)?),
+    }?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn read(filepath: &Path) -> Result<Xfbin> {
+    read_xfbin(filepath).with_context(|| format!("can't read {}", filepath.display()))
+}
+
+fn exact_filter(name: &str, chunk_type: Option<&str>) -> StructFilter {
+    let filter = StructFilter::new().chunk_name(Pattern::exact(name));
+
+    match chunk_type {
+        Some(chunk_type) => filter.chunk_type(Pattern::exact(chunk_type)),
+        None => filter,
+    }
+}
+
+fn info(xfbin: &Xfbin) -> Result<()> {
+    let structs = xfbin.structs().collect::<Vec<_>>();
+    let references = xfbin.references();
+    let dangling = references.iter().filter(|reference| reference.target.is_dangling()).count();
+    let chunk_type_counts = structs
+        .iter()
+        .map(|nucc_struct| nucc_struct.chunk_type().to_string())
+        .counts();
+
+    let mut out = io::stdout().lock();
+
+    writeln!(out, "version: {}", xfbin.version)?;
+    writeln!(out, "pages: {}", xfbin.pages.len())?;
+    writeln!(out, "structs: {}", structs.len())?;
+
+    for (chunk_type, count) in chunk_type_counts.into_iter().sorted() {
+        writeln!(out, "  {}: {}", chunk_type, count)?;
+    }
+
+    writeln!(out, "references: {} ({} dangling)", references.len(), dangling)?;
+
+    Ok(())
+}
+
+fn list(xfbin: &Xfbin, filter: &StructFilter) -> Result<()> {
+    let mut out = io::stdout().lock();
+
+    for (page_index, page) in xfbin.pages.iter().enumerate() {
+        for (struct_index, nucc_struct) in page.structs.iter().enumerate() {
+            let struct_info = nucc_struct.struct_info();
+
+            if filter.matches(struct_info) {
+                writeln!(
+                    out,
+                    "{}\t{}\t{}\t{}\t{}",
+                    page_index, struct_index, struct_info.chunk_type, struct_info.chunk_name, struct_info.filepath
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Raw bytes of binaries and unknown chunks, JSON of every other struct
+fn payload_of(nucc_struct: &dyn NuccStruct) -> Result<Vec<u8>> {
+    if let Some(binary) = nucc_struct.downcast_ref::<NuccBinary>() {
+        return Ok(binary.data.to_vec());
+    }
+
+    if let Some(unknown) = nucc_struct.downcast_ref::<NuccUnknown>() {
+        return Ok(unknown.data.to_vec());
+    }
+
+    Ok(struct_to_json_string(nucc_struct)?.into_bytes())
+}
+
+fn replace(xfbin: &mut Xfbin, filter: &StructFilter, input: &Path) -> Result<()> {
+    let data = fs::read(input).with_context(|| format!("can't read {}", input.display()))?;
+
+    let (page_index, struct_index) = xfbin
+        .pages
+        .iter()
+        .enumerate()
+        .find_map(|(page_index, page)| {
+            page.structs
+                .iter()
+                .position(|nucc_struct| filter.matches(nucc_struct.struct_info()))
+                .map(|struct_index| (page_index, struct_index))
+        })
+        .context("no matching struct")?;
+
+    let page = &mut xfbin.pages[page_index];
+    let nucc_struct = page.structs[struct_index].as_mut();
+
+    if let Some(binary) = nucc_struct.downcast_mut::<NuccBinary>() {
+        binary.data = data.into();
+    } else if let Some(unknown) = nucc_struct.downcast_mut::<NuccUnknown>() {
+        unknown.data = data.into();
+    } else {
+        let replacement = struct_from_json_str(&String::from_utf8(data)?)?;
+
+        if replacement.chunk_type() != nucc_struct.chunk_type() {
+            bail!(
+                "{} holds a {}, but the struct is a {}",
+                input.display(),
+                replacement.chunk_type(),
+                nucc_struct.chunk_type()
+            );
+        }
+
+        page.replace_struct(struct_index, replacement)?;
+    }
+
+    Ok(())
+}
+
+fn validate(filepath: &Path, strict: bool) -> Result<ExitCode> {
+    let findings = validate_xfbin(filepath).with_context(|| format!("can't read {}", filepath.display()))?;
+
+    let mut out = io::stdout().lock();
+    for finding in &findings {
+        writeln!(out, "{}", finding)?;
+    }
+
+    let failed = has_errors(&findings) || (strict && !findings.is_empty());
+
+    Ok(if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}
+
+fn diff(old: &Xfbin, new: &Xfbin) -> Result<ExitCode> {
+    let diff = old.diff(new)?;
+
+    if diff.is_empty() {
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    write!(io::stdout(), "{}", diff)?;
+
+    Ok(ExitCode::FAILURE)
+}
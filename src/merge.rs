@@ -0,0 +1,245 @@
+//! # Merge
+//! Applying overlays, such as mods, onto a base document. Overlay structs are matched to base structs by `NuccStructInfo`:
+//! new ones are added, and ones the base already has are handled by a `MergePolicy`.
+//! Each overlay page's new structs go into a new page that carries the overlay page's struct infos and references,
+//! since anms and unknown chunks address those tables by index and are only valid next to them.
+//!
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::fmt;
+
+use super::error::{XfbinError, XfbinResult};
+use super::nucc::{NuccStruct, NuccStructInfo};
+use super::nucc_chunk::NuccChunkType;
+use super::xfbin::{Xfbin, XfbinPage};
+
+/// Numbered chunk names tried for a renamed struct before giving up
+const MAX_RENAMES: usize = 10_000;
+
+/// What to do when an overlay struct has the same struct info as a base struct
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergePolicy {
+    #[default]
+    PreferOverlay,
+    PreferBase,
+
+    /// Stop at the first collision, leaving the base untouched
+    Fail,
+
+    /// Keep both, giving the overlay's struct a new chunk name
+    Rename,
+}
+
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeAction {
+    Inserted,
+    Replaced,
+    KeptBase,
+    Renamed,
+}
+
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone)]
+pub struct MergeEntry {
+    pub overlay_index: usize,
+    pub struct_info: NuccStructInfo,
+    pub action: MergeAction,
+
+    /// The page the overlay's struct ended up in, or `None` if the base struct was kept
+    pub page_index: Option<usize>,
+
+    /// The overlay struct's new struct info, if it was renamed
+    pub renamed_to: Option<NuccStructInfo>,
+}
+
+/// What happened to every struct of every overlay, in overlay order
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone, Default)]
+pub struct MergeReport {
+    pub entries: Vec<MergeEntry>,
+}
+
+impl MergeReport {
+    pub fn count(&self, action: MergeAction) -> usize {
+        self.entries.iter().filter(|entry| entry.action == action).count()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl MergeReport {
+    #[pyo3(name = "count")]
+    fn py_count(&self, action: MergeAction) -> usize {
+        self.count(action)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "MergeReport(inserted={}, replaced={}, kept_base={}, renamed={})",
+            self.count(MergeAction::Inserted),
+            self.count(MergeAction::Replaced),
+            self.count(MergeAction::KeptBase),
+            self.count(MergeAction::Renamed)
+        )
+    }
+
+    fn __str__(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for MergeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            write!(f, "overlay {}: {:?} {}", entry.overlay_index, entry.action, entry.struct_info)?;
+
+            if let Some(renamed_to) = &entry.renamed_to {
+                write!(f, " as \"{}\"", renamed_to.chunk_name)?;
+            }
+
+            if let Some(page_index) = entry.page_index {
+                write!(f, " (page {})", page_index)?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Xfbin {
+    /// Apply `overlays` onto this document in order, so later overlays win over earlier ones under `PreferOverlay`
+    pub fn merge<I>(&mut self, overlays: I, policy: MergePolicy) -> XfbinResult<MergeReport>
+    where
+        I: IntoIterator<Item = Xfbin>,
+    {
+        // Merge into a copy, so a conflict under the fail policy leaves the base as it was
+        let mut merged = self.clone();
+        let mut report = MergeReport::default();
+
+        for (overlay_index, overlay) in overlays.into_iter().enumerate() {
+            for overlay_page in overlay.pages {
+                merged.merge_page(overlay_index, overlay_page, policy, &mut report)?;
+            }
+        }
+
+        *self = merged;
+
+        Ok(report)
+    }
+
+    fn merge_page(
+        &mut self,
+        overlay_index: usize,
+        overlay_page: XfbinPage,
+        policy: MergePolicy,
+        report: &mut MergeReport,
+    ) -> XfbinResult<()> {
+        let new_page_index = self.pages.len();
+        let mut new_page = XfbinPage {
+            structs: Vec::new(),
+            struct_infos: overlay_page.struct_infos,
+            struct_references: overlay_page.struct_references,
+        };
+
+        // Base pages that had a struct replaced in place, and so need the overlay page's references too
+        let mut replaced_pages = Vec::new();
+
+        for mut nucc_struct in overlay_page.structs {
+            let struct_info = nucc_struct.struct_info().clone();
+
+            let mut entry = MergeEntry {
+                overlay_index,
+                struct_info: struct_info.clone(),
+                action: MergeAction::Inserted,
+                page_index: Some(new_page_index),
+                renamed_to: None,
+            };
+
+            match (self.locate(&struct_info), policy) {
+                (None, _) => new_page.structs.push(nucc_struct),
+
+                (Some(_), MergePolicy::PreferBase) => {
+                    entry.action = MergeAction::KeptBase;
+                    entry.page_index = None;
+                }
+
+                (Some(_), MergePolicy::Fail) => return Err(XfbinError::merge_conflict(overlay_index, &struct_info)),
+
+                (Some((page_index, struct_index)), MergePolicy::PreferOverlay) => {
+                    entry.action = MergeAction::Replaced;
+
+                    if addresses_struct_infos(nucc_struct.as_ref()) {
+                        self.pages[page_index].remove_struct(struct_index)?;
+                        new_page.structs.push(nucc_struct);
+                    } else {
+                        self.pages[page_index].replace_struct(struct_index, nucc_struct)?;
+                        entry.page_index = Some(page_index);
+                        replaced_pages.push(page_index);
+                    }
+                }
+
+                (Some(_), MergePolicy::Rename) => {
+                    let renamed = self
+                        .unused_struct_info(&struct_info, &new_page)
+                        .ok_or_else(|| XfbinError::merge_conflict(overlay_index, &struct_info))?;
+
+                    // Retargets the overlay page's table slot and references along with the struct
+                    new_page.tables().replace(&struct_info, &renamed, false);
+                    *nucc_struct.struct_info_mut() = renamed.clone();
+                    new_page.structs.push(nucc_struct);
+
+                    entry.action = MergeAction::Renamed;
+                    entry.renamed_to = Some(renamed);
+                }
+            }
+
+            report.entries.push(entry);
+        }
+
+        replaced_pages.sort_unstable();
+        replaced_pages.dedup();
+        for page_index in replaced_pages {
+            for reference in &new_page.struct_references {
+                self.pages[page_index].add_reference(reference.clone());
+            }
+        }
+
+        if !new_page.structs.is_empty() {
+            self.pages.push(new_page);
+        }
+
+        Ok(())
+    }
+
+    /// Page and struct index of the first struct with `struct_info`
+    fn locate(&self, struct_info: &NuccStructInfo) -> Option<(usize, usize)> {
+        self.pages.iter().enumerate().find_map(|(page_index, page)| {
+            page.structs
+                .iter()
+                .position(|nucc_struct| nucc_struct.struct_info() == struct_info)
+                .map(|struct_index| (page_index, struct_index))
+        })
+    }
+
+    /// `struct_info` with a numbered chunk name that neither this document nor `pending` uses,
+    /// or `None` if the first `MAX_RENAMES` are all taken
+    fn unused_struct_info(&self, struct_info: &NuccStructInfo, pending: &XfbinPage) -> Option<NuccStructInfo> {
+        (1..=MAX_RENAMES)
+            .map(|n| NuccStructInfo {
+                chunk_name: format!("{}_{}", struct_info.chunk_name, n),
+                ..struct_info.clone()
+            })
+            .find(|renamed| {
+                self.locate(renamed).is_none()
+                    && !pending.structs.iter().any(|nucc_struct| nucc_struct.struct_info() == renamed)
+            })
+    }
+}
+
+fn addresses_struct_infos(nucc_struct: &dyn NuccStruct) -> bool {
+    matches!(nucc_struct.chunk_type(), NuccChunkType::NuccChunkAnm | NuccChunkType::NuccChunkUnknown)
+}
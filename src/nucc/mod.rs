@@ -11,13 +11,20 @@ pub mod nucc_morphmodel;
 
 pub mod nucc_unknown;
 
+pub(crate) mod vtable;
+
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+use bytes::Bytes;
 use downcast_rs::{impl_downcast, Downcast};
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use std::fmt;
-use std::hash::{Hash, Hasher};
+#[cfg(feature = "python")]
+use std::hash::Hasher;
+use std::hash::Hash;
 
+use super::error::{get_indexed, XfbinError, XfbinResult};
 use super::nucc_chunk::*;
 use super::xfbin_file::{XfbinChunkMap, XfbinChunkReference};
 
@@ -33,20 +40,15 @@ pub use nucc_ambient::NuccAmbient;
 pub use nucc_morphmodel::NuccMorphModel;
 pub use nucc_unknown::NuccUnknown;
 
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
 #[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq, Hash)]
 pub struct NuccStructInfo {
-
-    #[pyo3(get, set)]
     pub chunk_name: String,
-
-    #[pyo3(get, set)]
     pub chunk_type: String,
-
-    #[pyo3(get, set)]
     pub filepath: String,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl NuccStructInfo {
     #[new]
@@ -90,8 +92,10 @@ pub struct XfbinChunkMapConverter {
     pub filepaths: Vec<String>,
 }
 
-impl From<XfbinChunkMapConverter> for Vec<NuccStructInfo> {
-    fn from(converter: XfbinChunkMapConverter) -> Self {
+impl TryFrom<XfbinChunkMapConverter> for Vec<NuccStructInfo> {
+    type Error = XfbinError;
+
+    fn try_from(converter: XfbinChunkMapConverter) -> XfbinResult<Self> {
         let XfbinChunkMapConverter {
             chunk_maps,
             chunk_names: names,
@@ -101,26 +105,25 @@ impl From<XfbinChunkMapConverter> for Vec<NuccStructInfo> {
 
         chunk_maps
             .into_iter()
-            .map(|c| NuccStructInfo {
-                chunk_name: names[c.chunk_name_index as usize].clone(),
-                chunk_type: types[c.chunk_type_index as usize].clone(),
-                filepath: paths[c.filepath_index as usize].clone(),
+            .map(|c| {
+                Ok(NuccStructInfo {
+                    chunk_name: get_indexed("chunk name", &names, c.chunk_name_index as usize)?.clone(),
+                    chunk_type: get_indexed("chunk type", &types, c.chunk_type_index as usize)?.clone(),
+                    filepath: get_indexed("filepath", &paths, c.filepath_index as usize)?.clone(),
+                })
             })
             .collect()
     }
 }
 
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
 #[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq, Hash)]
 pub struct NuccStructReference {
-
-    #[pyo3(get, set)]
     pub chunk_name: String,
-
-    #[pyo3(get, set)]
     pub struct_info: NuccStructInfo,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl NuccStructReference {
     #[new]
@@ -166,8 +169,10 @@ pub struct XfbinChunkReferenceConverter {
     pub struct_infos: Vec<NuccStructInfo>,
 }
 
-impl From<XfbinChunkReferenceConverter> for Vec<NuccStructReference> {
-    fn from(converter: XfbinChunkReferenceConverter) -> Self {
+impl TryFrom<XfbinChunkReferenceConverter> for Vec<NuccStructReference> {
+    type Error = XfbinError;
+
+    fn try_from(converter: XfbinChunkReferenceConverter) -> XfbinResult<Self> {
         let XfbinChunkReferenceConverter {
             references,
             chunk_names: names,
@@ -176,9 +181,11 @@ impl From<XfbinChunkReferenceConverter> for Vec<NuccStructReference> {
 
         references
             .into_iter()
-            .map(|r| NuccStructReference {
-                chunk_name: names[r.chunk_name_index as usize].clone(),
-                struct_info: infos[r.chunk_map_index as usize].clone(),
+            .map(|r| {
+                Ok(NuccStructReference {
+                    chunk_name: get_indexed("chunk name", &names, r.chunk_name_index as usize)?.clone(),
+                    struct_info: get_indexed("chunk map", &infos, r.chunk_map_index as usize)?.clone(),
+                })
             })
             .collect()
     }
@@ -223,91 +230,20 @@ impl std::fmt::Debug for dyn NuccStruct {
 
 impl Clone for Box<dyn NuccStruct> {
     fn clone(&self) -> Self {
-        // Match on the concrete types implementing `NuccStruct` and clone them before boxing
-        match self.chunk_type() {
-            
-            NuccChunkType::NuccChunkAnm => {
-                let nucc_anm: &NuccAnm = self.downcast_ref().unwrap();
-                Box::new(nucc_anm.clone()) as Box<dyn NuccStruct>
-            }
-
-            NuccChunkType::NuccChunkBinary => {
-                let nucc_binary: &NuccBinary = self.downcast_ref().unwrap();
-                Box::new(nucc_binary.clone()) as Box<dyn NuccStruct>
-            }
-
-            NuccChunkType::NuccChunkCamera => {
-                let nucc_camera: &NuccCamera = self.downcast_ref().unwrap();
-                Box::new(nucc_camera.clone()) as Box<dyn NuccStruct>
-            }
-
-            NuccChunkType::NuccChunkUnknown => {
-                let nucc_unknown: &NuccUnknown = self.downcast_ref().unwrap();
-                Box::new(nucc_unknown.clone()) as Box<dyn NuccStruct>
-            }
-            // Add other cases for the remaining concrete types
-            _ => panic!("Unsupported NuccStruct type for cloning"),
-        }
+        self.as_ref().clone_boxed()
     }
 }
 
-impl IntoPy<PyObject> for Box<dyn NuccStruct> {
-    fn into_py(self, py: Python) -> PyObject {
-        // Match on the concrete types implementing `NuccStruct` and convert them to Python objects
-        match self.chunk_type() {
-
-            NuccChunkType::NuccChunkAnm => {
-                let nucc_anm: Box<NuccAnm> = self.downcast().unwrap();
-                nucc_anm.into_py(py)
-            }
-
-            NuccChunkType::NuccChunkBinary => {
-                let nucc_binary: Box<NuccBinary> = self.downcast().unwrap();
-                nucc_binary.into_py(py)
-            }
-
-            NuccChunkType::NuccChunkCamera => {
-                let nucc_camera: Box<NuccCamera> = self.downcast().unwrap();
-                nucc_camera.into_py(py)
-            }
-
-            NuccChunkType::NuccChunkUnknown => {
-                let nucc_unknown: Box<NuccUnknown> = self.downcast().unwrap();
-                nucc_unknown.into_py(py)
-            }
-            // Add other cases for the remaining concrete types
-            _ => panic!("Unsupported NuccStruct type for conversion to PyAny"),
+impl dyn NuccStruct {
+    pub fn clone_boxed(&self) -> Box<dyn NuccStruct> {
+        match vtable::vtable(&self.chunk_type()) {
+            Ok(vtable) => (vtable.clone_struct)(self),
+            Err(_) => unreachable!("{} is not a NuccStruct type", self.chunk_type()),
         }
     }
 }
 
 
-impl<'a> FromPyObject<'a> for Box<dyn NuccStruct> {
-    fn extract(obj: &'a PyAny) -> PyResult<Self> {        
-        if let Ok(nucc_anm) = obj.extract::<NuccAnm>() {
-            return Ok(Box::new(nucc_anm));
-        }
-
-    
-        if let Ok(nucc_binary) = obj.extract::<NuccBinary>() {
-            return Ok(Box::new(nucc_binary));
-        }
-
-        if let Ok(nucc_camera) = obj.extract::<NuccCamera>() {
-            return Ok(Box::new(nucc_camera));
-        }
-
-        if let Ok(nucc_unknown) = obj.extract::<NuccUnknown>() {
-            return Ok(Box::new(nucc_unknown));
-        }
-
-        // Add other cases for the remaining concrete types
-        Err(pyo3::exceptions::PyTypeError::new_err("Unsupported NuccStruct type"))
-
-    }
-}
-
-
 impl_downcast!(NuccStruct);
 
 
@@ -323,40 +259,36 @@ pub struct NuccChunkConverter {
     pub struct_reference_map: IndexMap<NuccStructReference, u32>,
 }
 
-impl From<NuccStructConverter> for Box<dyn NuccStruct> {
-    fn from(converter: NuccStructConverter) -> Self {
-        match converter.nucc_chunk.chunk_type() {
-            NuccChunkType::NuccChunkBinary => Box::new(NuccBinary::from(converter)),
-            NuccChunkType::NuccChunkAnm => Box::new(NuccAnm::from(converter)),
-            NuccChunkType::NuccChunkAnmStrm => Box::new(NuccAnmStrm::from(converter)),
-            NuccChunkType::NuccChunkAnmStrmFrame => Box::new(NuccAnmStrmFrame::from(converter)),
-            NuccChunkType::NuccChunkCamera => Box::new(NuccCamera::from(converter)),
-            NuccChunkType::NuccChunkLightDirc => Box::new(NuccLightDirc::from(converter)),
-            NuccChunkType::NuccChunkLightPoint => Box::new(NuccLightPoint::from(converter)),
-            NuccChunkType::NuccChunkLayerSet => Box::new(NuccLayerSet::from(converter)),
-            NuccChunkType::NuccChunkAmbient => Box::new(NuccAmbient::from(converter)),
-            NuccChunkType::NuccChunkMorphModel => Box::new(NuccMorphModel::from(converter)),
-            NuccChunkType::NuccChunkUnknown => Box::new(NuccUnknown::from(converter)),
-            any => panic!("Unexpected NuccChunkType: {any}"),
-        }
+pub(crate) fn downcast_chunk<T: NuccChunk>(nucc_chunk: Box<dyn NuccChunk>, expected: NuccChunkType) -> XfbinResult<T> {
+    let found = nucc_chunk.chunk_type();
+
+    nucc_chunk
+        .downcast::<T>()
+        .map(|c| *c)
+        .map_err(|_| XfbinError::TypeMismatch { expected, found })
+}
+
+pub(crate) fn downcast_struct<T: NuccStruct>(nucc_struct: Box<dyn NuccStruct>, expected: NuccChunkType) -> XfbinResult<T> {
+    let found = nucc_struct.chunk_type();
+
+    nucc_struct
+        .downcast::<T>()
+        .map(|s| *s)
+        .map_err(|_| XfbinError::TypeMismatch { expected, found })
+}
+
+impl TryFrom<NuccStructConverter> for Box<dyn NuccStruct> {
+    type Error = XfbinError;
+
+    fn try_from(converter: NuccStructConverter) -> XfbinResult<Self> {
+        (vtable::vtable(&converter.nucc_chunk.chunk_type())?.to_struct)(converter)
     }
 }
 
-impl From<NuccChunkConverter> for Box<dyn NuccChunk> {
-    fn from(converter: NuccChunkConverter) -> Self {
-        match converter.nucc_struct.chunk_type() {
-            NuccChunkType::NuccChunkBinary => { Box::<NuccChunkBinary>::from(converter) as Box<dyn NuccChunk> }
-            NuccChunkType::NuccChunkAnm => { Box::<NuccChunkAnm>::from(converter) as Box<dyn NuccChunk> }
-            NuccChunkType::NuccChunkAnmStrm => { Box::<NuccChunkAnmStrm>::from(converter) as Box<dyn NuccChunk> }
-            NuccChunkType::NuccChunkAnmStrmFrame => { Box::<NuccChunkAnmStrmFrame>::from(converter) as Box<dyn NuccChunk> }
-            NuccChunkType::NuccChunkCamera => { Box::<NuccChunkCamera>::from(converter) as Box<dyn NuccChunk> }
-            NuccChunkType::NuccChunkLightDirc => { Box::<NuccChunkLightDirc>::from(converter) as Box<dyn NuccChunk> }
-            NuccChunkType::NuccChunkLightPoint => { Box::<NuccChunkLightPoint>::from(converter) as Box<dyn NuccChunk> }
-            NuccChunkType::NuccChunkLayerSet => { Box::<NuccChunkLayerSet>::from(converter) as Box<dyn NuccChunk> }
-            NuccChunkType::NuccChunkAmbient => { Box::<NuccChunkAmbient>::from(converter) as Box<dyn NuccChunk> }
-            NuccChunkType::NuccChunkMorphModel => { Box::<NuccChunkMorphModel>::from(converter) as Box<dyn NuccChunk> }
-            NuccChunkType::NuccChunkUnknown => { Box::<NuccChunkUnknown>::from(converter) as Box<dyn NuccChunk> }
-            any => panic!("Unexpected NuccChunkType: {any}"),
-        }
+impl TryFrom<NuccChunkConverter> for Box<dyn NuccChunk> {
+    type Error = XfbinError;
+
+    fn try_from(converter: NuccChunkConverter) -> XfbinResult<Self> {
+        (vtable::vtable(&converter.nucc_struct.chunk_type())?.to_chunk)(converter)
     }
-}
\ No newline at end of file
+}
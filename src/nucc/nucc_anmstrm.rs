@@ -1,81 +1,111 @@
 use super::*;
 
 use crate::nucc_chunk::nucc_chunk_anm::CoordParent;
+use super::nucc_anm::loop_flag;
 use crate::nucc_chunk::nucc_chunk_anmstrm::{AnmStrmClump, AnmStrmFrameInfo};
 
-#[derive(Default)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct NuccAnmStrm {
     pub struct_info: NuccStructInfo,
 
     pub version: u16,
 
     pub frame_count: u32,
+    pub frame_size: u32,
     pub is_looped: bool,
+    /// The loop flag as it was stored, since files don't always use 1 for looped animations
+    #[serde(default)]
+    pub looped_flag: u16,
 
     pub clumps: Vec<AnmStrmClump>,
 
     pub other_entry_indices: Vec<u32>,
+    pub unk_entry_count: u16,
 
     pub coord_parents: Vec<CoordParent>,
 
     pub entries: Vec<AnmStrmFrameInfo>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "crate::json::payload")]
+    pub trailing: Vec<u8>,
+}
+
+impl Default for NuccAnmStrm {
+    fn default() -> Self {
+        Self {
+            struct_info: Default::default(),
+            version: 121,
+            frame_count: 0,
+            frame_size: 100,
+            is_looped: false,
+            looped_flag: 0,
+            clumps: Vec::new(),
+            other_entry_indices: Vec::new(),
+            unk_entry_count: 0,
+            coord_parents: Vec::new(),
+            entries: Vec::new(),
+            trailing: Vec::new(),
+        }
+    }
 }
 
 impl_nucc_info!(NuccAnmStrm, struct_info);
 
-impl From<NuccStructConverter> for NuccAnmStrm {
-    fn from(converter: NuccStructConverter) -> Self {
+impl TryFrom<NuccStructConverter> for NuccAnmStrm {
+    type Error = XfbinError;
+
+    fn try_from(converter: NuccStructConverter) -> XfbinResult<Self> {
         let NuccStructConverter {
             nucc_chunk,
             struct_infos: _,
             struct_references: _,
         } = converter;
 
-        let chunk = nucc_chunk
-            .downcast::<NuccChunkAnmStrm>()
-            .map(|c| *c)
-            .ok()
-            .unwrap();
+        let chunk = downcast_chunk::<NuccChunkAnmStrm>(nucc_chunk, NuccChunkType::NuccChunkAnmStrm)?;
 
-        Self {
+        Ok(Self {
             struct_info: Default::default(),
             version: chunk.version,
             frame_count: chunk.frame_count,
+            frame_size: chunk.frame_size,
             is_looped: chunk.is_looped == 1,
+            looped_flag: chunk.is_looped,
             clumps: chunk.clumps,
             other_entry_indices: chunk.other_entry_indices,
+            unk_entry_count: chunk.unk_entry_count,
             coord_parents: chunk.coord_parents,
             entries: chunk.entries,
-        }
+            trailing: chunk.trailing,
+        })
     }
 }
 
-impl From<NuccChunkConverter> for Box<NuccChunkAnmStrm> {
-    fn from(converter: NuccChunkConverter) -> Self {
+impl TryFrom<NuccChunkConverter> for Box<NuccChunkAnmStrm> {
+    type Error = XfbinError;
+
+    fn try_from(converter: NuccChunkConverter) -> XfbinResult<Self> {
         let NuccChunkConverter {
             nucc_struct,
             struct_info_map: _,
             struct_reference_map: _,
         } = converter;
 
-        let anmstrm = nucc_struct
-            .downcast::<NuccAnmStrm>()
-            .map(|a| *a)
-            .ok()
-            .unwrap();
+        let anmstrm = downcast_struct::<NuccAnmStrm>(nucc_struct, NuccChunkType::NuccChunkAnmStrm)?;
 
         let chunk = NuccChunkAnmStrm {
             version: anmstrm.version,
             frame_count: anmstrm.frame_count,
-            is_looped: if anmstrm.is_looped { 1 } else { 0 },
+            frame_size: anmstrm.frame_size,
+            is_looped: loop_flag(anmstrm.is_looped, anmstrm.looped_flag),
             clumps: anmstrm.clumps,
-            unk_entry_count: 0,
+            unk_entry_count: anmstrm.unk_entry_count,
             other_entry_indices: anmstrm.other_entry_indices,
             coord_parents: anmstrm.coord_parents,
             entries: anmstrm.entries,
+            trailing: anmstrm.trailing,
         };
 
-        Box::new(chunk)
+        Ok(Box::new(chunk))
     }
 }
 
@@ -2,63 +2,64 @@ use super::*;
 
 use crate::nucc_chunk::nucc_chunk_anmstrmframe::AnmStrmEntry;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct NuccAnmStrmFrame {
     pub struct_info: NuccStructInfo,
     pub version: u16,
     pub frame_number: u32,
     pub unknown: u16,
     pub entries: Vec<AnmStrmEntry>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "crate::json::payload")]
+    pub trailing: Vec<u8>,
 }
 
 impl_nucc_info!(NuccAnmStrmFrame, struct_info);
 
-impl From<NuccStructConverter> for NuccAnmStrmFrame {
-    fn from(converter: NuccStructConverter) -> Self {
+impl TryFrom<NuccStructConverter> for NuccAnmStrmFrame {
+    type Error = XfbinError;
+
+    fn try_from(converter: NuccStructConverter) -> XfbinResult<Self> {
         let NuccStructConverter {
             nucc_chunk,
             struct_infos: _,
             struct_references: _,
         } = converter;
 
-        let chunk = nucc_chunk
-            .downcast::<NuccChunkAnmStrmFrame>()
-            .map(|c| *c)
-            .ok()
-            .unwrap();
+        let chunk = downcast_chunk::<NuccChunkAnmStrmFrame>(nucc_chunk, NuccChunkType::NuccChunkAnmStrmFrame)?;
 
-        Self {
+        Ok(Self {
             struct_info: Default::default(),
             version: chunk.version,
             frame_number: chunk.frame_number,
             unknown: chunk.unknown,
             entries: chunk.entries,
-        }
+            trailing: chunk.trailing,
+        })
     }
 }
 
-impl From<NuccChunkConverter> for Box<NuccChunkAnmStrmFrame> {
-    fn from(converter: NuccChunkConverter) -> Self {
+impl TryFrom<NuccChunkConverter> for Box<NuccChunkAnmStrmFrame> {
+    type Error = XfbinError;
+
+    fn try_from(converter: NuccChunkConverter) -> XfbinResult<Self> {
         let NuccChunkConverter {
             nucc_struct,
             struct_info_map: _,
             struct_reference_map: _,
         } = converter;
 
-        let anmstrmframe = nucc_struct
-            .downcast::<NuccAnmStrmFrame>()
-            .map(|a| *a)
-            .ok()
-            .unwrap();
+        let anmstrmframe = downcast_struct::<NuccAnmStrmFrame>(nucc_struct, NuccChunkType::NuccChunkAnmStrmFrame)?;
 
         let chunk = NuccChunkAnmStrmFrame {
             version: anmstrmframe.version,
             frame_number: anmstrmframe.frame_number,
             unknown: anmstrmframe.unknown,
             entries: anmstrmframe.entries,
+            trailing: anmstrmframe.trailing,
         };
 
-        Box::new(chunk)
+        Ok(Box::new(chunk))
     }
 }
 
@@ -1,18 +1,17 @@
 use super::*;
 
 
-#[pyclass]
-#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct NuccBinary {
-    #[pyo3(get, set)]
     pub struct_info: NuccStructInfo,
-    #[pyo3(get, set)]
     pub version: u16,
 
-    #[pyo3(get, set)]
-    pub data: Vec<u8>,
+    #[serde(with = "crate::json::payload")]
+    pub data: Bytes,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl NuccBinary {
     #[new]
@@ -25,10 +24,41 @@ impl NuccBinary {
         Self {
             struct_info: struct_info.unwrap_or_default(),
             version,
-            data: data.unwrap_or_default(),
+            data: data.unwrap_or_default().into(),
         }
     }
 
+    #[getter]
+    fn get_struct_info(&self) -> NuccStructInfo {
+        self.struct_info.clone()
+    }
+
+    #[setter]
+    fn set_struct_info(&mut self, struct_info: NuccStructInfo) {
+        self.struct_info = struct_info;
+    }
+
+    #[getter]
+    fn get_version(&self) -> u16 {
+        self.version
+    }
+
+    #[setter]
+    fn set_version(&mut self, version: u16) {
+        self.version = version;
+    }
+
+    // The payload is shared with the buffer it was read from, so Python gets its own copy
+    #[getter]
+    fn get_data(&self) -> Vec<u8> {
+        self.data.to_vec()
+    }
+
+    #[setter]
+    fn set_data(&mut self, data: Vec<u8>) {
+        self.data = data.into();
+    }
+
     fn __repr__(&self) -> PyResult<String> {
         Ok(format!(
             "NuccBinary {{ struct_info: {:?}, version: {}, data: {:?} }}",
@@ -43,46 +73,42 @@ impl NuccBinary {
 
 impl_nucc_info!(NuccBinary, struct_info);
 
-impl From<NuccStructConverter> for NuccBinary {
-    fn from(converter: NuccStructConverter) -> Self {
+impl TryFrom<NuccStructConverter> for NuccBinary {
+    type Error = XfbinError;
+
+    fn try_from(converter: NuccStructConverter) -> XfbinResult<Self> {
         let NuccStructConverter {
             nucc_chunk,
             struct_infos: _,
             struct_references: _,
         } = converter;
 
-        let chunk = nucc_chunk
-            .downcast::<NuccChunkBinary>()
-            .map(|c| *c)
-            .ok()
-            .unwrap();
+        let chunk = downcast_chunk::<NuccChunkBinary>(nucc_chunk, NuccChunkType::NuccChunkBinary)?;
 
-        Self {
+        Ok(Self {
             struct_info: Default::default(),
             version: chunk.version,
             data: chunk.data,
-        }
+        })
     }
 }
 
-impl From<NuccChunkConverter> for Box<NuccChunkBinary> {
-    fn from(converter: NuccChunkConverter) -> Self {
+impl TryFrom<NuccChunkConverter> for Box<NuccChunkBinary> {
+    type Error = XfbinError;
+
+    fn try_from(converter: NuccChunkConverter) -> XfbinResult<Self> {
         let NuccChunkConverter {
             nucc_struct,
             struct_info_map: _,
             struct_reference_map: _,
         } = converter;
 
-        let binary = nucc_struct
-            .downcast::<NuccBinary>()
-            .map(|c| *c)
-            .ok()
-            .unwrap();
+        let binary = downcast_struct::<NuccBinary>(nucc_struct, NuccChunkType::NuccChunkBinary)?;
 
-        Box::new(NuccChunkBinary {
+        Ok(Box::new(NuccChunkBinary {
             version: binary.version,
             data: binary.data,
-        })
+        }))
     }
 }
 
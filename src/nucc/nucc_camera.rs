@@ -1,22 +1,19 @@
 use super::*;
 
-use pyo3::prelude::*;
-
-#[pyclass]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct NuccCamera {
-
-    #[pyo3(get, set)]
     pub struct_info: NuccStructInfo,
-
-    #[pyo3(get, set)]
     pub version: u16,
 
-    #[pyo3(get, set)]
-    pub fov: f32
+    pub fov: f32,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "crate::json::payload")]
+    pub trailing: Vec<u8>,
 }
 
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl NuccCamera {
     #[new]
@@ -30,6 +27,7 @@ impl NuccCamera {
             struct_info: struct_info.unwrap_or_default(),
             version,
             fov,
+            trailing: Vec::new(),
         }
     
     }
@@ -48,46 +46,44 @@ impl NuccCamera {
 
 impl_nucc_info!(NuccCamera, struct_info);
 
-impl From<NuccStructConverter> for NuccCamera {
-    fn from(converter: NuccStructConverter) -> Self {
+impl TryFrom<NuccStructConverter> for NuccCamera {
+    type Error = XfbinError;
+
+    fn try_from(converter: NuccStructConverter) -> XfbinResult<Self> {
         let NuccStructConverter {
             nucc_chunk,
             struct_infos: _,
             struct_references: _,
         } = converter;
 
-        let chunk = nucc_chunk
-            .downcast::<NuccChunkCamera>()
-            .map(|c| *c)
-            .ok()
-            .unwrap();
+        let chunk = downcast_chunk::<NuccChunkCamera>(nucc_chunk, NuccChunkType::NuccChunkCamera)?;
 
-        Self {
+        Ok(Self {
             struct_info: Default::default(),
             version: chunk.version,
             fov: chunk.fov,
-        }
+            trailing: chunk.trailing,
+        })
     }
 }
 
-impl From<NuccChunkConverter> for Box<NuccChunkCamera> {
-    fn from(converter: NuccChunkConverter) -> Self {
+impl TryFrom<NuccChunkConverter> for Box<NuccChunkCamera> {
+    type Error = XfbinError;
+
+    fn try_from(converter: NuccChunkConverter) -> XfbinResult<Self> {
         let NuccChunkConverter {
             nucc_struct,
             struct_info_map: _,
             struct_reference_map: _,
         } = converter;
 
-        let cam = nucc_struct
-            .downcast::<NuccCamera>()
-            .map(|c| *c)
-            .ok()
-            .unwrap();
+        let cam = downcast_struct::<NuccCamera>(nucc_struct, NuccChunkType::NuccChunkCamera)?;
 
-        Box::new(NuccChunkCamera {
+        Ok(Box::new(NuccChunkCamera {
             version: cam.version,
             fov: cam.fov,
-        })
+            trailing: cam.trailing,
+        }))
     }
 }
 
@@ -1,55 +1,88 @@
 use super::*;
 
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct NuccLightPoint {
     pub struct_info: NuccStructInfo,
     pub version: u16,
 
+    #[serde(with = "crate::json::payload")]
     pub data: Vec<u8>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "crate::json::payload")]
+    pub trailing: Vec<u8>,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl NuccLightPoint {
+    #[new]
+    #[pyo3(signature = (struct_info = None, version = 121, data = None))]
+    pub fn __new__(
+        struct_info: Option<NuccStructInfo>,
+        version: u16,
+        data: Option<Vec<u8>>,
+    ) -> Self {
+        Self {
+            struct_info: struct_info.unwrap_or_default(),
+            version,
+            data: data.unwrap_or_default(),
+            trailing: Vec::new(),
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "NuccLightPoint {{ struct_info: {:?}, version: {}, data: {} bytes }}",
+            self.struct_info, self.version, self.data.len()
+        ))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
 }
 
 impl_nucc_info!(NuccLightPoint, struct_info);
 
-impl From<NuccStructConverter> for NuccLightPoint {
-    fn from(converter: NuccStructConverter) -> Self {
+impl TryFrom<NuccStructConverter> for NuccLightPoint {
+    type Error = XfbinError;
+
+    fn try_from(converter: NuccStructConverter) -> XfbinResult<Self> {
         let NuccStructConverter {
             nucc_chunk,
             struct_infos: _,
             struct_references: _,
         } = converter;
 
-        let chunk = nucc_chunk
-            .downcast::<NuccChunkLightPoint>()
-            .map(|c| *c)
-            .ok()
-            .unwrap();
+        let chunk = downcast_chunk::<NuccChunkLightPoint>(nucc_chunk, NuccChunkType::NuccChunkLightPoint)?;
 
-        Self {
+        Ok(Self {
             struct_info: Default::default(),
             version: chunk.version,
             data: chunk.data,
-        }
+            trailing: chunk.trailing,
+        })
     }
 }
 
-impl From<NuccChunkConverter> for Box<NuccChunkLightPoint> {
-    fn from(converter: NuccChunkConverter) -> Self {
+impl TryFrom<NuccChunkConverter> for Box<NuccChunkLightPoint> {
+    type Error = XfbinError;
+
+    fn try_from(converter: NuccChunkConverter) -> XfbinResult<Self> {
         let NuccChunkConverter {
             nucc_struct,
             struct_info_map: _,
             struct_reference_map: _,
         } = converter;
 
-        let lightpoint = nucc_struct
-            .downcast::<NuccLightPoint>()
-            .map(|c| *c)
-            .ok()
-            .unwrap();
+        let lightpoint = downcast_struct::<NuccLightPoint>(nucc_struct, NuccChunkType::NuccChunkLightPoint)?;
 
-        Box::new(NuccChunkLightPoint {
+        Ok(Box::new(NuccChunkLightPoint {
             version: lightpoint.version,
             data: lightpoint.data,
-        })
+            trailing: lightpoint.trailing,
+        }))
     }
 }
 
@@ -1,58 +1,93 @@
 use super::*;
 
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct NuccMorphModel {
     pub struct_info: NuccStructInfo,
     pub version: u16,
 
     pub count: u16,
+    #[serde(with = "crate::json::payload")]
     pub data: Vec<u8>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "crate::json::payload")]
+    pub trailing: Vec<u8>,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl NuccMorphModel {
+    #[new]
+    #[pyo3(signature = (struct_info = None, version = 121, count = 0, data = None))]
+    pub fn __new__(
+        struct_info: Option<NuccStructInfo>,
+        version: u16,
+        count: u16,
+        data: Option<Vec<u8>>,
+    ) -> Self {
+        Self {
+            struct_info: struct_info.unwrap_or_default(),
+            version,
+            count,
+            data: data.unwrap_or_default(),
+            trailing: Vec::new(),
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "NuccMorphModel {{ struct_info: {:?}, version: {}, count: {}, data: {} bytes }}",
+            self.struct_info, self.version, self.count, self.data.len()
+        ))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
 }
 
 impl_nucc_info!(NuccMorphModel, struct_info);
 
-impl From<NuccStructConverter> for NuccMorphModel {
-    fn from(converter: NuccStructConverter) -> Self {
+impl TryFrom<NuccStructConverter> for NuccMorphModel {
+    type Error = XfbinError;
+
+    fn try_from(converter: NuccStructConverter) -> XfbinResult<Self> {
         let NuccStructConverter {
             nucc_chunk,
             struct_infos: _,
             struct_references: _,
         } = converter;
 
-        let chunk = nucc_chunk
-            .downcast::<NuccChunkMorphModel>()
-            .map(|c| *c)
-            .ok()
-            .unwrap();
+        let chunk = downcast_chunk::<NuccChunkMorphModel>(nucc_chunk, NuccChunkType::NuccChunkMorphModel)?;
 
-        Self {
+        Ok(Self {
             struct_info: Default::default(),
             version: chunk.version,
             count: chunk.count,
             data: chunk.data,
-        }
+            trailing: chunk.trailing,
+        })
     }
 }
 
-impl From<NuccChunkConverter> for Box<NuccChunkMorphModel> {
-    fn from(converter: NuccChunkConverter) -> Self {
+impl TryFrom<NuccChunkConverter> for Box<NuccChunkMorphModel> {
+    type Error = XfbinError;
+
+    fn try_from(converter: NuccChunkConverter) -> XfbinResult<Self> {
         let NuccChunkConverter {
             nucc_struct,
             struct_info_map: _,
             struct_reference_map: _,
         } = converter;
 
-        let morphmodel = nucc_struct
-            .downcast::<NuccMorphModel>()
-            .map(|c| *c)
-            .ok()
-            .unwrap();
+        let morphmodel = downcast_struct::<NuccMorphModel>(nucc_struct, NuccChunkType::NuccChunkMorphModel)?;
 
-        Box::new(NuccChunkMorphModel {
+        Ok(Box::new(NuccChunkMorphModel {
             version: morphmodel.version,
             count: morphmodel.count,
             data: morphmodel.data,
-        })
+            trailing: morphmodel.trailing,
+        }))
     }
 }
 
@@ -1,61 +1,96 @@
 use super::*;
 
-use pyo3::prelude::*;
-
-#[pyclass]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct NuccUnknown {
     pub struct_info: NuccStructInfo,
     pub version: u16,
 
-    pub data: Vec<u8>,
+    #[serde(with = "crate::json::payload")]
+    pub data: Bytes,
     pub chunk_type: String,
 }
 
+#[cfg(feature = "python")]
+#[pymethods]
+impl NuccUnknown {
+    #[getter]
+    fn get_struct_info(&self) -> NuccStructInfo {
+        self.struct_info.clone()
+    }
+
+    #[setter]
+    fn set_struct_info(&mut self, struct_info: NuccStructInfo) {
+        self.struct_info = struct_info;
+    }
+
+    #[getter]
+    fn get_version(&self) -> u16 {
+        self.version
+    }
+
+    #[getter]
+    fn get_chunk_type(&self) -> String {
+        self.chunk_type.clone()
+    }
+
+    #[getter]
+    fn get_data(&self) -> Vec<u8> {
+        self.data.to_vec()
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "NuccUnknown {{ struct_info: {:?}, version: {}, chunk_type: {}, data: {} bytes }}",
+            self.struct_info, self.version, self.chunk_type, self.data.len()
+        ))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
+}
+
 impl_nucc_info!(NuccUnknown, struct_info);
 
-impl From<NuccStructConverter> for NuccUnknown {
-    fn from(converter: NuccStructConverter) -> Self {
+impl TryFrom<NuccStructConverter> for NuccUnknown {
+    type Error = XfbinError;
+
+    fn try_from(converter: NuccStructConverter) -> XfbinResult<Self> {
         let NuccStructConverter {
             nucc_chunk,
             struct_infos: _,
             struct_references: _,
         } = converter;
 
-        let chunk = nucc_chunk
-            .downcast::<NuccChunkUnknown>()
-            .map(|c| *c)
-            .ok()
-            .unwrap();
+        let chunk = downcast_chunk::<NuccChunkUnknown>(nucc_chunk, NuccChunkType::NuccChunkUnknown)?;
 
-        Self {
+        Ok(Self {
             struct_info: Default::default(),
             data: chunk.data,
             chunk_type: chunk.chunk_type,
             version: chunk.version,
-        }
+        })
     }
 }
 
-impl From<NuccChunkConverter> for Box<NuccChunkUnknown> {
-    fn from(converter: NuccChunkConverter) -> Self {
+impl TryFrom<NuccChunkConverter> for Box<NuccChunkUnknown> {
+    type Error = XfbinError;
+
+    fn try_from(converter: NuccChunkConverter) -> XfbinResult<Self> {
         let NuccChunkConverter {
             nucc_struct,
             struct_info_map: _,
             struct_reference_map: _,
         } = converter;
 
-        let unknown = nucc_struct
-            .downcast::<NuccUnknown>()
-            .map(|s| *s)
-            .ok()
-            .unwrap();
+        let unknown = downcast_struct::<NuccUnknown>(nucc_struct, NuccChunkType::NuccChunkUnknown)?;
 
-        Box::new(NuccChunkUnknown {
+        Ok(Box::new(NuccChunkUnknown {
             version: unknown.version,
             data: unknown.data,
             chunk_type: unknown.chunk_type,
-        })
+        }))
     }
 }
 
@@ -0,0 +1,257 @@
+//! # Struct type table
+//! Every struct type is handled through one entry of `VTABLES`, looked up by its chunk type:
+//! parsing and writing its chunk, converting between chunk and struct, cloning, JSON and Python.
+//! Supporting a new type means adding its entry here and nowhere else.
+//!
+use binrw::{io::Cursor, BinRead, BinWrite};
+use hashbrown::HashMap;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::sync::LazyLock;
+
+#[cfg(feature = "python")]
+use pyo3::{prelude::*, types::PyType};
+#[cfg(feature = "python")]
+use crate::nucc_chunk::{nucc_chunk_anm::*, nucc_chunk_anmstrm::*, nucc_chunk_anmstrmframe::*};
+
+use super::*;
+use crate::codec;
+#[cfg(feature = "python")]
+use crate::python;
+
+pub(crate) struct NuccStructVTable {
+    pub chunk_type: NuccChunkType,
+
+    /// Parse a chunk's data; the chunk type name is only needed by types not known ahead of time
+    pub read_chunk: fn(Bytes, &str, u16) -> XfbinResult<Box<dyn NuccChunk>>,
+    pub write_chunk: fn(Box<dyn NuccChunk>) -> XfbinResult<Vec<u8>>,
+
+    pub to_struct: fn(NuccStructConverter) -> XfbinResult<Box<dyn NuccStruct>>,
+    pub to_chunk: fn(NuccChunkConverter) -> XfbinResult<Box<dyn NuccChunk>>,
+    pub clone_struct: fn(&dyn NuccStruct) -> Box<dyn NuccStruct>,
+    /// The struct's fields, with its chunk type under `"type"`
+    pub to_json: fn(&dyn NuccStruct) -> XfbinResult<Value>,
+    /// The struct from its fields, without the `"type"` tag
+    pub from_json: fn(Value) -> serde_json::Result<Box<dyn NuccStruct>>,
+
+    #[cfg(feature = "python")]
+    pub into_py: fn(Python, Box<dyn NuccStruct>) -> PyResult<PyObject>,
+
+    /// `None` if the object isn't of this type
+    #[cfg(feature = "python")]
+    #[allow(clippy::type_complexity)]
+    pub from_py: fn(&Bound<'_, PyAny>) -> PyResult<Option<Box<dyn NuccStruct>>>,
+
+    /// The Python class of the type, if it has a single one
+    #[cfg(feature = "python")]
+    pub py_type: Option<fn(Python) -> Bound<'_, PyType>>,
+
+    /// Add the type's class, and those of the values it holds, to the module
+    #[cfg(feature = "python")]
+    pub add_py_classes: fn(&Bound<'_, PyModule>) -> PyResult<()>,
+}
+
+/// The entry of a type whose struct converts to and from its chunk with `TryFrom`.
+/// Chunks are parsed with binrw unless `read` and `write` are given.
+/// The Python class is either the struct itself (`direct`) or a `PyWrapper` around it (`wrapped`),
+/// registered along with the classes listed in `py_classes`.
+macro_rules! struct_vtable {
+    ($chunk_type:ident, $chunk:ty, $nucc_struct:ty, $py_kind:ident $py_class:ty) => {
+        struct_vtable!($chunk_type, $chunk, $nucc_struct, $py_kind $py_class; py_classes: [])
+    };
+
+    ($chunk_type:ident, $chunk:ty, $nucc_struct:ty, $py_kind:ident $py_class:ty; py_classes: [$($py_classes:ty),*]) => {
+        struct_vtable!(
+            @entry $chunk_type, $chunk, $nucc_struct, $py_kind $py_class;
+            read: read_chunk::<$chunk>,
+            write: write_chunk::<$chunk>;
+            py_classes: [$($py_classes),*]
+        )
+    };
+
+    ($chunk_type:ident, $chunk:ty, $nucc_struct:ty, $py_kind:ident $py_class:ty; read: $read:expr, write: $write:expr) => {
+        struct_vtable!(@entry $chunk_type, $chunk, $nucc_struct, $py_kind $py_class; read: $read, write: $write; py_classes: [])
+    };
+
+    (@entry $chunk_type:ident, $chunk:ty, $nucc_struct:ty, $py_kind:ident $py_class:ty; read: $read:expr, write: $write:expr; py_classes: [$($py_classes:ty),*]) => {
+        NuccStructVTable {
+            chunk_type: NuccChunkType::$chunk_type,
+            read_chunk: $read,
+            write_chunk: $write,
+            to_struct: to_struct::<$nucc_struct>,
+            to_chunk: to_chunk::<$chunk>,
+            clone_struct: clone_struct::<$nucc_struct>,
+            to_json: |nucc_struct| to_json(downcast_ref::<$nucc_struct>(nucc_struct, NuccChunkType::$chunk_type)?, NuccChunkType::$chunk_type),
+            from_json: from_json::<$nucc_struct>,
+            #[cfg(feature = "python")]
+            into_py: struct_vtable!(@into_py $py_kind $py_class),
+            #[cfg(feature = "python")]
+            from_py: struct_vtable!(@from_py $py_kind $py_class),
+            #[cfg(feature = "python")]
+            py_type: Some(|py| py.get_type_bound::<$py_class>()),
+            #[cfg(feature = "python")]
+            add_py_classes: |module| {
+                module.add_class::<$py_class>()?;
+                $(module.add_class::<$py_classes>()?;)*
+                Ok(())
+            },
+        }
+    };
+
+    (@into_py direct $py_class:ty) => { python::direct_into_py::<$py_class> };
+    (@into_py wrapped $py_class:ty) => { python::wrapped_into_py::<$py_class> };
+    (@from_py direct $py_class:ty) => { python::direct_from_py::<$py_class> };
+    (@from_py wrapped $py_class:ty) => { python::wrapped_from_py::<$py_class> };
+}
+
+static VTABLES: [NuccStructVTable; 12] = [
+    struct_vtable!(
+        NuccChunkAnm, NuccChunkAnm, NuccAnm, wrapped python::PyNuccAnm;
+        py_classes: [
+            NuccChunkAnm, AnmClump, AnmCoord, CoordParent, EntryFormat, python::PyAnmEntry, TrackHeader,
+            python::PyAnmTrack, NuccAnmKeyFormat, NuccAnmKey
+        ]
+    ),
+    struct_vtable!(
+        NuccChunkAnmStrm, NuccChunkAnmStrm, NuccAnmStrm, wrapped python::PyNuccAnmStrm;
+        py_classes: [AnmStrmClump, AnmStrmFrameInfo]
+    ),
+    struct_vtable!(
+        NuccChunkAnmStrmFrame, NuccChunkAnmStrmFrame, NuccAnmStrmFrame, wrapped python::PyNuccAnmStrmFrame;
+        py_classes: [
+            python::PyAnmStrmEntry, AnmEntryBone, AnmEntryCamera, AnmEntryMaterial, AnmEntryLightDirc,
+            AnmEntryLightPoint, AnmEntryAmbient, AnmEntryMorphModel
+        ]
+    ),
+    // Binary payloads stay views into the buffer they were read from
+    struct_vtable!(
+        NuccChunkBinary, NuccChunkBinary, NuccBinary, direct NuccBinary;
+        read: |data, _, version| Ok(Box::new(NuccChunkBinary::read_bytes(data, version)?)),
+        write: write_chunk::<NuccChunkBinary>
+    ),
+    struct_vtable!(NuccChunkCamera, NuccChunkCamera, NuccCamera, direct NuccCamera),
+    struct_vtable!(NuccChunkLightDirc, NuccChunkLightDirc, NuccLightDirc, direct NuccLightDirc),
+    struct_vtable!(NuccChunkLightPoint, NuccChunkLightPoint, NuccLightPoint, direct NuccLightPoint),
+    struct_vtable!(NuccChunkLayerSet, NuccChunkLayerSet, NuccLayerSet, direct NuccLayerSet),
+    struct_vtable!(NuccChunkAmbient, NuccChunkAmbient, NuccAmbient, direct NuccAmbient),
+    struct_vtable!(NuccChunkMorphModel, NuccChunkMorphModel, NuccMorphModel, direct NuccMorphModel),
+    // Unknown chunks keep their data as is, unless a codec is registered for their type
+    struct_vtable!(
+        NuccChunkUnknown, NuccChunkUnknown, NuccUnknown, direct NuccUnknown;
+        read: read_unknown,
+        write: |chunk| Ok(downcast_chunk::<NuccChunkUnknown>(chunk, NuccChunkType::NuccChunkUnknown)?.data.to_vec())
+    ),
+    // Types registered in `codec`, which does the routing to each codec itself
+    NuccStructVTable {
+        chunk_type: NuccChunkType::NuccChunkCustom,
+        read_chunk: read_unknown,
+        write_chunk: codec::encode,
+        to_struct: codec::to_struct,
+        to_chunk: codec::to_chunk,
+        clone_struct: codec::clone_struct,
+        // Without a page to resolve references against, only codecs that don't keep any can be written out on their own
+        to_json: |nucc_struct| {
+            to_json(
+                &codec::to_unknown(nucc_struct, Default::default(), Default::default())?,
+                NuccChunkType::NuccChunkUnknown,
+            )
+        },
+        // Written out as unknown structs, so never read back under this type
+        from_json: from_json::<NuccUnknown>,
+        #[cfg(feature = "python")]
+        into_py: python::codec_into_py,
+        #[cfg(feature = "python")]
+        from_py: python::codec_from_py,
+        #[cfg(feature = "python")]
+        py_type: None,
+        #[cfg(feature = "python")]
+        add_py_classes: |_| Ok(()),
+    },
+];
+
+static BY_CHUNK_TYPE: LazyLock<HashMap<NuccChunkType, &'static NuccStructVTable>> =
+    LazyLock::new(|| VTABLES.iter().map(|vtable| (vtable.chunk_type.clone(), vtable)).collect());
+
+/// The entry for `chunk_type`, or `UnsupportedType` for the chunk types that never become structs
+pub(crate) fn vtable(chunk_type: &NuccChunkType) -> XfbinResult<&'static NuccStructVTable> {
+    BY_CHUNK_TYPE
+        .get(chunk_type)
+        .copied()
+        .ok_or_else(|| XfbinError::UnsupportedType(chunk_type.to_string()))
+}
+
+#[cfg(feature = "python")]
+pub(crate) fn vtables() -> &'static [NuccStructVTable] {
+    &VTABLES
+}
+
+fn read_chunk<C>(data: Bytes, _chunk_type: &str, version: u16) -> XfbinResult<Box<dyn NuccChunk>>
+where
+    C: NuccChunk + BinRead<Args = u16>,
+{
+    C::read_boxed(&data, version)
+}
+
+fn read_unknown(data: Bytes, chunk_type: &str, version: u16) -> XfbinResult<Box<dyn NuccChunk>> {
+    match codec::decode(data.clone(), chunk_type, version) {
+        Some(decoded) => decoded,
+        None => Ok(Box::new(NuccChunkUnknown {
+            version,
+            chunk_type: chunk_type.to_string(),
+            data,
+        })),
+    }
+}
+
+fn write_chunk<C>(chunk: Box<dyn NuccChunk>) -> XfbinResult<Vec<u8>>
+where
+    C: NuccChunk + BinWrite<Args = ()>,
+{
+    let mut output = Cursor::new(Vec::new());
+    C::write_boxed(chunk, &mut output)?;
+
+    Ok(output.into_inner())
+}
+
+fn to_struct<S>(converter: NuccStructConverter) -> XfbinResult<Box<dyn NuccStruct>>
+where
+    S: NuccStruct + TryFrom<NuccStructConverter, Error = XfbinError>,
+{
+    Ok(Box::new(S::try_from(converter)?))
+}
+
+fn to_chunk<C>(converter: NuccChunkConverter) -> XfbinResult<Box<dyn NuccChunk>>
+where
+    C: NuccChunk,
+    Box<C>: TryFrom<NuccChunkConverter, Error = XfbinError>,
+{
+    Ok(Box::<C>::try_from(converter)?)
+}
+
+fn clone_struct<S: NuccStruct + Clone>(nucc_struct: &dyn NuccStruct) -> Box<dyn NuccStruct> {
+    match nucc_struct.downcast_ref::<S>() {
+        Some(nucc_struct) => Box::new(nucc_struct.clone()),
+        None => unreachable!("chunk_type() does not match the concrete NuccStruct type"),
+    }
+}
+
+fn to_json<S: Serialize>(nucc_struct: &S, chunk_type: NuccChunkType) -> XfbinResult<Value> {
+    let mut value = serde_json::to_value(nucc_struct)?;
+    if let Value::Object(fields) = &mut value {
+        fields.insert("type".to_string(), Value::String(chunk_type.to_string()));
+    }
+
+    Ok(value)
+}
+
+fn from_json<S: NuccStruct + DeserializeOwned>(value: Value) -> serde_json::Result<Box<dyn NuccStruct>> {
+    Ok(Box::new(serde_json::from_value::<S>(value)?))
+}
+
+fn downcast_ref<S: NuccStruct>(nucc_struct: &dyn NuccStruct, expected: NuccChunkType) -> XfbinResult<&S> {
+    let found = nucc_struct.chunk_type();
+
+    nucc_struct
+        .downcast_ref::<S>()
+        .ok_or(XfbinError::TypeMismatch { expected, found })
+}
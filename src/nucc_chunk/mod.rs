@@ -15,13 +15,17 @@ mod nucc_chunk_unknown;
 pub mod nucc_helper;
 
 use binrw::{io::Cursor, BinRead, BinWrite};
+use bytes::Bytes;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 use std::{fmt, str::FromStr};
 
 use downcast_rs::{impl_downcast, Downcast};
-use std::error::Error;
 use strum_macros::{Display, EnumString};
 
+use crate::nucc::vtable::vtable;
+use crate::error::{XfbinError, XfbinResult};
+
 pub use nucc_chunk_null::NuccChunkNull;
 pub use nucc_chunk_page::NuccChunkPage;
 pub use nucc_chunk_index::NuccChunkIndex;
@@ -37,14 +41,11 @@ pub use nucc_chunk_ambient::NuccChunkAmbient;
 pub use nucc_chunk_morphmodel::NuccChunkMorphModel;
 pub use nucc_chunk_unknown::NuccChunkUnknown;
 
-pub trait NuccChunk: Downcast + fmt::Debug {
+pub trait NuccChunk: Downcast + fmt::Debug + Send {
     fn chunk_type(&self) -> NuccChunkType;
     fn version(&self) -> u16;
     
-    fn read_boxed(
-        input: &[u8],
-        version: u16,
-    ) -> Result<(Vec<u8>, Box<dyn NuccChunk>), Box<dyn Error>>
+    fn read_boxed(input: &[u8], version: u16) -> XfbinResult<Box<dyn NuccChunk>>
     where
         Self: Sized + BinRead<Args = u16>,
     {
@@ -52,31 +53,27 @@ pub trait NuccChunk: Downcast + fmt::Debug {
         let mut cursor = Cursor::new(input);
         let result = Self::read_le_args(&mut cursor, version)?;
 
-        // set the version of the chunk
-        Ok((input.into(), Box::new(result) as Box<dyn NuccChunk>))
- 
+        Ok(Box::new(result) as Box<dyn NuccChunk>)
     }
 
-    fn write_boxed(
-        boxed: Box<dyn NuccChunk>,
-        output: &mut Cursor<Vec<u8>>,
-        version: u16,
-    ) -> Result<(), Box<dyn Error>>
+    fn write_boxed(boxed: Box<dyn NuccChunk>, output: &mut Cursor<Vec<u8>>) -> XfbinResult<()>
     where
         Self: Sized + BinWrite<Args = ()>,
     {
+        let chunk_type = boxed.chunk_type();
+        let chunk = boxed
+            .downcast::<Self>()
+            .map_err(|_| XfbinError::UnsupportedType(chunk_type.to_string()))?;
+
         // Serialize the data using binrw
-        Ok(Self::write_le(
-            &boxed.downcast::<Self>().map(|c| *c).ok().unwrap(),
-            output,
-        )?)
+        Ok(Self::write_le(&chunk, output)?)
     }
 }
 
 impl_downcast!(NuccChunk);
 
-#[pyclass]
-#[derive(Debug, Clone, Display, EnumString, PartialEq, Eq)]
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Debug, Clone, Default, Display, EnumString, PartialEq, Eq, Hash)]
 #[strum(serialize_all = "camelCase")]
 pub enum NuccChunkType {
     NuccChunkNull,
@@ -92,76 +89,35 @@ pub enum NuccChunkType {
     NuccChunkLayerSet,
     NuccChunkAmbient,
     NuccChunkMorphModel,
+    #[default]
     NuccChunkUnknown,
-}
 
-impl Default for NuccChunkType {
-    fn default() -> Self {
-        NuccChunkType::NuccChunkUnknown
-    }
+    /// A type decoded by a codec registered in `codec`; its name is in the struct info
+    #[strum(disabled)]
+    NuccChunkCustom,
 }
 
 impl NuccChunkType {
-    pub fn read_data(
-        data: Vec<u8>,
-        chunk_type: &str,
-        version: u16,
-    ) -> Result<(Vec<u8>, Box<dyn NuccChunk>), Box<dyn Error>> {
+    pub fn read_data(data: Bytes, chunk_type: &str, version: u16) -> XfbinResult<Box<dyn NuccChunk>> {
         match NuccChunkType::from_str(chunk_type).unwrap_or_default() {
-            NuccChunkType::NuccChunkNull => Ok((data, Box::new(NuccChunkNull(version)))),
+            NuccChunkType::NuccChunkNull => Ok(Box::new(NuccChunkNull(version))),
             NuccChunkType::NuccChunkPage => NuccChunkPage::read_boxed(&data, version),
-            NuccChunkType::NuccChunkIndex => Ok((data, Box::new(NuccChunkIndex))),
-            NuccChunkType::NuccChunkBinary => NuccChunkBinary::read_boxed(&data, version),
-            NuccChunkType::NuccChunkAnm => NuccChunkAnm::read_boxed(&data, version), // Fix: Change `u16` to `()`
-            NuccChunkType::NuccChunkAnmStrm => NuccChunkAnmStrm::read_boxed(&data, version),
-            NuccChunkType::NuccChunkAnmStrmFrame => { NuccChunkAnmStrmFrame::read_boxed(&data, version)}
-            NuccChunkType::NuccChunkCamera => NuccChunkCamera::read_boxed(&data, version),
-            NuccChunkType::NuccChunkLightDirc => NuccChunkLightDirc::read_boxed(&data, version),
-            NuccChunkType::NuccChunkLightPoint => NuccChunkLightPoint::read_boxed(&data, version),
-            NuccChunkType::NuccChunkLayerSet => NuccChunkLayerSet::read_boxed(&data, version),
-            NuccChunkType::NuccChunkAmbient => NuccChunkAmbient::read_boxed(&data, version),
-            NuccChunkType::NuccChunkMorphModel => NuccChunkMorphModel::read_boxed(&data, version),
-            // If the chunk type is unknown, return the data as an unknown chunk
-            _ => Ok((
-                data.clone(),
-                Box::new(NuccChunkUnknown {
-                    version,
-                    chunk_type: chunk_type.to_string(),
-                    data: data.into(),
-                }),
-            )),
+            NuccChunkType::NuccChunkIndex => Ok(Box::new(NuccChunkIndex)),
+            // Unknown types are decoded with their registered codec if they have one, and kept as they are otherwise
+            struct_type => (vtable(&struct_type)?.read_chunk)(data, chunk_type, version),
         }
     }
 
-    pub fn write_data(boxed: Box<dyn NuccChunk>, version: u16) -> Result<Vec<u8>, Box<dyn Error>> {
-        // Create a new cursor for writing
-        let mut output = Cursor::new(Vec::new());
-
-        // Downcast the boxed trait object to the specific chunk type
+    pub fn write_data(boxed: Box<dyn NuccChunk>) -> XfbinResult<Vec<u8>> {
         match boxed.chunk_type() {
-            NuccChunkType::NuccChunkNull | NuccChunkType::NuccChunkIndex => { return Ok(output.into_inner()); }
-            NuccChunkType::NuccChunkPage => { NuccChunkPage::write_boxed(boxed, &mut output, version)?; }
-            NuccChunkType::NuccChunkBinary => { NuccChunkBinary::write_boxed(boxed, &mut output, version)?; }
-            NuccChunkType::NuccChunkAnm => { NuccChunkAnm::write_boxed(boxed, &mut output, version)?; }
-            NuccChunkType::NuccChunkAnmStrm => { NuccChunkAnmStrm::write_boxed(boxed, &mut output, version)?; }
-            NuccChunkType::NuccChunkAnmStrmFrame => { NuccChunkAnmStrmFrame::write_boxed(boxed, &mut output, version)?; }
-            NuccChunkType::NuccChunkCamera => { NuccChunkCamera::write_boxed(boxed, &mut output, version)?; }
-            NuccChunkType::NuccChunkLightDirc => { NuccChunkLightDirc::write_boxed(boxed, &mut output, version)?; }
-            NuccChunkType::NuccChunkLightPoint => { NuccChunkLightPoint::write_boxed(boxed, &mut output, version)?; }
-            NuccChunkType::NuccChunkLayerSet => { NuccChunkLayerSet::write_boxed(boxed, &mut output, version)?; }
-            NuccChunkType::NuccChunkAmbient => { NuccChunkAmbient::write_boxed(boxed, &mut output, version)?; }
-            NuccChunkType::NuccChunkMorphModel => { NuccChunkMorphModel::write_boxed(boxed, &mut output, version)?; }
-            // If the chunk type is unknown, return the data as an unknown chunk
-            _ => {
-                let unknown = boxed
-                    .downcast::<NuccChunkUnknown>()
-                    .map(|x| x.data)
-                    .unwrap();
-                unknown.write(&mut output)?;
+            NuccChunkType::NuccChunkNull | NuccChunkType::NuccChunkIndex => Ok(Vec::new()),
+            NuccChunkType::NuccChunkPage => {
+                let mut output = Cursor::new(Vec::new());
+                NuccChunkPage::write_boxed(boxed, &mut output)?;
+
+                Ok(output.into_inner())
             }
+            struct_type => (vtable(&struct_type)?.write_chunk)(boxed),
         }
-
-        // Get the written data from the cursor and return it
-        Ok(output.into_inner())
     }
 }
@@ -6,11 +6,16 @@ use super::{NuccChunk, NuccChunkType};
 #[br(import_raw(version: u16))]
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct NuccChunkAmbient {
-    #[brw(ignore)]
+    #[br(calc = version)]
+    #[bw(ignore)]
     pub version: u16,
 
     #[br(count = 16)]
     pub data: Vec<u8>,
+
+    /// Whatever is left after the fields above, so padded chunks are written back as they were read
+    #[br(parse_with = binrw::helpers::until_eof)]
+    pub trailing: Vec<u8>,
 }
 
 impl NuccChunk for NuccChunkAmbient {
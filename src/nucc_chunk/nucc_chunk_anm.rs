@@ -2,24 +2,26 @@
 //! nuccAnm is a chunk that contains animation data.
 //! The extension ".anm" stands for "Animation".
 //!
-use std::{default, io::{Read, Seek, Write}};
+use std::{default, io::{Read, Seek, SeekFrom}};
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
-use binrw::{binrw, BinRead, BinReaderExt, BinResult, BinWrite, ReadOptions, WriteOptions};
+use binrw::{binrw, BinRead, BinReaderExt, BinResult, ReadOptions};
+use serde::{Deserialize, Serialize};
 
 use super::{NuccChunk, NuccChunkType};
 
-#[pyclass]
 #[binrw]
 #[brw(big)]
 #[br(import_raw(version: u16))]
+#[cfg_attr(feature = "python", pyclass)]
 #[derive(Debug, Clone, Default)]
 pub struct NuccChunkAnm {
-    #[brw(ignore)]
+    #[br(calc = version)]
+    #[bw(ignore)]
     pub version: u16,
 
     pub frame_count: u32,
 
-    #[bw(calc = 100)]
     pub frame_size: u32,
 
     #[bw(calc = entries.len() as u16)]
@@ -53,14 +55,16 @@ pub struct NuccChunkAnm {
 
     #[br(count = entry_count)]
     pub entries: Vec<AnmEntry>,
+
+    /// Whatever is left after the fields above, so padded chunks are written back as they were read
+    #[br(parse_with = binrw::helpers::until_eof)]
+    pub trailing: Vec<u8>,
 }
 
-#[pyclass]
 #[binrw]
-#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct AnmClump {
-
-    #[pyo3(get, set)]
     pub clump_index: u32,
 
     #[bw(calc = bone_material_indices.len() as u16)]
@@ -69,15 +73,14 @@ pub struct AnmClump {
     #[bw(calc = model_indices.len() as u16)]
     pub model_count: u16,
 
-    #[pyo3(get, set)]
     #[br(count = bone_material_count)]
     pub bone_material_indices: Vec<u32>,
 
-    #[pyo3(get, set)]
     #[br(count = model_count)]
     pub model_indices: Vec<u32>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl AnmClump {
     #[new]
@@ -97,14 +100,15 @@ impl AnmClump {
 
 
 
-#[pyclass]
 #[binrw]
-#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct CoordParent {
     pub parent: AnmCoord,
     pub child: AnmCoord,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl CoordParent {
     #[new]
@@ -128,14 +132,15 @@ impl CoordParent {
 }
 
 
-#[pyclass]
 #[binrw]
-#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct AnmCoord {
     pub clump_index: i16,
     pub coord_index: u16,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl AnmCoord {
     #[new]
@@ -159,10 +164,10 @@ impl AnmCoord {
     }
 }
 
-#[pyclass]
 #[binrw]
 #[brw(repr(u16))]
-#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub enum EntryFormat {
     Coord = 1,
     Camera = 2,
@@ -176,43 +181,40 @@ pub enum EntryFormat {
     Unknown,
 }
 
-#[pyclass]
 #[binrw]
-#[derive(Debug, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AnmEntry {
-    #[pyo3(get, set)]
     pub coord: AnmCoord,
 
-    #[pyo3(get, set)]
     pub entry_format: EntryFormat,
 
-    #[bw(calc = track_headers.len() as u16)]
+    #[bw(calc = tracks.len() as u16)]
     pub track_count: u16,
 
+    /// Only kept in the file; written from the tracks themselves
     #[br(count = track_count)]
+    #[bw(calc = tracks.iter().map(AnmTrack::header).collect())]
     pub track_headers: Vec<TrackHeader>,
 
     #[br(parse_with = read_tracks(track_headers.iter()))]
-    #[br(align_after = 4)]
+    #[brw(align_after = 4)]
     pub tracks: Vec<AnmTrack>,
 }
- 
 
-#[pyclass]
+
 #[binrw]
-#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct TrackHeader {
-    #[pyo3(get, set)]
     pub track_index: u16,
-    #[pyo3(get, set)]
     pub key_format: NuccAnmKeyFormat,
-    #[pyo3(get, set)]
     pub frame_count: u16,
 
     #[bw(calc = key_format.size_per_frame() as u16 * frame_count)]
     pub track_size: u16,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl TrackHeader {
     #[new]
@@ -241,10 +243,10 @@ impl TrackHeader {
     }
 }
 
-#[pyclass]
 #[binrw]
 #[brw(repr(u16))]
-#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub enum NuccAnmKeyFormat {
     Vector3Fixed = 0x5,
     Vector3Linear = 0x6,
@@ -300,25 +302,65 @@ impl NuccAnmKeyFormat {
         }
     }
 
-    
+    /// Whether `key` is the variant this format decodes to, or `None` if the format isn't decoded.
+    /// Formats with the same layout decode to the same variant.
+    pub fn matches_key(&self, key: &NuccAnmKey) -> Option<bool> {
+        let matches = match self {
+            NuccAnmKeyFormat::Vector3Fixed
+            | NuccAnmKeyFormat::EulerXYZFixed
+            | NuccAnmKeyFormat::EulerInterpolated
+            | NuccAnmKeyFormat::Vector3Table
+            | NuccAnmKeyFormat::Vector3TableNoInterp => matches!(key, NuccAnmKey::Vec3 { .. }),
+            NuccAnmKeyFormat::Vector3Linear | NuccAnmKeyFormat::Vector3Bezier => {
+                matches!(key, NuccAnmKey::Vec3Linear { .. })
+            }
+            NuccAnmKeyFormat::QuaternionLinear => matches!(key, NuccAnmKey::Vec4Linear { .. }),
+            NuccAnmKeyFormat::QuaternionTable => matches!(key, NuccAnmKey::Vec4 { .. }),
+            NuccAnmKeyFormat::QuaternionShortTable | NuccAnmKeyFormat::QuaternionShortTableNoInterp => {
+                matches!(key, NuccAnmKey::ShortVec4 { .. })
+            }
+            NuccAnmKeyFormat::Vector2Fixed => matches!(key, NuccAnmKey::Vec2 { .. }),
+            NuccAnmKeyFormat::Vector2Linear => matches!(key, NuccAnmKey::Vec2Linear { .. }),
+            NuccAnmKeyFormat::FloatFixed | NuccAnmKeyFormat::FloatTable | NuccAnmKeyFormat::FloatTableNoInterp => {
+                matches!(key, NuccAnmKey::Float { .. })
+            }
+            NuccAnmKeyFormat::FloatLinear => matches!(key, NuccAnmKey::FloatLinear { .. }),
+            NuccAnmKeyFormat::OpacityShortTable | NuccAnmKeyFormat::OpacityShortTableNoInterp => {
+                matches!(key, NuccAnmKey::I16Vec { .. })
+            }
+            NuccAnmKeyFormat::ScaleShortTable => matches!(key, NuccAnmKey::I16Vec3 { .. }),
+            NuccAnmKeyFormat::Vector3ShortLinear => matches!(key, NuccAnmKey::I16Vec3Linear { .. }),
+            NuccAnmKeyFormat::ColorRGBTable => matches!(key, NuccAnmKey::Color { .. }),
+            NuccAnmKeyFormat::Unknown => return None,
+        };
+
+        Some(matches)
+    }
 }
 
-#[pyclass]
 #[binrw]
-#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum NuccAnmKey {
 
-    Vec3 { values: (f32, f32, f32) },
-    Vec3Linear { frame: i32, values: (f32, f32, f32) },
-    Vec4Linear { frame: i32, values: (f32, f32, f32, f32) },
-    Float { values: f32 },
-    FloatLinear { frame: i32, values: f32 },
-    I16Vec { values: i16 },
-    I16Vec3 { values: (i16, i16, i16) },
-    ShortVec4 { values: (i16, i16, i16, i16) }, // QuaternionShortTable
+    Vec2 { values: (f32, f32) }, // Vector2Fixed
+    Vec2Linear { frame: i32, values: (f32, f32) }, // Vector2Linear
+    Vec3 { values: (f32, f32, f32) }, // Vector3Fixed, EulerXYZFixed, EulerInterpolated, Vector3Table, Vector3TableNoInterp
+    Vec3Linear { frame: i32, values: (f32, f32, f32) }, // Vector3Linear, Vector3Bezier
+    Vec4 { values: (f32, f32, f32, f32) }, // QuaternionTable
+    Vec4Linear { frame: i32, values: (f32, f32, f32, f32) }, // QuaternionLinear
+    Float { values: f32 }, // FloatFixed, FloatTable, FloatTableNoInterp
+    FloatLinear { frame: i32, values: f32 }, // FloatLinear
+    I16Vec { values: i16 }, // OpacityShortTable, OpacityShortTableNoInterp
+    I16Vec3 { values: (i16, i16, i16) }, // ScaleShortTable
+    I16Vec3Linear {
+        frame: i32,
+        #[brw(pad_after = 2)]
+        values: (i16, i16, i16),
+    }, // Vector3ShortLinear
+    ShortVec4 { values: (i16, i16, i16, i16) }, // QuaternionShortTable, QuaternionShortTableNoInterp
     Color { values: (u8, u8, u8) }, // ColorRGBTable
 
-    // Add more variants as needed...
     Unknown {}
     
 }
@@ -329,37 +371,38 @@ impl default::Default for NuccAnmKey {
     }
 }
 
-#[pyclass]
 #[binrw]
 #[br(import_raw(header: TrackHeader))]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct AnmTrack {
-    #[pyo3(get, set)]
+    #[br(calc = header.track_index)]
+    #[bw(ignore)]
+    pub track_index: u16,
+
+    /// The format every key is encoded in, so all keys have to be the variant it matches
+    #[br(calc = header.key_format.clone())]
+    #[bw(ignore)]
+    pub key_format: NuccAnmKeyFormat,
+
     #[br(parse_with = |r, o, _h: TrackHeader| read_track_data(r, o, header))]
     pub keys: Vec<NuccAnmKey>,
 }
 
-#[pymethods]
 impl AnmTrack {
-    #[new]
-    #[pyo3(signature = (keys = None))]
-    pub fn __new__(
-        keys: Option<Vec<NuccAnmKey>>,
-    ) -> Self {
-        Self {
-            keys: keys.unwrap_or_default(),
+    /// The header this track is written with
+    pub fn header(&self) -> TrackHeader {
+        TrackHeader {
+            track_index: self.track_index,
+            key_format: self.key_format.clone(),
+            frame_count: self.keys.len() as u16,
         }
     }
 
-    pub fn __repr__(&self) -> PyResult<String> {
-        Ok(format!(
-            "NuccAnmKeyFormat {{keyframes: {:?}}}",
-            self.keys
-        ))
-    }
-
-    fn __str__(&self) -> PyResult<String> {
-        self.__repr__()
+    /// The index of the first key that can't be encoded in the track's key format
+    pub fn mismatched_key(&self) -> Option<usize> {
+        self.keys
+            .iter()
+            .position(|key| self.key_format.matches_key(key) != Some(true))
     }
 }
 
@@ -382,94 +425,84 @@ where
 
 fn read_track_data<R: Read + Seek>(
     reader: &mut R,
-    _: &ReadOptions, 
+    _: &ReadOptions,
     header: TrackHeader
-
 ) -> BinResult<Vec<NuccAnmKey>> {
-    
-    let mut keyframes: Vec<NuccAnmKey> = Vec::new();
-
-      
-    for _ in 0..header.frame_count {
-        match header.key_format {
-            NuccAnmKeyFormat::Vector3Fixed => {
-                let x = reader.read_be::<f32>()?;
-                let y = reader.read_be::<f32>()?;
-                let z = reader.read_be::<f32>()?;
-                keyframes.push(NuccAnmKey::Vec3 { values: (x, y, z) });
-            }
+    (0..header.frame_count)
+        .map(|_| read_key(reader, &header.key_format))
+        .collect()
+}
 
-            NuccAnmKeyFormat::Vector3Linear => {
-                let frame = reader.read_be::<i32>()?;
-                let x = reader.read_be::<f32>()?;
-                let y = reader.read_be::<f32>()?;
-                let z = reader.read_be::<f32>()?;
-                keyframes.push(NuccAnmKey::Vec3Linear { frame, values: (x, y, z) });
-            }
+/// Read one key in the variant `NuccAnmKeyFormat::matches_key` expects for `key_format`
+pub(crate) fn read_key<R: Read + Seek>(reader: &mut R, key_format: &NuccAnmKeyFormat) -> BinResult<NuccAnmKey> {
+    let key = match key_format {
+        NuccAnmKeyFormat::Vector3Fixed
+        | NuccAnmKeyFormat::EulerXYZFixed
+        | NuccAnmKeyFormat::EulerInterpolated
+        | NuccAnmKeyFormat::Vector3Table
+        | NuccAnmKeyFormat::Vector3TableNoInterp => NuccAnmKey::Vec3 { values: reader.read_be()? },
+
+        NuccAnmKeyFormat::Vector3Linear | NuccAnmKeyFormat::Vector3Bezier => NuccAnmKey::Vec3Linear {
+            frame: reader.read_be()?,
+            values: reader.read_be()?,
+        },
+
+        NuccAnmKeyFormat::QuaternionLinear => NuccAnmKey::Vec4Linear {
+            frame: reader.read_be()?,
+            values: reader.read_be()?,
+        },
+
+        NuccAnmKeyFormat::QuaternionTable => NuccAnmKey::Vec4 { values: reader.read_be()? },
+
+        NuccAnmKeyFormat::QuaternionShortTable | NuccAnmKeyFormat::QuaternionShortTableNoInterp => {
+            NuccAnmKey::ShortVec4 { values: reader.read_be()? }
+        }
 
+        NuccAnmKeyFormat::Vector2Fixed => NuccAnmKey::Vec2 { values: reader.read_be()? },
 
-            NuccAnmKeyFormat::QuaternionShortTable => {
-                let x = reader.read_be::<i16>()?;
-                let y = reader.read_be::<i16>()?;
-                let z = reader.read_be::<i16>()?;
-                let w = reader.read_be::<i16>()?;
-                keyframes.push(NuccAnmKey::ShortVec4 { values: (x, y, z, w) });
-            }
-            
-            NuccAnmKeyFormat::QuaternionLinear => {
-                let frame = reader.read_be::<i32>()?;
-                let x = reader.read_be::<f32>()?;
-                let y = reader.read_be::<f32>()?;
-                let z = reader.read_be::<f32>()?;
-                let w = reader.read_be::<f32>()?;
-                keyframes.push(NuccAnmKey::Vec4Linear { frame, values: (x, y, z, w) });
-            }
+        NuccAnmKeyFormat::Vector2Linear => NuccAnmKey::Vec2Linear {
+            frame: reader.read_be()?,
+            values: reader.read_be()?,
+        },
 
-            NuccAnmKeyFormat::FloatFixed => {
-                let x = reader.read_be::<f32>()?;
-                keyframes.push(NuccAnmKey::Float { values: x });
-            }
+        NuccAnmKeyFormat::FloatFixed | NuccAnmKeyFormat::FloatTable | NuccAnmKeyFormat::FloatTableNoInterp => {
+            NuccAnmKey::Float { values: reader.read_be()? }
+        }
 
-            NuccAnmKeyFormat::FloatTable => {
-                let x = reader.read_be::<f32>()?;
-                keyframes.push(NuccAnmKey::Float { values: x });
-            }
+        NuccAnmKeyFormat::FloatLinear => NuccAnmKey::FloatLinear {
+            frame: reader.read_be()?,
+            values: reader.read_be()?,
+        },
 
-            NuccAnmKeyFormat::FloatLinear => {
-                let frame = reader.read_be::<i32>()?;
-                let x = reader.read_be::<f32>()?;
-                keyframes.push(NuccAnmKey::FloatLinear { frame, values: x });
-            }
+        NuccAnmKeyFormat::OpacityShortTable | NuccAnmKeyFormat::OpacityShortTableNoInterp => {
+            NuccAnmKey::I16Vec { values: reader.read_be()? }
+        }
 
-            NuccAnmKeyFormat::OpacityShortTable => {
-                let x = reader.read_be::<i16>()?;
-                keyframes.push(NuccAnmKey::I16Vec { values: x });
-            }
+        NuccAnmKeyFormat::ScaleShortTable => NuccAnmKey::I16Vec3 { values: reader.read_be()? },
 
-            NuccAnmKeyFormat::ScaleShortTable => {
-                let x = reader.read_be::<i16>()?;
-                let y = reader.read_be::<i16>()?;
-                let z = reader.read_be::<i16>()?;
-                keyframes.push(NuccAnmKey::I16Vec3 { values: (x, y, z) });
-            }
+        NuccAnmKeyFormat::Vector3ShortLinear => {
+            let key = NuccAnmKey::I16Vec3Linear {
+                frame: reader.read_be()?,
+                values: reader.read_be()?,
+            };
 
-            NuccAnmKeyFormat::ColorRGBTable => {
-                let r = reader.read_be::<u8>()?;
-                let g = reader.read_be::<u8>()?;
-                let b = reader.read_be::<u8>()?;
-                keyframes.push(NuccAnmKey::Color { values: (r, g, b) });
-            }
-            
-            // Return an error saying that the key format is not supported and the key format itself
-            _ => {
+            // Keys are padded to 4 bytes
+            reader.seek(SeekFrom::Current(2))?;
+            key
+        }
 
-                dbg!(format!("Key format {:?} is not supported", header.key_format));
-            }
+        NuccAnmKeyFormat::ColorRGBTable => NuccAnmKey::Color { values: reader.read_be()? },
+
+        // Keys of a format without a known layout can't be skipped over or written back, so refuse the chunk
+        NuccAnmKeyFormat::Unknown => {
+            return Err(binrw::Error::AssertFail {
+                pos: reader.stream_position()?,
+                message: format!("key format {:?} can't be decoded", key_format),
+            })
         }
-    
-    }
+    };
 
-    Ok(keyframes)
+    Ok(key)
 }
 
 
@@ -3,6 +3,9 @@
 //! The extension ".anmstrm" stands for "Animation Stream".
 //!
 use binrw::binrw;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use super::nucc_chunk_anm::CoordParent;
 use super::{NuccChunk, NuccChunkType};
@@ -13,12 +16,12 @@ use super::{NuccChunk, NuccChunkType};
 #[derive(Debug, Clone, PartialEq)]
 
 pub struct NuccChunkAnmStrm {
-    #[brw(ignore)]
+    #[br(calc = version)]
+    #[bw(ignore)]
     pub version: u16,
 
     pub frame_count: u32,
 
-    #[bw(calc = 100)]
     pub frame_size: u32,
 
     #[bw(calc = entries.len() as u16)]
@@ -29,7 +32,8 @@ pub struct NuccChunkAnmStrm {
     #[bw(calc = clumps.len() as u16)]
     pub clump_count: u16,
 
-    #[bw(calc = other_entry_indices.len() as u16)]
+    /// The indices list holds the unknown entries' indices after the other entries' ones
+    #[bw(calc = (other_entry_indices.len() as u16).saturating_sub(*unk_entry_count))]
     pub other_entry_count: u16,
 
     pub unk_entry_count: u16,
@@ -48,11 +52,16 @@ pub struct NuccChunkAnmStrm {
 
     #[br(count = entry_count)]
     pub entries: Vec<AnmStrmFrameInfo>,
+
+    /// Whatever is left after the fields above, so padded chunks are written back as they were read
+    #[br(parse_with = binrw::helpers::until_eof)]
+    pub trailing: Vec<u8>,
 }
 
 #[binrw]
 #[brw(big)]
-#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct AnmStrmClump {
     pub clump_index: u32,
 
@@ -66,19 +75,74 @@ pub struct AnmStrmClump {
     pub bone_material_indices: Vec<u32>,
 
     #[br(count = model_count)]
-    #[br(pad_after = 4 * (model_count as usize))]
+    #[brw(pad_after = 4 * (model_count as usize))]
     pub model_indices: Vec<u32>,
 }
 
+#[cfg(feature = "python")]
+#[pymethods]
+impl AnmStrmClump {
+    #[new]
+    #[pyo3(signature = (clump_index = 0, bone_material_indices = None, model_indices = None))]
+    pub fn __new__(
+        clump_index: u32,
+        bone_material_indices: Option<Vec<u32>>,
+        model_indices: Option<Vec<u32>>,
+    ) -> Self {
+        Self {
+            clump_index,
+            bone_material_indices: bone_material_indices.unwrap_or_default(),
+            model_indices: model_indices.unwrap_or_default(),
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "AnmStrmClump(clump_index={}, bone_material_indices={:?}, model_indices={:?})",
+            self.clump_index, self.bone_material_indices, self.model_indices
+        ))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
+}
+
 #[binrw]
 #[brw(big)]
-#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct AnmStrmFrameInfo {
     pub unknown: u16,
     pub frame_offset: u16,
     pub frame_number: u32,
 }
 
+#[cfg(feature = "python")]
+#[pymethods]
+impl AnmStrmFrameInfo {
+    #[new]
+    #[pyo3(signature = (unknown = 0, frame_offset = 0, frame_number = 0))]
+    pub fn __new__(unknown: u16, frame_offset: u16, frame_number: u32) -> Self {
+        Self {
+            unknown,
+            frame_offset,
+            frame_number,
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "AnmStrmFrameInfo(unknown={}, frame_offset={}, frame_number={})",
+            self.unknown, self.frame_offset, self.frame_number
+        ))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
+}
+
 impl NuccChunk for NuccChunkAnmStrm {
     fn chunk_type(&self) -> NuccChunkType {
         NuccChunkType::NuccChunkAnmStrm
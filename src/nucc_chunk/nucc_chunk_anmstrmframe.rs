@@ -2,6 +2,9 @@
 //! nuccAnmStrm is a chunk that contains animation frame data for the previous generation.
 //! The extension ".anmstrmframe" stands for "Animation Stream Frame
 use binrw::binrw;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use super::{NuccChunk, NuccChunkType};
 
@@ -13,7 +16,8 @@ use super::nucc_helper::*;
 #[br(import_raw(version: u16))]
 #[derive(Debug, Clone, PartialEq)]
 pub struct NuccChunkAnmStrmFrame {
-    #[brw(ignore)]
+    #[br(calc = version)]
+    #[bw(ignore)]
     pub version: u16,
 
     pub frame_number: u32,
@@ -25,11 +29,15 @@ pub struct NuccChunkAnmStrmFrame {
 
     #[br(count = entry_count)]
     pub entries: Vec<AnmStrmEntry>,
+
+    /// Whatever is left after the fields above, so padded chunks are written back as they were read
+    #[br(parse_with = binrw::helpers::until_eof)]
+    pub trailing: Vec<u8>,
 }
 
 #[binrw]
 #[brw(big)]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct AnmStrmEntry {
     pub coord: AnmCoord,
     pub entry_format: u16,
@@ -41,7 +49,7 @@ pub struct AnmStrmEntry {
 
 #[binrw]
 #[brw(big)]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[br(import(entry_format: u16))]
 pub enum Entry {
     #[br(pre_assert(entry_format == 1))]
@@ -71,7 +79,8 @@ pub enum Entry {
 
 #[binrw]
 #[brw(big)]
-#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct AnmEntryBone {
     pub frame_count: i32,
     pub location: Vector3,
@@ -80,9 +89,43 @@ pub struct AnmEntryBone {
     pub toggled: f32,
 }
 
+#[cfg(feature = "python")]
+#[pymethods]
+impl AnmEntryBone {
+    #[new]
+    #[pyo3(signature = (frame_count = 0, location = None, rotation = None, scale = None, toggled = 0.0))]
+    pub fn __new__(
+        frame_count: i32,
+        location: Option<Vector3>,
+        rotation: Option<Vector4>,
+        scale: Option<Vector3>,
+        toggled: f32,
+    ) -> Self {
+        Self {
+            frame_count,
+            location: location.unwrap_or_default(),
+            rotation: rotation.unwrap_or_default(),
+            scale: scale.unwrap_or_default(),
+            toggled,
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "AnmEntryBone(frame_count={:?}, location={:?}, rotation={:?}, scale={:?}, toggled={:?})",
+            self.frame_count, self.location, self.rotation, self.scale, self.toggled
+        ))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
+}
+
 #[binrw]
 #[brw(big)]
-#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct AnmEntryCamera {
     pub frame_count: i32,
     pub location: Vector3,
@@ -91,17 +134,79 @@ pub struct AnmEntryCamera {
     pub scale: Vector3,
 }
 
+#[cfg(feature = "python")]
+#[pymethods]
+impl AnmEntryCamera {
+    #[new]
+    #[pyo3(signature = (frame_count = 0, location = None, rotation = None, fov = 0.0, scale = None))]
+    pub fn __new__(
+        frame_count: i32,
+        location: Option<Vector3>,
+        rotation: Option<Vector4>,
+        fov: f32,
+        scale: Option<Vector3>,
+    ) -> Self {
+        Self {
+            frame_count,
+            location: location.unwrap_or_default(),
+            rotation: rotation.unwrap_or_default(),
+            fov,
+            scale: scale.unwrap_or_default(),
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "AnmEntryCamera(frame_count={:?}, location={:?}, rotation={:?}, fov={:?}, scale={:?})",
+            self.frame_count, self.location, self.rotation, self.fov, self.scale
+        ))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
+}
+
 #[binrw]
 #[brw(big)]
-#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct AnmEntryMaterial {
     pub frame_count: i32,
     pub ambient_color: [f32; 16],
 }
 
+#[cfg(feature = "python")]
+#[pymethods]
+impl AnmEntryMaterial {
+    #[new]
+    #[pyo3(signature = (frame_count = 0, ambient_color = None))]
+    pub fn __new__(
+        frame_count: i32,
+        ambient_color: Option<[f32; 16]>,
+    ) -> Self {
+        Self {
+            frame_count,
+            ambient_color: ambient_color.unwrap_or_default(),
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "AnmEntryMaterial(frame_count={:?}, ambient_color={:?})",
+            self.frame_count, self.ambient_color
+        ))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
+}
+
 #[binrw]
 #[brw(big)]
-#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct AnmEntryLightDirc {
     pub frame_count: i32,
     pub color: Vector3,
@@ -109,9 +214,41 @@ pub struct AnmEntryLightDirc {
     pub direction: Vector4,
 }
 
+#[cfg(feature = "python")]
+#[pymethods]
+impl AnmEntryLightDirc {
+    #[new]
+    #[pyo3(signature = (frame_count = 0, color = None, intensity = 0.0, direction = None))]
+    pub fn __new__(
+        frame_count: i32,
+        color: Option<Vector3>,
+        intensity: f32,
+        direction: Option<Vector4>,
+    ) -> Self {
+        Self {
+            frame_count,
+            color: color.unwrap_or_default(),
+            intensity,
+            direction: direction.unwrap_or_default(),
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "AnmEntryLightDirc(frame_count={:?}, color={:?}, intensity={:?}, direction={:?})",
+            self.frame_count, self.color, self.intensity, self.direction
+        ))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
+}
+
 #[binrw]
 #[brw(big)]
-#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct AnmEntryLightPoint {
     pub frame_count: i32,
     pub color: Vector3,
@@ -121,24 +258,117 @@ pub struct AnmEntryLightPoint {
     pub falloff: f32,
 }
 
+#[cfg(feature = "python")]
+#[pymethods]
+impl AnmEntryLightPoint {
+    #[new]
+    #[pyo3(signature = (frame_count = 0, color = None, position = None, intensity = 0.0, radius = 0.0, falloff = 0.0))]
+    pub fn __new__(
+        frame_count: i32,
+        color: Option<Vector3>,
+        position: Option<Vector3>,
+        intensity: f32,
+        radius: f32,
+        falloff: f32,
+    ) -> Self {
+        Self {
+            frame_count,
+            color: color.unwrap_or_default(),
+            position: position.unwrap_or_default(),
+            intensity,
+            radius,
+            falloff,
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "AnmEntryLightPoint(frame_count={:?}, color={:?}, position={:?}, intensity={:?}, radius={:?}, falloff={:?})",
+            self.frame_count, self.color, self.position, self.intensity, self.radius, self.falloff
+        ))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
+}
+
 #[binrw]
 #[brw(big)]
-#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct AnmEntryAmbient {
     pub frame_count: i32,
     pub color: Vector3,
     pub intensity: f32,
 }
 
+#[cfg(feature = "python")]
+#[pymethods]
+impl AnmEntryAmbient {
+    #[new]
+    #[pyo3(signature = (frame_count = 0, color = None, intensity = 0.0))]
+    pub fn __new__(
+        frame_count: i32,
+        color: Option<Vector3>,
+        intensity: f32,
+    ) -> Self {
+        Self {
+            frame_count,
+            color: color.unwrap_or_default(),
+            intensity,
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "AnmEntryAmbient(frame_count={:?}, color={:?}, intensity={:?})",
+            self.frame_count, self.color, self.intensity
+        ))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
+}
+
 #[binrw]
 #[brw(big)]
-#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct AnmEntryMorphModel {
     pub frame_count: i32,
     #[br(count = frame_count)]
     pub morph_weight: Vec<f32>,
 }
 
+#[cfg(feature = "python")]
+#[pymethods]
+impl AnmEntryMorphModel {
+    #[new]
+    #[pyo3(signature = (frame_count = 0, morph_weight = None))]
+    pub fn __new__(
+        frame_count: i32,
+        morph_weight: Option<Vec<f32>>,
+    ) -> Self {
+        Self {
+            frame_count,
+            morph_weight: morph_weight.unwrap_or_default(),
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "AnmEntryMorphModel(frame_count={:?}, morph_weight={:?})",
+            self.frame_count, self.morph_weight
+        ))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
+}
+
 impl NuccChunk for NuccChunkAnmStrmFrame {
     fn chunk_type(&self) -> NuccChunkType {
         NuccChunkType::NuccChunkAnmStrmFrame
@@ -1,20 +1,48 @@
-use binrw::binrw;
+use binrw::{binrw, BinResult, WriteOptions};
+use bytes::Bytes;
+use std::io::{Seek, Write};
 
 use super::{NuccChunk, NuccChunkType};
+use crate::error::XfbinResult;
 
 #[binrw]
 #[brw(big)]
 #[br(import_raw(version: u16))]
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct NuccChunkBinary {
-    #[brw(ignore)]
+    #[br(calc = version)]
+    #[bw(ignore)]
     pub version: u16,
 
     #[bw(calc = data.len() as u32)]
     pub size: u32,
 
-    #[br(count = size)]
-    pub data: Vec<u8>,
+    #[br(count = size, map = |data: Vec<u8>| Bytes::from(data))]
+    #[bw(write_with = write_bytes)]
+    pub data: Bytes,
+}
+
+impl NuccChunkBinary {
+    /// Read the chunk from a shared buffer, keeping the payload as a view into it instead of copying it
+    pub fn read_bytes(data: Bytes, version: u16) -> XfbinResult<Self> {
+        let size = data
+            .get(..4)
+            .map(|size| u32::from_be_bytes([size[0], size[1], size[2], size[3]]) as usize)
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?;
+
+        if data.len() < 4 + size {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+
+        Ok(Self {
+            version,
+            data: data.slice(4..4 + size),
+        })
+    }
+}
+
+fn write_bytes<W: Write + Seek>(data: &Bytes, writer: &mut W, _: &WriteOptions, _: ()) -> BinResult<()> {
+    Ok(writer.write_all(data)?)
 }
 
 impl NuccChunk for NuccChunkBinary {
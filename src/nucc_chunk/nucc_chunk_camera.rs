@@ -7,11 +7,16 @@ use super::{NuccChunk, NuccChunkType};
 #[br(import_raw(version: u16))]
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct NuccChunkCamera {
-    #[brw(ignore)]
+    #[br(calc = version)]
+    #[bw(ignore)]
     pub version: u16,
 
     #[brw(pad_before = 4)]
     pub fov: f32,
+
+    /// Whatever is left after the fields above, so padded chunks are written back as they were read
+    #[br(parse_with = binrw::helpers::until_eof)]
+    pub trailing: Vec<u8>,
 }
 
 impl NuccChunk for NuccChunkCamera {
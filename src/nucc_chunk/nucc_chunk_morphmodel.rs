@@ -7,7 +7,8 @@ use super::{NuccChunk, NuccChunkType};
 #[brw(big)]
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct NuccChunkMorphModel {
-    #[brw(ignore)]
+    #[br(calc = version)]
+    #[bw(ignore)]
     pub version: u16,
 
     #[brw(pad_after = 2)]
@@ -16,6 +17,10 @@ pub struct NuccChunkMorphModel {
 
     #[br(count = (count * 8) + 8)]
     pub data: Vec<u8>,
+
+    /// Whatever is left after the fields above, so padded chunks are written back as they were read
+    #[br(parse_with = binrw::helpers::until_eof)]
+    pub trailing: Vec<u8>,
 }
 
 impl NuccChunk for NuccChunkMorphModel {
@@ -8,8 +8,8 @@ use super::{NuccChunk, NuccChunkType};
 #[br(import_raw(version: u16))]
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct NuccChunkPage {
-    #[brw(ignore)]
-    #[bw(args_raw = version)]
+    #[br(calc = version)]
+    #[bw(ignore)]
     pub version: u16,
 
     pub map_index_count: u32,
@@ -1,3 +1,5 @@
+use bytes::Bytes;
+
 use super::{NuccChunk, NuccChunkType};
 
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -6,7 +8,7 @@ pub struct NuccChunkUnknown {
 
     pub chunk_type: String,
 
-    pub data: Vec<u8>,
+    pub data: Bytes,
 }
 
 impl NuccChunk for NuccChunkUnknown {
@@ -1,14 +1,36 @@
 use binrw::binrw;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::hash::Hash;
 
 #[binrw]
-#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct Vector3 {
     pub x: f32,
     pub y: f32,
     pub z: f32,
 }
 
+#[cfg(feature = "python")]
+#[pymethods]
+impl Vector3 {
+    #[new]
+    #[pyo3(signature = (x = 0.0, y = 0.0, z = 0.0))]
+    pub fn __new__(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("Vector3(x={}, y={}, z={})", self.x, self.y, self.z))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
+}
+
 impl Eq for Vector3 {}
 
 impl Hash for Vector3 {
@@ -24,7 +46,8 @@ impl Hash for Vector3 {
 }
 
 #[binrw]
-#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct Vector4 {
     pub x: f32,
     pub y: f32,
@@ -32,6 +55,24 @@ pub struct Vector4 {
     pub w: f32,
 }
 
+#[cfg(feature = "python")]
+#[pymethods]
+impl Vector4 {
+    #[new]
+    #[pyo3(signature = (x = 0.0, y = 0.0, z = 0.0, w = 0.0))]
+    pub fn __new__(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("Vector4(x={}, y={}, z={}, w={})", self.x, self.y, self.z, self.w))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
+}
+
 impl Eq for Vector4 {}
 
 impl Hash for Vector4 {
@@ -48,7 +89,7 @@ impl Hash for Vector4 {
 }
 
 #[binrw]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Vector3Short {
     pub x: i16,
     pub y: i16,
@@ -70,7 +111,7 @@ impl Hash for Vector3Short {
 }
 
 #[binrw]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct VectorShort {
     pub x: i16
 }
@@ -88,28 +129,28 @@ impl Hash for VectorShort {
 }
 
 #[binrw]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct KeyframeVector3 {
     pub frame: i32,
     pub value: Vector3,
 }
 
 #[binrw]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct KeyframeVector4 {
     pub frame: i32,
     pub value: Vector4,
 }
 
 #[binrw]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct KeyframeFloat {
     pub frame: i32,
     pub value: f32,
 }
 
 #[binrw]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct QuaternionShort {
     pub x: i16,
     pub y: i16,
@@ -133,7 +174,7 @@ impl Hash for QuaternionShort {
 }
 
 #[binrw]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct RGB {
     pub r: u8,
     pub g: u8,
@@ -0,0 +1,319 @@
+//! # Python bindings
+//! The `xfbin_lib` extension module, layered on top of the Rust document model.
+//! Types that hold lists are mirrored by wrapper classes whose lists live on the Python heap,
+//! so scripts can mutate them in place; everything else is exposed directly.
+//!
+pub mod py_codec;
+pub mod py_error;
+pub mod py_nucc_anm;
+pub mod py_nucc_anmstrm;
+pub mod py_xfbin;
+pub mod py_xfbin_reader;
+
+use pyo3::prelude::*;
+use hashbrown::HashMap;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::PyList;
+use pyo3::wrap_pyfunction;
+use pyo3::PyClass;
+
+use crate::nucc::vtable::{vtable, vtables, NuccStructVTable};
+use crate::nucc::{NuccAnm, NuccAnmStrm, NuccAnmStrmFrame, NuccStruct, NuccStructInfo, NuccStructReference};
+use crate::nucc_chunk::nucc_chunk_anm::{AnmCoord, EntryFormat, NuccAnmKey, NuccAnmKeyFormat};
+use crate::nucc_chunk::nucc_helper::{Vector3, Vector4};
+use crate::nucc_chunk::NuccChunkType;
+use crate::json::PayloadMode;
+use crate::diff::{DiffKind, EntryDiff, FieldDiff, KeyDiff, StringTableDiff, StructDiff, TrackDiff, XfbinDiff};
+use crate::merge::{MergeAction, MergeEntry, MergePolicy, MergeReport};
+use crate::reference::{ReferenceTarget, ResolvedReference};
+use crate::validate::{Finding, Severity};
+use crate::xfbin::XfbinLayout;
+use crate::xfbin_reader::XfbinChunkEntry;
+use crate::XfbinError;
+
+pub use py_codec::PyCodecStruct;
+pub use py_nucc_anm::{PyAnmEntry, PyAnmTrack, PyNuccAnm};
+pub use py_nucc_anmstrm::{PyAnmStrmEntry, PyNuccAnmStrm, PyNuccAnmStrmFrame};
+pub use py_xfbin::{PyXfbin, PyXfbinPage};
+pub use py_xfbin_reader::PyXfbinReader;
+
+/// A Python class mirroring a Rust model type.
+pub trait PyWrapper: Sized {
+    type Inner;
+
+    fn from_inner(py: Python, inner: Self::Inner) -> PyResult<Self>;
+    fn to_inner(&self, py: Python) -> PyResult<Self::Inner>;
+}
+
+/// Build a Python list of wrapper objects from Rust values.
+pub(crate) fn wrap_list<W>(py: Python, items: Vec<W::Inner>) -> PyResult<Py<PyList>>
+where
+    W: PyWrapper + PyClass + Into<PyClassInitializer<W>>,
+{
+    let items = items
+        .into_iter()
+        .map(|item| Py::new(py, W::from_inner(py, item)?))
+        .collect::<PyResult<Vec<_>>>()?;
+
+    Ok(PyList::new_bound(py, items).into())
+}
+
+/// Read a Python list of wrapper objects back into Rust values.
+pub(crate) fn unwrap_list<W>(py: Python, list: &Py<PyList>) -> PyResult<Vec<W::Inner>>
+where
+    W: PyWrapper + PyClass,
+{
+    list.bind(py)
+        .iter()
+        .map(|item| item.downcast::<W>()?.borrow().to_inner(py))
+        .collect()
+}
+
+/// Build a Python list of pyclass objects.
+pub(crate) fn into_py_list<T>(py: Python, items: Vec<T>) -> PyResult<Py<PyList>>
+where
+    T: PyClass + Into<PyClassInitializer<T>>,
+{
+    let items = items
+        .into_iter()
+        .map(|item| Py::new(py, item))
+        .collect::<PyResult<Vec<_>>>()?;
+
+    Ok(PyList::new_bound(py, items).into())
+}
+
+pub fn nucc_struct_into_py(py: Python, nucc_struct: Box<dyn NuccStruct>) -> PyResult<PyObject> {
+    (vtable(&nucc_struct.chunk_type())?.into_py)(py, nucc_struct)
+}
+
+pub fn nucc_struct_from_py(obj: &Bound<'_, PyAny>) -> PyResult<Box<dyn NuccStruct>> {
+    // Built-in classes are looked up by type; instances of classes registered with `register_codec`
+    // aren't, so whatever is left is offered to each type in turn
+    if let Some(vtable) = vtable_by_class(obj.py()).get(&(obj.get_type().as_type_ptr() as usize)) {
+        if let Some(nucc_struct) = (vtable.from_py)(obj)? {
+            return Ok(nucc_struct);
+        }
+    }
+
+    for vtable in vtables() {
+        if let Some(nucc_struct) = (vtable.from_py)(obj)? {
+            return Ok(nucc_struct);
+        }
+    }
+
+    Err(pyo3::exceptions::PyTypeError::new_err(format!(
+        "Unsupported NuccStruct type: {}",
+        obj.get_type().name()?
+    )))
+}
+
+/// The struct type table keyed by the address of each type's Python class
+fn vtable_by_class(py: Python) -> &'static HashMap<usize, &'static NuccStructVTable> {
+    static BY_CLASS: GILOnceCell<HashMap<usize, &'static NuccStructVTable>> = GILOnceCell::new();
+
+    BY_CLASS.get_or_init(py, || {
+        vtables()
+            .iter()
+            .filter_map(|vtable| Some((vtable.py_type?(py).as_type_ptr() as usize, vtable)))
+            .collect()
+    })
+}
+
+pub(crate) fn wrapped_into_py<W>(py: Python, nucc_struct: Box<dyn NuccStruct>) -> PyResult<PyObject>
+where
+    W: PyWrapper + PyClass + Into<PyClassInitializer<W>>,
+    W::Inner: NuccStruct,
+{
+    let chunk_type = nucc_struct.chunk_type();
+    let inner = nucc_struct.downcast::<W::Inner>().map_err(|_| type_mismatch(chunk_type))?;
+
+    Ok(Py::new(py, W::from_inner(py, *inner)?)?.into_py(py))
+}
+
+pub(crate) fn wrapped_from_py<W>(obj: &Bound<'_, PyAny>) -> PyResult<Option<Box<dyn NuccStruct>>>
+where
+    W: PyWrapper + PyClass,
+    W::Inner: NuccStruct,
+{
+    match obj.downcast::<W>() {
+        Ok(wrapper) => Ok(Some(Box::new(wrapper.borrow().to_inner(obj.py())?))),
+        Err(_) => Ok(None),
+    }
+}
+
+pub(crate) fn direct_into_py<T>(py: Python, nucc_struct: Box<dyn NuccStruct>) -> PyResult<PyObject>
+where
+    T: NuccStruct + PyClass + Into<PyClassInitializer<T>>,
+{
+    let chunk_type = nucc_struct.chunk_type();
+    let nucc_struct = nucc_struct.downcast::<T>().map_err(|_| type_mismatch(chunk_type))?;
+
+    Ok(Py::new(py, *nucc_struct)?.into_py(py))
+}
+
+pub(crate) fn direct_from_py<T>(obj: &Bound<'_, PyAny>) -> PyResult<Option<Box<dyn NuccStruct>>>
+where
+    T: NuccStruct + PyClass + Clone,
+{
+    match obj.downcast::<T>() {
+        Ok(nucc_struct) => Ok(Some(Box::new(nucc_struct.borrow().clone()))),
+        Err(_) => Ok(None),
+    }
+}
+
+pub(crate) fn codec_into_py(py: Python, nucc_struct: Box<dyn NuccStruct>) -> PyResult<PyObject> {
+    match nucc_struct.downcast::<PyCodecStruct>() {
+        Ok(py_codec_struct) => py_codec_struct.into_object(py),
+        // Types registered from Rust have no Python counterpart
+        Err(_) => Err(type_mismatch(NuccChunkType::NuccChunkCustom)),
+    }
+}
+
+pub(crate) fn codec_from_py(obj: &Bound<'_, PyAny>) -> PyResult<Option<Box<dyn NuccStruct>>> {
+    Ok(PyCodecStruct::from_object(obj)?.map(|py_codec_struct| Box::new(py_codec_struct) as Box<dyn NuccStruct>))
+}
+
+fn type_mismatch(expected: NuccChunkType) -> PyErr {
+    XfbinError::UnsupportedType(expected.to_string()).into()
+}
+
+
+#[pyfunction]
+pub fn read_xfbin(py: Python, filepath: &str) -> PyResult<PyXfbin> {
+    let xfbin = py.allow_threads(|| crate::read_xfbin(filepath))?;
+
+    PyXfbin::from_inner(py, xfbin)
+}
+
+#[pyfunction]
+pub fn read_xfbin_buf(py: Python, buf: Vec<u8>) -> PyResult<PyXfbin> {
+    let xfbin = py.allow_threads(|| crate::read_xfbin_buf(buf))?;
+
+    PyXfbin::from_inner(py, xfbin)
+}
+
+#[pyfunction]
+pub fn open_xfbin(py: Python, filepath: &str) -> PyResult<PyXfbinReader> {
+    let inner = py.allow_threads(|| crate::open_xfbin_mmap(filepath))?;
+
+    Ok(PyXfbinReader { inner })
+}
+
+#[pyfunction]
+pub fn write_xfbin(py: Python, xfbin: PyRef<PyXfbin>, filepath: &str) -> PyResult<()> {
+    let xfbin = xfbin.to_inner(py)?;
+
+    Ok(py.allow_threads(|| crate::write_xfbin(xfbin, filepath))?)
+}
+
+#[pyfunction]
+pub fn write_xfbin_buf(py: Python, xfbin: PyRef<PyXfbin>) -> PyResult<Vec<u8>> {
+    let xfbin = xfbin.to_inner(py)?;
+
+    Ok(py.allow_threads(|| crate::write_xfbin_buf(xfbin))?)
+}
+
+#[pyfunction]
+pub fn validate_xfbin(py: Python, filepath: &str) -> PyResult<Vec<Finding>> {
+    Ok(py.allow_threads(|| crate::validate_xfbin(filepath))?)
+}
+
+#[pyfunction]
+pub fn validate_xfbin_buf(py: Python, buf: Vec<u8>) -> PyResult<Vec<Finding>> {
+    Ok(py.allow_threads(|| crate::validate_xfbin_buf(buf))?)
+}
+
+#[pyfunction]
+pub fn to_json(py: Python, xfbin: PyRef<PyXfbin>) -> PyResult<String> {
+    let xfbin = xfbin.to_inner(py)?;
+
+    Ok(py.allow_threads(move || crate::to_json_string(&xfbin))?)
+}
+
+#[pyfunction]
+pub fn from_json(py: Python, json: &str) -> PyResult<PyXfbin> {
+    let xfbin = py.allow_threads(|| crate::from_json_str(json))?;
+
+    PyXfbin::from_inner(py, xfbin)
+}
+
+#[pyfunction]
+#[pyo3(signature = (xfbin, filepath, payloads = PayloadMode::Inline))]
+pub fn write_xfbin_json(py: Python, xfbin: PyRef<PyXfbin>, filepath: &str, payloads: PayloadMode) -> PyResult<()> {
+    let xfbin = xfbin.to_inner(py)?;
+
+    Ok(py.allow_threads(move || crate::write_xfbin_json(&xfbin, filepath, payloads))?)
+}
+
+#[pyfunction]
+pub fn read_xfbin_json(py: Python, filepath: &str) -> PyResult<PyXfbin> {
+    let xfbin = py.allow_threads(|| crate::read_xfbin_json(filepath))?;
+
+    PyXfbin::from_inner(py, xfbin)
+}
+
+#[pyfunction]
+pub fn unpack_to_dir(py: Python, xfbin: PyRef<PyXfbin>, dirpath: &str) -> PyResult<()> {
+    let xfbin = xfbin.to_inner(py)?;
+
+    Ok(py.allow_threads(move || crate::unpack_to_dir(&xfbin, dirpath))?)
+}
+
+#[pyfunction]
+pub fn repack_from_dir(py: Python, dirpath: &str) -> PyResult<PyXfbin> {
+    let xfbin = py.allow_threads(|| crate::repack_from_dir(dirpath))?;
+
+    PyXfbin::from_inner(py, xfbin)
+}
+
+#[pymodule]
+fn xfbin_lib(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    py_error::register_exceptions(py, m)?;
+
+    m.add_function(wrap_pyfunction!(read_xfbin, m)?)?;
+    m.add_function(wrap_pyfunction!(read_xfbin_buf, m)?)?;
+    m.add_function(wrap_pyfunction!(open_xfbin, m)?)?;
+    m.add_function(wrap_pyfunction!(write_xfbin, m)?)?;
+    m.add_function(wrap_pyfunction!(write_xfbin_buf, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_xfbin, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_xfbin_buf, m)?)?;
+    m.add_function(wrap_pyfunction!(to_json, m)?)?;
+    m.add_function(wrap_pyfunction!(from_json, m)?)?;
+    m.add_function(wrap_pyfunction!(write_xfbin_json, m)?)?;
+    m.add_function(wrap_pyfunction!(read_xfbin_json, m)?)?;
+    m.add_function(wrap_pyfunction!(unpack_to_dir, m)?)?;
+    m.add_function(wrap_pyfunction!(repack_from_dir, m)?)?;
+    m.add_function(wrap_pyfunction!(py_codec::register_codec, m)?)?;
+    m.add_class::<PyXfbin>()?;
+    m.add_class::<PyXfbinPage>()?;
+    m.add_class::<XfbinLayout>()?;
+    m.add_class::<PyXfbinReader>()?;
+    m.add_class::<XfbinChunkEntry>()?;
+    m.add_class::<NuccStructInfo>()?;
+    m.add_class::<NuccStructReference>()?;
+    m.add_class::<ReferenceTarget>()?;
+    m.add_class::<ResolvedReference>()?;
+    m.add_class::<Severity>()?;
+    m.add_class::<Finding>()?;
+    m.add_class::<DiffKind>()?;
+    m.add_class::<XfbinDiff>()?;
+    m.add_class::<StringTableDiff>()?;
+    m.add_class::<StructDiff>()?;
+    m.add_class::<FieldDiff>()?;
+    m.add_class::<EntryDiff>()?;
+    m.add_class::<TrackDiff>()?;
+    m.add_class::<KeyDiff>()?;
+    m.add_class::<PayloadMode>()?;
+    m.add_class::<MergePolicy>()?;
+    m.add_class::<MergeAction>()?;
+    m.add_class::<MergeEntry>()?;
+    m.add_class::<MergeReport>()?;
+    m.add_class::<Vector3>()?;
+    m.add_class::<Vector4>()?;
+
+    for vtable in vtables() {
+        (vtable.add_py_classes)(m)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyType};
+use std::sync::{LazyLock, RwLock};
+
+use super::*;
+use crate::codec::{self, Bytes, IndexMap, NuccChunkCodec};
+use crate::error::XfbinResult;
+use crate::nucc::{impl_nucc_info, NuccInfo};
+use crate::nucc_chunk::NuccChunk;
+
+/// Version given to structs of Python-defined types that don't have a `version` attribute
+const DEFAULT_VERSION: u16 = 121;
+
+/// Classes registered with `register_codec`, for recognizing their instances when converting back to Rust
+static CLASSES: LazyLock<RwLock<Vec<Py<PyType>>>> = LazyLock::new(Default::default);
+
+/// A chunk of a Python-defined type, still in its encoded form
+#[derive(Debug)]
+pub struct PyCodecChunk {
+    version: u16,
+    data: Bytes,
+
+    /// The class to decode the data with, kept here since every Python-defined type shares this chunk type
+    class: Py<PyType>,
+}
+
+impl NuccChunk for PyCodecChunk {
+    fn chunk_type(&self) -> NuccChunkType {
+        NuccChunkType::NuccChunkCustom
+    }
+
+    fn version(&self) -> u16 {
+        self.version
+    }
+}
+
+/// An instance of a Python-defined type. Clones share the instance.
+#[derive(Debug, Clone)]
+pub struct PyCodecStruct {
+    struct_info: NuccStructInfo,
+    version: u16,
+    object: PyObject,
+}
+
+impl_nucc_info!(PyCodecStruct, struct_info);
+
+impl NuccStruct for PyCodecStruct {
+    fn chunk_type(&self) -> NuccChunkType {
+        NuccChunkType::NuccChunkCustom
+    }
+
+    fn version(&self) -> u16 {
+        self.version
+    }
+}
+
+impl PyCodecStruct {
+    /// The Python instance, tagged with the struct info and version it was read with
+    pub(crate) fn into_object(self, py: Python) -> PyResult<PyObject> {
+        let object = self.object.bind(py);
+        object.setattr("struct_info", self.struct_info.into_py(py))?;
+        object.setattr("version", self.version)?;
+
+        Ok(self.object)
+    }
+
+    /// Wrap `object` if it's an instance of a registered class
+    pub(crate) fn from_object(object: &Bound<'_, PyAny>) -> PyResult<Option<Self>> {
+        let registered = CLASSES
+            .read()
+            .unwrap()
+            .iter()
+            .any(|class| object.is_instance(class.bind(object.py())).unwrap_or_default());
+
+        if !registered {
+            return Ok(None);
+        }
+
+        let version = match object.getattr("version") {
+            Ok(version) => version.extract()?,
+            Err(_) => DEFAULT_VERSION,
+        };
+
+        Ok(Some(Self {
+            struct_info: object.getattr("struct_info")?.extract()?,
+            version,
+            object: object.clone().unbind(),
+        }))
+    }
+}
+
+struct PyCodec {
+    class: Py<PyType>,
+}
+
+impl NuccChunkCodec for PyCodec {
+    type Chunk = PyCodecChunk;
+    type Struct = PyCodecStruct;
+
+    fn decode(&self, data: Bytes, version: u16) -> XfbinResult<PyCodecChunk> {
+        let class = Python::with_gil(|py| self.class.clone_ref(py));
+
+        Ok(PyCodecChunk { version, data, class })
+    }
+
+    fn encode(&self, chunk: PyCodecChunk) -> XfbinResult<Vec<u8>> {
+        Ok(chunk.data.to_vec())
+    }
+
+    fn to_struct(
+        &self,
+        chunk: PyCodecChunk,
+        _struct_infos: &[NuccStructInfo],
+        _struct_references: &[NuccStructReference],
+    ) -> XfbinResult<PyCodecStruct> {
+        let object = Python::with_gil(|py| {
+            let data = PyBytes::new_bound(py, &chunk.data);
+
+            chunk
+                .class
+                .bind(py)
+                .call_method1("from_bytes", (data, chunk.version))
+                .map(Bound::unbind)
+                .map_err(|e| codec_error(py, "from_bytes", e))
+        })?;
+
+        Ok(PyCodecStruct {
+            struct_info: Default::default(),
+            version: chunk.version,
+            object,
+        })
+    }
+
+    fn to_chunk(
+        &self,
+        nucc_struct: PyCodecStruct,
+        _struct_info_map: &IndexMap<NuccStructInfo, u32>,
+        _struct_reference_map: &IndexMap<NuccStructReference, u32>,
+    ) -> XfbinResult<PyCodecChunk> {
+        Python::with_gil(|py| {
+            let object = nucc_struct.object.bind(py);
+
+            let data = object
+                .call_method0("to_bytes")
+                .and_then(|data| data.extract::<Vec<u8>>())
+                .map_err(|e| codec_error(py, "to_bytes", e))?;
+
+            Ok(PyCodecChunk {
+                version: nucc_struct.version,
+                data: data.into(),
+                class: object.get_type().unbind(),
+            })
+        })
+    }
+
+    fn handles(&self, nucc_struct: &PyCodecStruct) -> bool {
+        Python::with_gil(|py| {
+            nucc_struct
+                .object
+                .bind(py)
+                .is_instance(self.class.bind(py))
+                .unwrap_or_default()
+        })
+    }
+}
+
+fn codec_error(py: Python, method: &str, error: PyErr) -> XfbinError {
+    XfbinError::Codec(format!("{}: {}", method, error.value_bound(py)))
+}
+
+/// Read chunks whose type is `chunk_type` as instances of `cls` instead of `NuccUnknown`.
+/// `cls.from_bytes(data, version)` builds an instance from a chunk's data and `to_bytes()` turns it back into data.
+/// Instances get `struct_info` and `version` attributes when read, and need a `struct_info` to be written.
+#[pyfunction]
+pub fn register_codec(chunk_type: &str, cls: &Bound<'_, PyType>) -> PyResult<()> {
+    for method in ["from_bytes", "to_bytes"] {
+        if !cls.hasattr(method)? {
+            return Err(pyo3::exceptions::PyTypeError::new_err(format!(
+                "{} has no {} method",
+                cls.name()?,
+                method
+            )));
+        }
+    }
+
+    codec::register_codec(chunk_type, PyCodec { class: cls.clone().unbind() });
+    CLASSES.write().unwrap().push(cls.clone().unbind());
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyOSError};
+use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyDict, PyTuple, PyType};
+
+use crate::error::XfbinError;
+
+create_exception!(xfbin_lib, XfbinException, PyException, "Base class for all xfbin errors.");
+create_exception!(xfbin_lib, BadMagicError, XfbinException, "The file does not start with the NUCC magic.");
+create_exception!(xfbin_lib, ParseError, XfbinException, "The xfbin header or index could not be parsed.");
+create_exception!(xfbin_lib, JsonError, XfbinException, "A JSON document could not be written or read back.");
+create_exception!(xfbin_lib, IndexOutOfRangeError, XfbinException, "An index table refers to an entry that does not exist.");
+create_exception!(xfbin_lib, ChunkDecodeError, XfbinException, "A chunk could not be decoded.");
+create_exception!(xfbin_lib, ChunkEncodeError, XfbinException, "A chunk could not be encoded.");
+create_exception!(xfbin_lib, UnsupportedTypeError, XfbinException, "A chunk or struct type is not supported.");
+create_exception!(xfbin_lib, InvalidPatternError, XfbinException, "A glob or regex filter pattern could not be compiled.");
+create_exception!(xfbin_lib, EncryptedError, XfbinException, "An encrypted file could not be decoded or encoded.");
+create_exception!(xfbin_lib, CodecError, XfbinException, "A registered chunk codec failed.");
+create_exception!(xfbin_lib, MergeConflictError, XfbinException, "An overlay struct collided with a base struct under the fail policy.");
+
+static XFBIN_IO_ERROR: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+/// `XfbinIoError` derives from `OSError` as well as `XfbinException`, which `create_exception!` can't express
+fn xfbin_io_error(py: Python<'_>) -> PyResult<Bound<'_, PyType>> {
+    let error_type = XFBIN_IO_ERROR.get_or_try_init(py, || {
+        let bases = PyTuple::new_bound(py, [py.get_type_bound::<XfbinException>(), py.get_type_bound::<PyOSError>()]);
+
+        let namespace = PyDict::new_bound(py);
+        namespace.set_item("__module__", "xfbin_lib")?;
+        namespace.set_item("__doc__", "Reading or writing the underlying file failed.")?;
+
+        let error_type = py.get_type_bound::<PyType>().call1(("XfbinIoError", bases, namespace))?;
+
+        Ok::<_, PyErr>(error_type.downcast_into::<PyType>()?.unbind())
+    })?;
+
+    Ok(error_type.bind(py).clone())
+}
+
+impl From<XfbinError> for PyErr {
+    fn from(error: XfbinError) -> Self {
+        let message = error.to_string();
+
+        match error {
+            XfbinError::Io(_) => Python::with_gil(|py| match xfbin_io_error(py) {
+                Ok(error_type) => PyErr::from_type_bound(error_type, message),
+                Err(e) => e,
+            }),
+            XfbinError::BadMagic { .. } => BadMagicError::new_err(message),
+            XfbinError::Parse(_) => ParseError::new_err(message),
+            XfbinError::Json(_) => JsonError::new_err(message),
+            XfbinError::IndexOutOfRange { .. } => IndexOutOfRangeError::new_err(message),
+            XfbinError::ChunkDecode { .. } => ChunkDecodeError::new_err(message),
+            XfbinError::ChunkEncode { .. } | XfbinError::KeyFormatMismatch { .. } | XfbinError::TrackTooLarge { .. } => {
+                ChunkEncodeError::new_err(message)
+            }
+            XfbinError::UnsupportedType(_)
+            | XfbinError::TypeMismatch { .. }
+            | XfbinError::UnsupportedConversion { .. }
+            | XfbinError::UnsupportedFormatVersion { .. } => {
+                UnsupportedTypeError::new_err(message)
+            }
+            XfbinError::InvalidPattern { .. } => InvalidPatternError::new_err(message),
+            XfbinError::MissingTransform { .. } | XfbinError::EncryptedStream { .. } | XfbinError::Transform(_) => {
+                EncryptedError::new_err(message)
+            }
+            XfbinError::Codec(_) => CodecError::new_err(message),
+            XfbinError::MergeConflict { .. } => MergeConflictError::new_err(message),
+        }
+    }
+}
+
+pub fn register_exceptions(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("XfbinException", py.get_type_bound::<XfbinException>())?;
+    m.add("XfbinIoError", xfbin_io_error(py)?)?;
+    m.add("BadMagicError", py.get_type_bound::<BadMagicError>())?;
+    m.add("ParseError", py.get_type_bound::<ParseError>())?;
+    m.add("JsonError", py.get_type_bound::<JsonError>())?;
+    m.add("IndexOutOfRangeError", py.get_type_bound::<IndexOutOfRangeError>())?;
+    m.add("ChunkDecodeError", py.get_type_bound::<ChunkDecodeError>())?;
+    m.add("ChunkEncodeError", py.get_type_bound::<ChunkEncodeError>())?;
+    m.add("UnsupportedTypeError", py.get_type_bound::<UnsupportedTypeError>())?;
+    m.add("InvalidPatternError", py.get_type_bound::<InvalidPatternError>())?;
+    m.add("EncryptedError", py.get_type_bound::<EncryptedError>())?;
+    m.add("CodecError", py.get_type_bound::<CodecError>())?;
+    m.add("MergeConflictError", py.get_type_bound::<MergeConflictError>())?;
+
+    Ok(())
+}
@@ -0,0 +1,267 @@
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+
+use super::*;
+use crate::nucc_chunk::nucc_chunk_anm::{AnmEntry, AnmTrack};
+
+
+#[pyclass(name = "NuccAnm", module = "xfbin_lib")]
+#[derive(Debug, Clone)]
+pub struct PyNuccAnm {
+
+    #[pyo3(get, set)]
+    pub struct_info: NuccStructInfo,
+
+    #[pyo3(get, set)]
+    pub version: u16,
+
+    #[pyo3(get, set)]
+    pub frame_count: u32,
+
+    #[pyo3(get, set)]
+    pub frame_size: u32,
+
+    #[pyo3(get, set)]
+    pub is_looped: bool,
+
+    #[pyo3(get, set)]
+    pub looped_flag: u16,
+
+    #[pyo3(get, set)]
+    pub other_entries_indices: Py<PyList>,
+
+    #[pyo3(get, set)]
+    pub unk_entry_indices: Vec<u32>,
+
+    #[pyo3(get, set)]
+    pub clumps: Py<PyList>,
+
+    #[pyo3(get, set)]
+    pub coord_parents: Py<PyList>,
+
+    #[pyo3(get, set)]
+    pub entries: Py<PyList>,
+
+    #[pyo3(get, set)]
+    pub trailing: Vec<u8>,
+}
+
+#[pymethods]
+impl PyNuccAnm {
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (struct_info = None, version = 121, frame_count = 0, is_looped = false, other_entries_indices = None, unk_entry_indices = None, clumps = None, coord_parents = None, entries = None, frame_size = 100))]
+    pub fn __new__(
+        py: Python,
+        struct_info: Option<NuccStructInfo>,
+        version: u16,
+        frame_count: u32,
+        is_looped: bool,
+        other_entries_indices: Option<Py<PyList>>,
+        unk_entry_indices: Option<Vec<u32>>,
+        clumps: Option<Py<PyList>>,
+        coord_parents: Option<Py<PyList>>,
+        entries: Option<Py<PyList>>,
+        frame_size: u32,
+    ) -> Self {
+        Self {
+            struct_info: struct_info.unwrap_or_default(),
+            version,
+            frame_count,
+            frame_size,
+            is_looped,
+            looped_flag: is_looped as u16,
+            other_entries_indices: other_entries_indices.unwrap_or(PyList::empty_bound(py).into()),
+            unk_entry_indices: unk_entry_indices.unwrap_or_default(),
+            clumps: clumps.unwrap_or(PyList::empty_bound(py).into()),
+            coord_parents: coord_parents.unwrap_or(PyList::empty_bound(py).into()),
+            entries: entries.unwrap_or(PyList::empty_bound(py).into()),
+            trailing: Vec::new(),
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "NuccAnm(struct_info={:?}, version={}, frame_count={}, frame_size={}, is_looped={}, other_entries_indices={:?}, unk_entry_indices={:?} clumps={:?}, coord_parents={:?}, entries={:?})",
+            self.struct_info, self.version, self.frame_count, self.frame_size, self.is_looped, self.other_entries_indices, self.unk_entry_indices, self.clumps,  self.coord_parents, self.entries
+        ))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
+
+}
+
+impl PyWrapper for PyNuccAnm {
+    type Inner = NuccAnm;
+
+    fn from_inner(py: Python, anm: NuccAnm) -> PyResult<Self> {
+        Ok(Self {
+            struct_info: anm.struct_info,
+            version: anm.version,
+            frame_count: anm.frame_count,
+            frame_size: anm.frame_size,
+            is_looped: anm.is_looped,
+            looped_flag: anm.looped_flag,
+            other_entries_indices: PyList::new_bound(py, anm.other_entries_indices).into(),
+            unk_entry_indices: anm.unk_entry_indices,
+            clumps: into_py_list(py, anm.clumps)?,
+            coord_parents: into_py_list(py, anm.coord_parents)?,
+            entries: wrap_list::<PyAnmEntry>(py, anm.entries)?,
+            trailing: anm.trailing,
+        })
+    }
+
+    fn to_inner(&self, py: Python) -> PyResult<NuccAnm> {
+        Ok(NuccAnm {
+            struct_info: self.struct_info.clone(),
+            version: self.version,
+            frame_count: self.frame_count,
+            frame_size: self.frame_size,
+            is_looped: self.is_looped,
+            looped_flag: self.looped_flag,
+            other_entries_indices: self.other_entries_indices.extract(py)?,
+            unk_entry_indices: self.unk_entry_indices.clone(),
+            clumps: self.clumps.extract(py)?,
+            coord_parents: self.coord_parents.extract(py)?,
+            entries: unwrap_list::<PyAnmEntry>(py, &self.entries)?,
+            trailing: self.trailing.clone(),
+        })
+    }
+}
+
+
+#[pyclass(name = "AnmEntry", module = "xfbin_lib")]
+#[derive(Debug, Clone)]
+pub struct PyAnmEntry {
+    #[pyo3(get, set)]
+    pub coord: AnmCoord,
+
+    #[pyo3(get, set)]
+    pub entry_format: EntryFormat,
+
+    #[pyo3(get, set)]
+    pub tracks: Py<PyList>,
+}
+
+#[pymethods]
+impl PyAnmEntry {
+    #[new]
+    #[pyo3(signature = (coord = None, entry_format = None, tracks = None))]
+    pub fn __new__(
+        py: Python,
+        coord: Option<AnmCoord>,
+        entry_format: Option<EntryFormat>,
+        tracks: Option<Py<PyList>>,
+    ) -> Self {
+        Self {
+            coord: coord.unwrap_or_default(),
+            entry_format: entry_format.unwrap_or_default(),
+            tracks: tracks.unwrap_or(PyList::empty_bound(py).into()),
+        }
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        // Use original __repr__ methods
+        let tracks = self.tracks.bind(py)
+            .iter()
+            .map(|track| track.downcast::<PyAnmTrack>()?.borrow().__repr__(py))
+            .collect::<PyResult<Vec<String>>>()?;
+
+        Ok(format!(
+            "AnmEntry(coord={:?}, entry_format=EntryFormat.{:?}, tracks={})",
+            self.coord, self.entry_format, tracks.join(", ")
+        ))
+    }
+
+    fn __str__(&self, py: Python) -> PyResult<String> {
+        self.__repr__(py)
+    }
+}
+
+impl PyWrapper for PyAnmEntry {
+    type Inner = AnmEntry;
+
+    fn from_inner(py: Python, entry: AnmEntry) -> PyResult<Self> {
+        Ok(Self {
+            coord: entry.coord,
+            entry_format: entry.entry_format,
+            tracks: wrap_list::<PyAnmTrack>(py, entry.tracks)?,
+        })
+    }
+
+    fn to_inner(&self, py: Python) -> PyResult<AnmEntry> {
+        Ok(AnmEntry {
+            coord: self.coord.clone(),
+            entry_format: self.entry_format.clone(),
+            tracks: unwrap_list::<PyAnmTrack>(py, &self.tracks)?,
+        })
+    }
+}
+
+
+#[pyclass(name = "Track", module = "xfbin_lib")]
+#[derive(Debug, Clone)]
+pub struct PyAnmTrack {
+    #[pyo3(get, set)]
+    pub track_index: u16,
+
+    #[pyo3(get, set)]
+    pub key_format: NuccAnmKeyFormat,
+
+    #[pyo3(get, set)]
+    pub keys: Py<PyList>,
+}
+
+#[pymethods]
+impl PyAnmTrack {
+    #[new]
+    #[pyo3(signature = (track_index = 0, key_format = None, keys = None))]
+    pub fn __new__(
+        py: Python,
+        track_index: u16,
+        key_format: Option<NuccAnmKeyFormat>,
+        keys: Option<Py<PyList>>,
+    ) -> Self {
+        Self {
+            track_index,
+            key_format: key_format.unwrap_or_default(),
+            keys: keys.unwrap_or(PyList::empty_bound(py).into()),
+        }
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        let keys: Vec<NuccAnmKey> = self.keys.extract(py)?;
+
+        Ok(format!(
+            "Track(track_index={}, key_format={:?}, keyframes={:?})",
+            self.track_index, self.key_format, keys
+        ))
+    }
+
+    fn __str__(&self, py: Python) -> PyResult<String> {
+        self.__repr__(py)
+    }
+
+}
+
+impl PyWrapper for PyAnmTrack {
+    type Inner = AnmTrack;
+
+    fn from_inner(py: Python, track: AnmTrack) -> PyResult<Self> {
+        Ok(Self {
+            track_index: track.track_index,
+            key_format: track.key_format,
+            keys: into_py_list(py, track.keys)?,
+        })
+    }
+
+    fn to_inner(&self, py: Python) -> PyResult<AnmTrack> {
+        Ok(AnmTrack {
+            track_index: self.track_index,
+            key_format: self.key_format.clone(),
+            keys: self.keys.extract(py)?,
+        })
+    }
+}
@@ -0,0 +1,323 @@
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+
+use super::*;
+use crate::nucc_chunk::nucc_chunk_anmstrmframe::{
+    AnmEntryAmbient, AnmEntryBone, AnmEntryCamera, AnmEntryLightDirc, AnmEntryLightPoint, AnmEntryMaterial,
+    AnmEntryMorphModel, AnmStrmEntry, Entry,
+};
+
+
+#[pyclass(name = "NuccAnmStrm", module = "xfbin_lib")]
+#[derive(Debug, Clone)]
+pub struct PyNuccAnmStrm {
+    #[pyo3(get, set)]
+    pub struct_info: NuccStructInfo,
+
+    #[pyo3(get, set)]
+    pub version: u16,
+
+    #[pyo3(get, set)]
+    pub frame_count: u32,
+
+    #[pyo3(get, set)]
+    pub frame_size: u32,
+
+    #[pyo3(get, set)]
+    pub is_looped: bool,
+
+    #[pyo3(get, set)]
+    pub looped_flag: u16,
+
+    #[pyo3(get, set)]
+    pub clumps: Py<PyList>,
+
+    #[pyo3(get, set)]
+    pub other_entry_indices: Py<PyList>,
+
+    #[pyo3(get, set)]
+    pub unk_entry_count: u16,
+
+    #[pyo3(get, set)]
+    pub coord_parents: Py<PyList>,
+
+    #[pyo3(get, set)]
+    pub entries: Py<PyList>,
+
+    #[pyo3(get, set)]
+    pub trailing: Vec<u8>,
+}
+
+#[pymethods]
+impl PyNuccAnmStrm {
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (struct_info = None, version = 121, frame_count = 0, frame_size = 100, is_looped = false, clumps = None, other_entry_indices = None, unk_entry_count = 0, coord_parents = None, entries = None))]
+    pub fn __new__(
+        py: Python,
+        struct_info: Option<NuccStructInfo>,
+        version: u16,
+        frame_count: u32,
+        frame_size: u32,
+        is_looped: bool,
+        clumps: Option<Py<PyList>>,
+        other_entry_indices: Option<Py<PyList>>,
+        unk_entry_count: u16,
+        coord_parents: Option<Py<PyList>>,
+        entries: Option<Py<PyList>>,
+    ) -> Self {
+        Self {
+            struct_info: struct_info.unwrap_or_default(),
+            version,
+            frame_count,
+            frame_size,
+            is_looped,
+            looped_flag: is_looped as u16,
+            clumps: clumps.unwrap_or(PyList::empty_bound(py).into()),
+            other_entry_indices: other_entry_indices.unwrap_or(PyList::empty_bound(py).into()),
+            unk_entry_count,
+            coord_parents: coord_parents.unwrap_or(PyList::empty_bound(py).into()),
+            entries: entries.unwrap_or(PyList::empty_bound(py).into()),
+            trailing: Vec::new(),
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "NuccAnmStrm(struct_info={:?}, version={}, frame_count={}, frame_size={}, is_looped={}, clumps={:?}, other_entry_indices={:?}, unk_entry_count={}, coord_parents={:?}, entries={:?})",
+            self.struct_info, self.version, self.frame_count, self.frame_size, self.is_looped, self.clumps, self.other_entry_indices, self.unk_entry_count, self.coord_parents, self.entries
+        ))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
+}
+
+impl PyWrapper for PyNuccAnmStrm {
+    type Inner = NuccAnmStrm;
+
+    fn from_inner(py: Python, anmstrm: NuccAnmStrm) -> PyResult<Self> {
+        Ok(Self {
+            struct_info: anmstrm.struct_info,
+            version: anmstrm.version,
+            frame_count: anmstrm.frame_count,
+            frame_size: anmstrm.frame_size,
+            is_looped: anmstrm.is_looped,
+            looped_flag: anmstrm.looped_flag,
+            clumps: into_py_list(py, anmstrm.clumps)?,
+            other_entry_indices: PyList::new_bound(py, anmstrm.other_entry_indices).into(),
+            unk_entry_count: anmstrm.unk_entry_count,
+            coord_parents: into_py_list(py, anmstrm.coord_parents)?,
+            entries: into_py_list(py, anmstrm.entries)?,
+            trailing: anmstrm.trailing,
+        })
+    }
+
+    fn to_inner(&self, py: Python) -> PyResult<NuccAnmStrm> {
+        Ok(NuccAnmStrm {
+            struct_info: self.struct_info.clone(),
+            version: self.version,
+            frame_count: self.frame_count,
+            frame_size: self.frame_size,
+            is_looped: self.is_looped,
+            looped_flag: self.looped_flag,
+            clumps: self.clumps.extract(py)?,
+            other_entry_indices: self.other_entry_indices.extract(py)?,
+            unk_entry_count: self.unk_entry_count,
+            coord_parents: self.coord_parents.extract(py)?,
+            entries: self.entries.extract(py)?,
+            trailing: self.trailing.clone(),
+        })
+    }
+}
+
+
+#[pyclass(name = "NuccAnmStrmFrame", module = "xfbin_lib")]
+#[derive(Debug, Clone)]
+pub struct PyNuccAnmStrmFrame {
+    #[pyo3(get, set)]
+    pub struct_info: NuccStructInfo,
+
+    #[pyo3(get, set)]
+    pub version: u16,
+
+    #[pyo3(get, set)]
+    pub frame_number: u32,
+
+    #[pyo3(get, set)]
+    pub unknown: u16,
+
+    #[pyo3(get, set)]
+    pub entries: Py<PyList>,
+
+    #[pyo3(get, set)]
+    pub trailing: Vec<u8>,
+}
+
+#[pymethods]
+impl PyNuccAnmStrmFrame {
+    #[new]
+    #[pyo3(signature = (struct_info = None, version = 121, frame_number = 0, unknown = 0, entries = None))]
+    pub fn __new__(
+        py: Python,
+        struct_info: Option<NuccStructInfo>,
+        version: u16,
+        frame_number: u32,
+        unknown: u16,
+        entries: Option<Py<PyList>>,
+    ) -> Self {
+        Self {
+            struct_info: struct_info.unwrap_or_default(),
+            version,
+            frame_number,
+            unknown,
+            entries: entries.unwrap_or(PyList::empty_bound(py).into()),
+            trailing: Vec::new(),
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "NuccAnmStrmFrame(struct_info={:?}, version={}, frame_number={}, unknown={}, entries={:?})",
+            self.struct_info, self.version, self.frame_number, self.unknown, self.entries
+        ))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
+}
+
+impl PyWrapper for PyNuccAnmStrmFrame {
+    type Inner = NuccAnmStrmFrame;
+
+    fn from_inner(py: Python, frame: NuccAnmStrmFrame) -> PyResult<Self> {
+        Ok(Self {
+            struct_info: frame.struct_info,
+            version: frame.version,
+            frame_number: frame.frame_number,
+            unknown: frame.unknown,
+            entries: wrap_list::<PyAnmStrmEntry>(py, frame.entries)?,
+            trailing: frame.trailing,
+        })
+    }
+
+    fn to_inner(&self, py: Python) -> PyResult<NuccAnmStrmFrame> {
+        Ok(NuccAnmStrmFrame {
+            struct_info: self.struct_info.clone(),
+            version: self.version,
+            frame_number: self.frame_number,
+            unknown: self.unknown,
+            entries: unwrap_list::<PyAnmStrmEntry>(py, &self.entries)?,
+            trailing: self.trailing.clone(),
+        })
+    }
+}
+
+
+/// A frame entry. `entry_data` is one of the `AnmEntry*` classes, picked by `entry_format`, or `None`
+#[pyclass(name = "AnmStrmEntry", module = "xfbin_lib")]
+#[derive(Debug, Clone)]
+pub struct PyAnmStrmEntry {
+    #[pyo3(get, set)]
+    pub coord: AnmCoord,
+
+    #[pyo3(get, set)]
+    pub entry_format: u16,
+
+    #[pyo3(get, set)]
+    pub entry_size: u16,
+
+    #[pyo3(get, set)]
+    pub entry_data: PyObject,
+}
+
+#[pymethods]
+impl PyAnmStrmEntry {
+    #[new]
+    #[pyo3(signature = (coord = None, entry_format = 0, entry_size = 0, entry_data = None))]
+    pub fn __new__(
+        py: Python,
+        coord: Option<AnmCoord>,
+        entry_format: u16,
+        entry_size: u16,
+        entry_data: Option<PyObject>,
+    ) -> Self {
+        Self {
+            coord: coord.unwrap_or_default(),
+            entry_format,
+            entry_size,
+            entry_data: entry_data.unwrap_or(py.None()),
+        }
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        Ok(format!(
+            "AnmStrmEntry(coord={:?}, entry_format={}, entry_size={}, entry_data={})",
+            self.coord, self.entry_format, self.entry_size, self.entry_data.bind(py).repr()?
+        ))
+    }
+
+    fn __str__(&self, py: Python) -> PyResult<String> {
+        self.__repr__(py)
+    }
+}
+
+impl PyWrapper for PyAnmStrmEntry {
+    type Inner = AnmStrmEntry;
+
+    fn from_inner(py: Python, entry: AnmStrmEntry) -> PyResult<Self> {
+        let entry_data = match entry.entry_data {
+            Entry::Bone(data) => Py::new(py, data)?.into_py(py),
+            Entry::Camera(data) => Py::new(py, data)?.into_py(py),
+            Entry::Material(data) => Py::new(py, data)?.into_py(py),
+            Entry::LightDirc(data) => Py::new(py, data)?.into_py(py),
+            Entry::LightPoint(data) => Py::new(py, data)?.into_py(py),
+            Entry::Ambient(data) => Py::new(py, data)?.into_py(py),
+            Entry::MorphModel(data) => Py::new(py, data)?.into_py(py),
+            Entry::Unknown => py.None(),
+        };
+
+        Ok(Self {
+            coord: entry.coord,
+            entry_format: entry.entry_format,
+            entry_size: entry.entry_size,
+            entry_data,
+        })
+    }
+
+    fn to_inner(&self, py: Python) -> PyResult<AnmStrmEntry> {
+        let data = self.entry_data.bind(py);
+
+        let entry_data = if data.is_none() {
+            Entry::Unknown
+        } else if let Ok(data) = data.extract::<AnmEntryBone>() {
+            Entry::Bone(data)
+        } else if let Ok(data) = data.extract::<AnmEntryCamera>() {
+            Entry::Camera(data)
+        } else if let Ok(data) = data.extract::<AnmEntryMaterial>() {
+            Entry::Material(data)
+        } else if let Ok(data) = data.extract::<AnmEntryLightDirc>() {
+            Entry::LightDirc(data)
+        } else if let Ok(data) = data.extract::<AnmEntryLightPoint>() {
+            Entry::LightPoint(data)
+        } else if let Ok(data) = data.extract::<AnmEntryAmbient>() {
+            Entry::Ambient(data)
+        } else if let Ok(data) = data.extract::<AnmEntryMorphModel>() {
+            Entry::MorphModel(data)
+        } else {
+            return Err(pyo3::exceptions::PyTypeError::new_err(format!(
+                "Unsupported entry_data type: {}",
+                data.get_type().name()?
+            )));
+        };
+
+        Ok(AnmStrmEntry {
+            coord: self.coord.clone(),
+            entry_format: self.entry_format,
+            entry_size: self.entry_size,
+            entry_data,
+        })
+    }
+}
@@ -0,0 +1,433 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyList, PyType};
+
+use super::*;
+use crate::diff::XfbinDiff;
+use crate::merge::{MergePolicy, MergeReport};
+use crate::query::{Pattern, StructFilter};
+use crate::reference::{ReferenceResolver, ReferenceTarget, ResolvedReference};
+use crate::validate::Finding;
+use crate::xfbin::{Xfbin, XfbinLayout, XfbinPage};
+use crate::XfbinResult;
+
+
+#[pyclass(name = "Xfbin", module = "xfbin_lib")]
+#[derive(Debug, Clone)]
+pub struct PyXfbin {
+    #[pyo3(get, set)]
+    pub version: u16,
+
+    #[pyo3(get, set)]
+    pub pages: Py<PyList>,
+
+    #[pyo3(get, set)]
+    pub layout: Option<XfbinLayout>,
+}
+
+#[pymethods]
+impl PyXfbin {
+    #[new]
+    #[pyo3(signature = (version = None, pages = None, layout = None))]
+    fn __new__(py: Python, version: Option<u16>, pages: Option<Py<PyList>>, layout: Option<XfbinLayout>) -> Self {
+        Self {
+            version: version.unwrap_or(121),
+            pages: pages.unwrap_or(PyList::empty_bound(py).into()),
+            layout,
+        }
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        let pages = self.pages.bind(py)
+            .iter()
+            .map(|page| page.downcast::<PyXfbinPage>()?.borrow().__repr__(py))
+            .collect::<PyResult<Vec<String>>>()?;
+
+        Ok(format!(
+            "Xfbin(version={}, pages={})",
+            self.version, pages.join(", ")
+        ))
+    }
+
+    fn __str__(&self, py: Python) -> PyResult<String> {
+        self.__repr__(py)
+    }
+
+    /// Every struct in every page, in file order
+    pub fn structs<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyAny>>> {
+        let mut structs = Vec::new();
+
+        for page in self.pages.bind(py).iter() {
+            let page = page.downcast::<PyXfbinPage>()?.borrow();
+            structs.extend(page.structs.bind(py).iter());
+        }
+
+        Ok(structs)
+    }
+
+    #[pyo3(signature = (chunk_name = None, chunk_type = None, filepath = None, mode = "exact"))]
+    pub fn find<'py>(
+        &self,
+        py: Python<'py>,
+        chunk_name: Option<&str>,
+        chunk_type: Option<&str>,
+        filepath: Option<&str>,
+        mode: &str,
+    ) -> PyResult<Option<Bound<'py, PyAny>>> {
+        Ok(self.find_all(py, chunk_name, chunk_type, filepath, mode)?.into_iter().next())
+    }
+
+    #[pyo3(signature = (chunk_name = None, chunk_type = None, filepath = None, mode = "exact"))]
+    pub fn find_all<'py>(
+        &self,
+        py: Python<'py>,
+        chunk_name: Option<&str>,
+        chunk_type: Option<&str>,
+        filepath: Option<&str>,
+        mode: &str,
+    ) -> PyResult<Vec<Bound<'py, PyAny>>> {
+        let filter = struct_filter(chunk_name, chunk_type, filepath, mode)?;
+
+        let mut found = Vec::new();
+        for nucc_struct in self.structs(py)? {
+            if filter.matches(&struct_info_of(&nucc_struct)?) {
+                found.push(nucc_struct);
+            }
+        }
+
+        Ok(found)
+    }
+
+    pub fn get_by_name<'py>(&self, py: Python<'py>, chunk_name: &str) -> PyResult<Option<Bound<'py, PyAny>>> {
+        self.find(py, Some(chunk_name), None, None, "exact")
+    }
+
+    /// Every struct that is an instance of `struct_type`, e.g. `xfbin.structs_of(NuccAnm)`
+    pub fn structs_of<'py>(&self, py: Python<'py>, struct_type: &Bound<'py, PyType>) -> PyResult<Vec<Bound<'py, PyAny>>> {
+        let mut found = Vec::new();
+        for nucc_struct in self.structs(py)? {
+            if nucc_struct.is_instance(struct_type)? {
+                found.push(nucc_struct);
+            }
+        }
+
+        Ok(found)
+    }
+
+    pub fn resolve_reference(&self, py: Python, page_index: usize, reference: NuccStructReference) -> PyResult<ReferenceTarget> {
+        let struct_infos = self.page_struct_infos(py)?;
+
+        Ok(resolver(&struct_infos).resolve(page_index, &reference))
+    }
+
+    /// The struct object `reference` points at, or None if it isn't in this file
+    pub fn follow_reference<'py>(&self, py: Python<'py>, page_index: usize, reference: NuccStructReference) -> PyResult<Option<Bound<'py, PyAny>>> {
+        let (page_index, struct_index) = match self.resolve_reference(py, page_index, reference)? {
+            ReferenceTarget::InPage { struct_index } => (page_index, struct_index),
+            ReferenceTarget::OtherPage { page_index, struct_index } => (page_index, struct_index),
+            ReferenceTarget::External { .. } | ReferenceTarget::Dangling {} => return Ok(None),
+        };
+
+        let page = self.pages.bind(py).get_item(page_index)?;
+        let page = page.downcast::<PyXfbinPage>()?.borrow();
+
+        Ok(Some(page.structs.bind(py).get_item(struct_index)?))
+    }
+
+    pub fn references(&self, py: Python) -> PyResult<Vec<ResolvedReference>> {
+        let struct_infos = self.page_struct_infos(py)?;
+
+        let mut struct_references = Vec::new();
+        for page in self.pages.bind(py).iter() {
+            let page = page.downcast::<PyXfbinPage>()?.borrow();
+            struct_references.push(page.struct_references.extract::<Vec<NuccStructReference>>(py)?);
+        }
+
+        Ok(resolver(&struct_infos).resolve_all(
+            struct_references
+                .iter()
+                .enumerate()
+                .map(|(page_index, references)| (page_index, references.as_slice())),
+        ))
+    }
+
+    pub fn dangling_references(&self, py: Python) -> PyResult<Vec<ResolvedReference>> {
+        Ok(self.references(py)?
+            .into_iter()
+            .filter(|resolved| resolved.target.is_dangling())
+            .collect())
+    }
+
+    pub fn validate(&self, py: Python) -> PyResult<Vec<Finding>> {
+        let xfbin = self.to_inner(py)?;
+
+        Ok(py.allow_threads(move || xfbin.validate()))
+    }
+
+    /// Compare this file, as the old side, against `other`
+    pub fn diff(&self, py: Python, other: PyRef<PyXfbin>) -> PyResult<XfbinDiff> {
+        let old = self.to_inner(py)?;
+        let new = other.to_inner(py)?;
+
+        Ok(py.allow_threads(move || old.diff(&new))?)
+    }
+
+    /// Apply `overlays` onto this file in place; see `MergePolicy` for how collisions are handled
+    #[pyo3(signature = (overlays, policy = MergePolicy::PreferOverlay))]
+    pub fn merge(&mut self, py: Python, overlays: Vec<PyRef<PyXfbin>>, policy: MergePolicy) -> PyResult<MergeReport> {
+        let mut xfbin = self.to_inner(py)?;
+        let overlays = overlays
+            .iter()
+            .map(|overlay| overlay.to_inner(py))
+            .collect::<PyResult<Vec<Xfbin>>>()?;
+
+        let (xfbin, report) = py.allow_threads(move || xfbin.merge(overlays, policy).map(|report| (xfbin, report)))?;
+        *self = PyXfbin::from_inner(py, xfbin)?;
+
+        Ok(report)
+    }
+}
+
+impl PyXfbin {
+    /// The struct infos of each page's structs
+    fn page_struct_infos(&self, py: Python) -> PyResult<Vec<Vec<NuccStructInfo>>> {
+        self.pages.bind(py)
+            .iter()
+            .map(|page| {
+                let page = page.downcast::<PyXfbinPage>()?.borrow();
+
+                page.structs.bind(py)
+                    .iter()
+                    .map(|nucc_struct| struct_info_of(&nucc_struct))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn resolver(struct_infos: &[Vec<NuccStructInfo>]) -> ReferenceResolver<'_> {
+    ReferenceResolver::new(struct_infos.iter().map(|page| page.iter().collect()).collect())
+}
+
+/// Build a filter from the keyword arguments of the query methods; `mode` is "exact", "glob" or "regex".
+fn struct_filter(chunk_name: Option<&str>, chunk_type: Option<&str>, filepath: Option<&str>, mode: &str) -> PyResult<StructFilter> {
+    let new_pattern: fn(&str) -> XfbinResult<Pattern> = match mode {
+        "exact" => |pattern| Ok(Pattern::exact(pattern)),
+        "glob" => Pattern::glob,
+        "regex" => Pattern::regex,
+        _ => return Err(PyValueError::new_err(format!("unknown match mode \"{}\", expected \"exact\", \"glob\" or \"regex\"", mode))),
+    };
+
+    let pattern = |pattern: Option<&str>| pattern.map(new_pattern).transpose();
+
+    Ok(StructFilter {
+        chunk_name: pattern(chunk_name)?,
+        chunk_type: pattern(chunk_type)?,
+        filepath: pattern(filepath)?,
+    })
+}
+
+fn struct_info_of(nucc_struct: &Bound<'_, PyAny>) -> PyResult<NuccStructInfo> {
+    nucc_struct.getattr("struct_info")?.extract()
+}
+
+impl PyWrapper for PyXfbin {
+    type Inner = Xfbin;
+
+    fn from_inner(py: Python, xfbin: Xfbin) -> PyResult<Self> {
+        Ok(Self {
+            version: xfbin.version,
+            pages: wrap_list::<PyXfbinPage>(py, xfbin.pages)?,
+            layout: xfbin.layout,
+        })
+    }
+
+    fn to_inner(&self, py: Python) -> PyResult<Xfbin> {
+        Ok(Xfbin {
+            version: self.version,
+            pages: unwrap_list::<PyXfbinPage>(py, &self.pages)?,
+            layout: self.layout.clone(),
+        })
+    }
+}
+
+
+#[pyclass(name = "XfbinPage", module = "xfbin_lib")]
+#[derive(Debug, Clone)]
+pub struct PyXfbinPage {
+    #[pyo3(get, set)]
+    pub structs: Py<PyList>,
+
+    #[pyo3(get, set)]
+    pub struct_infos: Py<PyList>,
+
+    #[pyo3(get, set)]
+    pub struct_references: Py<PyList>
+}
+
+#[pymethods]
+impl PyXfbinPage {
+    #[new]
+    #[pyo3(signature = (structs = None, struct_infos = None, struct_references = None))]
+    fn __new__(py: Python, structs: Option<Py<PyList>>, struct_infos: Option<Py<PyList>>, struct_references: Option<Py<PyList>>) -> Self {
+        Self {
+            structs: structs.unwrap_or(PyList::empty_bound(py).into()),
+            struct_infos: struct_infos.unwrap_or(PyList::empty_bound(py).into()),
+            struct_references: struct_references.unwrap_or(PyList::empty_bound(py).into())
+        }
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        let XfbinPage { structs, struct_infos, struct_references } = self.to_inner(py)?;
+
+        Ok(format!(
+            "XfbinPage(structs={:?}, struct_infos={:?}, struct_references={:?})",
+            structs, struct_infos, struct_references
+        ))
+    }
+
+    fn __str__(&self, py: Python) -> PyResult<String> {
+        self.__repr__(py)
+    }
+
+    pub fn has_unknown_chunk(&self, py: Python) -> PyResult<bool> {
+        Ok(self.to_inner(py)?.has_unknown_chunk())
+    }
+
+    pub fn has_anm_chunk(&self, py: Python) -> PyResult<bool> {
+        Ok(self.to_inner(py)?.has_anm_chunk())
+    }
+
+    pub fn add_struct(&mut self, py: Python, nucc_struct: Bound<'_, PyAny>) -> PyResult<()> {
+        let mut edit = PageEdit::new(py, self)?;
+        let nucc_struct = edit.track(nucc_struct)?;
+
+        edit.page.add_struct(nucc_struct);
+        edit.finish(self)
+    }
+
+    pub fn remove_struct(&mut self, py: Python, index: usize) -> PyResult<PyObject> {
+        let mut edit = PageEdit::new(py, self)?;
+
+        let old_struct = edit.page.remove_struct(index)?;
+        let old_struct = edit.object(old_struct)?;
+
+        edit.finish(self)?;
+        Ok(old_struct)
+    }
+
+    pub fn replace_struct(&mut self, py: Python, index: usize, nucc_struct: Bound<'_, PyAny>) -> PyResult<PyObject> {
+        let mut edit = PageEdit::new(py, self)?;
+        let nucc_struct = edit.track(nucc_struct)?;
+
+        let old_struct = edit.page.replace_struct(index, nucc_struct)?;
+        let old_struct = edit.object(old_struct)?;
+
+        edit.finish(self)?;
+        Ok(old_struct)
+    }
+
+    pub fn move_struct(&mut self, py: Python, from: usize, to: usize) -> PyResult<()> {
+        let mut edit = PageEdit::new(py, self)?;
+
+        edit.page.move_struct(from, to)?;
+        edit.finish(self)
+    }
+}
+
+/// A page converted to Rust for one of `XfbinPage`'s edits.
+/// Each struct remembers the Python object it came from, so the objects in the page keep their identity.
+struct PageEdit<'py> {
+    py: Python<'py>,
+    page: XfbinPage,
+    objects: HashMap<*const (), Bound<'py, PyAny>>,
+}
+
+impl<'py> PageEdit<'py> {
+    fn new(py: Python<'py>, page: &PyXfbinPage) -> PyResult<Self> {
+        let mut edit = Self {
+            py,
+            page: XfbinPage {
+                structs: Vec::new(),
+                struct_infos: page.struct_infos.extract(py)?,
+                struct_references: page.struct_references.extract(py)?,
+            },
+            objects: HashMap::new(),
+        };
+
+        for obj in page.structs.bind(py).iter() {
+            let nucc_struct = edit.track(obj)?;
+            edit.page.structs.push(nucc_struct);
+        }
+
+        Ok(edit)
+    }
+
+    fn key(nucc_struct: &dyn NuccStruct) -> *const () {
+        nucc_struct as *const dyn NuccStruct as *const ()
+    }
+
+    /// Convert `obj`, remembering it as the object for the struct
+    fn track(&mut self, obj: Bound<'py, PyAny>) -> PyResult<Box<dyn NuccStruct>> {
+        let nucc_struct = nucc_struct_from_py(&obj)?;
+        self.objects.insert(Self::key(nucc_struct.as_ref()), obj);
+
+        Ok(nucc_struct)
+    }
+
+    /// The Python object `nucc_struct` came from
+    fn object(&mut self, nucc_struct: Box<dyn NuccStruct>) -> PyResult<PyObject> {
+        match self.objects.remove(&Self::key(nucc_struct.as_ref())) {
+            Some(obj) => Ok(obj.unbind()),
+            None => nucc_struct_into_py(self.py, nucc_struct),
+        }
+    }
+
+    /// Write the edited page back, updating the struct list in place
+    fn finish(mut self, page: &mut PyXfbinPage) -> PyResult<()> {
+        let XfbinPage { structs, struct_infos, struct_references } = std::mem::take(&mut self.page);
+
+        let structs = structs
+            .into_iter()
+            .map(|nucc_struct| self.object(nucc_struct))
+            .collect::<PyResult<Vec<_>>>()?;
+
+        let list = page.structs.bind(self.py);
+        list.as_sequence().set_slice(0, list.len(), &PyList::new_bound(self.py, structs))?;
+
+        page.struct_infos = into_py_list(self.py, struct_infos)?;
+        page.struct_references = into_py_list(self.py, struct_references)?;
+
+        Ok(())
+    }
+}
+
+impl PyWrapper for PyXfbinPage {
+    type Inner = XfbinPage;
+
+    fn from_inner(py: Python, page: XfbinPage) -> PyResult<Self> {
+        let structs = page.structs
+            .into_iter()
+            .map(|nucc_struct| nucc_struct_into_py(py, nucc_struct))
+            .collect::<PyResult<Vec<_>>>()?;
+
+        Ok(Self {
+            structs: PyList::new_bound(py, structs).into(),
+            struct_infos: into_py_list(py, page.struct_infos)?,
+            struct_references: into_py_list(py, page.struct_references)?,
+        })
+    }
+
+    fn to_inner(&self, py: Python) -> PyResult<XfbinPage> {
+        let structs = self.structs.bind(py)
+            .iter()
+            .map(|nucc_struct| nucc_struct_from_py(&nucc_struct))
+            .collect::<PyResult<Vec<_>>>()?;
+
+        Ok(XfbinPage {
+            structs,
+            struct_infos: self.struct_infos.extract(py)?,
+            struct_references: self.struct_references.extract(py)?,
+        })
+    }
+}
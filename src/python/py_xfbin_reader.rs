@@ -0,0 +1,69 @@
+use bytes::Bytes;
+use pyo3::prelude::*;
+use std::io::Cursor;
+use std::str::FromStr;
+
+use super::*;
+use crate::xfbin_reader::{XfbinChunkEntry, XfbinReader};
+
+
+/// A lazy reader over a memory-mapped xfbin.
+#[pyclass(name = "XfbinReader", module = "xfbin_lib")]
+pub struct PyXfbinReader {
+    pub inner: XfbinReader<Cursor<Bytes>>,
+}
+
+#[pymethods]
+impl PyXfbinReader {
+    #[getter]
+    fn version(&self) -> u16 {
+        self.inner.version()
+    }
+
+    #[getter]
+    fn page_count(&self) -> usize {
+        self.inner.page_count()
+    }
+
+    fn entries(&self) -> Vec<XfbinChunkEntry> {
+        self.inner.entries().to_vec()
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.entries().len()
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "XfbinReader(version={}, page_count={}, entries={})",
+            self.inner.version(), self.inner.page_count(), self.inner.entries().len()
+        ))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        self.__repr__()
+    }
+
+    pub fn get(&mut self, py: Python, name: &str) -> PyResult<Option<PyObject>> {
+        py.allow_threads(|| self.inner.read_by_name(name))?
+            .map(|nucc_struct| nucc_struct_into_py(py, nucc_struct))
+            .transpose()
+    }
+
+    pub fn get_by_type(&mut self, py: Python, chunk_type: &str) -> PyResult<Vec<PyObject>> {
+        let chunk_type = NuccChunkType::from_str(chunk_type)
+            .map_err(|_| XfbinError::UnsupportedType(chunk_type.to_string()))?;
+
+        py.allow_threads(|| self.inner.read_by_type(chunk_type))?
+            .into_iter()
+            .map(|nucc_struct| nucc_struct_into_py(py, nucc_struct))
+            .collect()
+    }
+
+    pub fn get_by_filepath(&mut self, py: Python, filepath: &str) -> PyResult<Vec<PyObject>> {
+        py.allow_threads(|| self.inner.read_by_filepath(filepath))?
+            .into_iter()
+            .map(|nucc_struct| nucc_struct_into_py(py, nucc_struct))
+            .collect()
+    }
+}
@@ -0,0 +1,81 @@
+//! # Queries
+//! Filters for finding structs by the fields of their `NuccStructInfo`.
+//! Each field can be matched exactly, with a glob pattern, or with a regular expression.
+//!
+use globset::{Glob, GlobMatcher};
+use regex::Regex;
+
+use super::error::{XfbinError, XfbinResult};
+use super::nucc::NuccStructInfo;
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Exact(String),
+    Glob(GlobMatcher),
+    Regex(Regex),
+}
+
+impl Pattern {
+    pub fn exact(pattern: &str) -> Self {
+        Self::Exact(pattern.to_string())
+    }
+
+    pub fn glob(pattern: &str) -> XfbinResult<Self> {
+        Glob::new(pattern)
+            .map(|glob| Self::Glob(glob.compile_matcher()))
+            .map_err(|e| XfbinError::invalid_pattern(pattern, e))
+    }
+
+    pub fn regex(pattern: &str) -> XfbinResult<Self> {
+        Regex::new(pattern)
+            .map(Self::Regex)
+            .map_err(|e| XfbinError::invalid_pattern(pattern, e))
+    }
+
+    pub fn is_match(&self, value: &str) -> bool {
+        match self {
+            Self::Exact(pattern) => pattern == value,
+            Self::Glob(matcher) => matcher.is_match(value),
+            Self::Regex(regex) => regex.is_match(value),
+        }
+    }
+}
+
+/// Matches struct infos field by field; fields without a pattern match anything.
+#[derive(Debug, Clone, Default)]
+pub struct StructFilter {
+    pub chunk_name: Option<Pattern>,
+    pub chunk_type: Option<Pattern>,
+    pub filepath: Option<Pattern>,
+}
+
+impl StructFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn chunk_name(mut self, pattern: Pattern) -> Self {
+        self.chunk_name = Some(pattern);
+        self
+    }
+
+    pub fn chunk_type(mut self, pattern: Pattern) -> Self {
+        self.chunk_type = Some(pattern);
+        self
+    }
+
+    pub fn filepath(mut self, pattern: Pattern) -> Self {
+        self.filepath = Some(pattern);
+        self
+    }
+
+    pub fn matches(&self, struct_info: &NuccStructInfo) -> bool {
+        let field_matches = |pattern: &Option<Pattern>, value: &str| {
+            pattern.as_ref().is_none_or(|pattern| pattern.is_match(value))
+        };
+
+        field_matches(&self.chunk_name, &struct_info.chunk_name)
+            && field_matches(&self.chunk_type, &struct_info.chunk_type)
+            && field_matches(&self.filepath, &struct_info.filepath)
+    }
+}
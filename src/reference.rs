@@ -0,0 +1,129 @@
+//! # References
+//! Resolution of the `NuccStructReference`s in a page's reference table to the structs they point at.
+//! A reference whose struct isn't in the file counts as external when no struct in the file shares its filepath,
+//! as it then lives in another xfbin; otherwise it's dangling, usually because its struct was removed or renamed.
+//!
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+use super::nucc::{NuccStructInfo, NuccStructReference};
+
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReferenceTarget {
+    /// A struct in the same page as the reference
+    InPage { struct_index: usize },
+    OtherPage { page_index: usize, struct_index: usize },
+    External { filepath: String },
+    // Braced so the enum stays a valid complex pyclass
+    Dangling {},
+}
+
+impl ReferenceTarget {
+    pub fn is_dangling(&self) -> bool {
+        matches!(self, Self::Dangling {})
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ReferenceTarget {
+    #[pyo3(name = "is_dangling")]
+    fn py_is_dangling(&self) -> bool {
+        self.is_dangling()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ReferenceTarget.{:?}", self)
+    }
+}
+
+/// An entry of a page's reference table with what it resolved to.
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone)]
+pub struct ResolvedReference {
+    pub page_index: usize,
+
+    /// Position of the reference in the page's `struct_references`
+    pub reference_index: usize,
+    pub reference: NuccStructReference,
+    pub target: ReferenceTarget,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ResolvedReference {
+    fn __repr__(&self) -> String {
+        format!(
+            "ResolvedReference(page_index={}, reference_index={}, reference={}, target=ReferenceTarget.{:?})",
+            self.page_index, self.reference_index, self.reference, self.target
+        )
+    }
+}
+
+/// The struct infos of every page's structs, in page order.
+pub(crate) struct ReferenceResolver<'a> {
+    pages: Vec<Vec<&'a NuccStructInfo>>,
+}
+
+impl<'a> ReferenceResolver<'a> {
+    pub(crate) fn new(pages: Vec<Vec<&'a NuccStructInfo>>) -> Self {
+        Self { pages }
+    }
+
+    /// Resolve a reference from the page at `page_index`, preferring a struct in that page
+    pub(crate) fn resolve(&self, page_index: usize, reference: &NuccStructReference) -> ReferenceTarget {
+        let position = |struct_infos: &Vec<&NuccStructInfo>| {
+            struct_infos
+                .iter()
+                .position(|struct_info| **struct_info == reference.struct_info)
+        };
+
+        if let Some(struct_index) = self.pages.get(page_index).and_then(position) {
+            return ReferenceTarget::InPage { struct_index };
+        }
+
+        for (other_index, struct_infos) in self.pages.iter().enumerate() {
+            if let Some(struct_index) = position(struct_infos) {
+                return ReferenceTarget::OtherPage {
+                    page_index: other_index,
+                    struct_index,
+                };
+            }
+        }
+
+        let filepath = &reference.struct_info.filepath;
+        let in_file = self
+            .pages
+            .iter()
+            .flatten()
+            .any(|struct_info| &struct_info.filepath == filepath);
+
+        if in_file {
+            ReferenceTarget::Dangling {}
+        } else {
+            ReferenceTarget::External { filepath: filepath.clone() }
+        }
+    }
+
+    /// Resolve every reference table, given as `(page_index, struct_references)`
+    pub(crate) fn resolve_all<'r, I>(&self, tables: I) -> Vec<ResolvedReference>
+    where
+        I: IntoIterator<Item = (usize, &'r [NuccStructReference])>,
+    {
+        tables
+            .into_iter()
+            .flat_map(|(page_index, struct_references)| {
+                struct_references
+                    .iter()
+                    .enumerate()
+                    .map(move |(reference_index, reference)| ResolvedReference {
+                        page_index,
+                        reference_index,
+                        reference: reference.clone(),
+                        target: self.resolve(page_index, reference),
+                    })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,290 @@
+//! # Sampling
+//! Evaluation of anm tracks at arbitrary, fractional frames, the way the game plays them back.
+//! Fixed tracks hold one value, table tracks have one key per frame and keyed tracks place their keys
+//! on explicit frames counted in ticks, `frame_size` of which make up a frame. Quaternions are interpolated
+//! with slerp, and values stored as fixed point are scaled back to floats.
+//!
+use indexmap::IndexMap;
+
+use super::nucc::NuccAnm;
+use super::nucc_chunk::nucc_chunk_anm::{AnmEntry, AnmTrack, NuccAnmKey, NuccAnmKeyFormat};
+
+/// Fixed point scales of the short formats
+pub(crate) const QUATERNION_SHORT_SCALE: f32 = 0x4000 as f32;
+pub(crate) const OPACITY_SHORT_SCALE: f32 = 0x8000 as f32;
+pub(crate) const SCALE_SHORT_SCALE: f32 = 0x1000 as f32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrackValue {
+    Float(f32),
+    Vec2([f32; 2]),
+
+    /// Locations, euler rotations and scales, and colors normalized to 0..1
+    Vec3([f32; 3]),
+    Quaternion([f32; 4]),
+}
+
+impl TrackValue {
+    pub(crate) fn components(&self) -> &[f32] {
+        match self {
+            Self::Float(value) => std::slice::from_ref(value),
+            Self::Vec2(values) => values,
+            Self::Vec3(values) => values,
+            Self::Quaternion(values) => values,
+        }
+    }
+
+    /// A value of the same variant with every component mapped by `f`
+    fn map(&self, mut f: impl FnMut(usize, f32) -> f32) -> Self {
+        match *self {
+            Self::Float(value) => Self::Float(f(0, value)),
+            Self::Vec2(values) => Self::Vec2(std::array::from_fn(|i| f(i, values[i]))),
+            Self::Vec3(values) => Self::Vec3(std::array::from_fn(|i| f(i, values[i]))),
+            Self::Quaternion(values) => Self::Quaternion(std::array::from_fn(|i| f(i, values[i]))),
+        }
+    }
+
+    /// The value `t` of the way from `self` to `other`, which has to be the same variant
+    pub fn interpolate(&self, other: &Self, t: f32) -> Self {
+        let other = other.components();
+
+        match self {
+            Self::Quaternion(values) => Self::Quaternion(slerp(values, other, t)),
+            _ => self.map(|i, value| value + (other[i] - value) * t),
+        }
+    }
+}
+
+/// The value `key` stores, or `None` if it's `Unknown`
+fn key_value(key: &NuccAnmKey) -> Option<TrackValue> {
+    let value = match *key {
+        NuccAnmKey::Vec2 { values: (x, y) } | NuccAnmKey::Vec2Linear { values: (x, y), .. } => TrackValue::Vec2([x, y]),
+        NuccAnmKey::Vec3 { values: (x, y, z) } | NuccAnmKey::Vec3Linear { values: (x, y, z), .. } => {
+            TrackValue::Vec3([x, y, z])
+        }
+        NuccAnmKey::Vec4 { values: (x, y, z, w) } | NuccAnmKey::Vec4Linear { values: (x, y, z, w), .. } => {
+            TrackValue::Quaternion([x, y, z, w])
+        }
+        NuccAnmKey::Float { values } | NuccAnmKey::FloatLinear { values, .. } => TrackValue::Float(values),
+        NuccAnmKey::I16Vec { values } => TrackValue::Float(values as f32 / OPACITY_SHORT_SCALE),
+        NuccAnmKey::I16Vec3 { values: (x, y, z) } => {
+            TrackValue::Vec3([x, y, z].map(|value| value as f32 / SCALE_SHORT_SCALE))
+        }
+        // The scale of this format isn't known, so its values are kept as stored
+        NuccAnmKey::I16Vec3Linear { values: (x, y, z), .. } => TrackValue::Vec3([x, y, z].map(f32::from)),
+        NuccAnmKey::ShortVec4 { values: (x, y, z, w) } => {
+            TrackValue::Quaternion([x, y, z, w].map(|value| value as f32 / QUATERNION_SHORT_SCALE))
+        }
+        NuccAnmKey::Color { values: (r, g, b) } => TrackValue::Vec3([r, g, b].map(|value| value as f32 / 255.0)),
+        NuccAnmKey::Unknown {} => return None,
+    };
+
+    Some(value)
+}
+
+/// How a format's keys are laid out over time
+pub(crate) enum Timing {
+    /// The first key holds for the whole animation
+    Fixed,
+
+    /// One key per frame, interpolated between frames unless `step` is set
+    Table { step: bool },
+
+    /// Keys on the frame they carry, interpolated along a curve through them if `bezier` is set
+    Keyed { bezier: bool },
+}
+
+pub(crate) fn timing(key_format: &NuccAnmKeyFormat) -> Option<Timing> {
+    let timing = match key_format {
+        NuccAnmKeyFormat::Vector3Fixed
+        | NuccAnmKeyFormat::EulerXYZFixed
+        | NuccAnmKeyFormat::FloatFixed
+        | NuccAnmKeyFormat::Vector2Fixed => Timing::Fixed,
+        NuccAnmKeyFormat::EulerInterpolated
+        | NuccAnmKeyFormat::Vector3Table
+        | NuccAnmKeyFormat::FloatTable
+        | NuccAnmKeyFormat::QuaternionTable
+        | NuccAnmKeyFormat::QuaternionShortTable
+        | NuccAnmKeyFormat::OpacityShortTable
+        | NuccAnmKeyFormat::ScaleShortTable
+        | NuccAnmKeyFormat::ColorRGBTable => Timing::Table { step: false },
+        NuccAnmKeyFormat::FloatTableNoInterp
+        | NuccAnmKeyFormat::Vector3TableNoInterp
+        | NuccAnmKeyFormat::QuaternionShortTableNoInterp
+        | NuccAnmKeyFormat::OpacityShortTableNoInterp => Timing::Table { step: true },
+        NuccAnmKeyFormat::Vector3Linear
+        | NuccAnmKeyFormat::QuaternionLinear
+        | NuccAnmKeyFormat::FloatLinear
+        | NuccAnmKeyFormat::Vector2Linear
+        | NuccAnmKeyFormat::Vector3ShortLinear => Timing::Keyed { bezier: false },
+        NuccAnmKeyFormat::Vector3Bezier => Timing::Keyed { bezier: true },
+        NuccAnmKeyFormat::Unknown => return None,
+    };
+
+    Some(timing)
+}
+
+fn key_frame(key: &NuccAnmKey) -> Option<i32> {
+    match *key {
+        NuccAnmKey::Vec2Linear { frame, .. }
+        | NuccAnmKey::Vec3Linear { frame, .. }
+        | NuccAnmKey::Vec4Linear { frame, .. }
+        | NuccAnmKey::FloatLinear { frame, .. }
+        | NuccAnmKey::I16Vec3Linear { frame, .. } => Some(frame),
+        _ => None,
+    }
+}
+
+impl AnmTrack {
+    /// The value of the track at `frame`, with key frames counted in `frame_size` ticks per frame.
+    /// Frames before the first key or after the last one hold that key's value.
+    /// Bezier keys store no handles, so they're approximated: the curve between two keys gets the handles
+    /// of a Catmull-Rom spline through its neighbours, and may not match what the game plays exactly.
+    /// `None` if the track has no keys, or they can't be decoded in its key format.
+    pub fn sample(&self, frame: f32, frame_size: u32) -> Option<TrackValue> {
+        self.sample_looping(frame, frame_size, false)
+    }
+
+    /// Like `sample`, except that in a looped animation tables run on from their last key back into the first
+    pub(crate) fn sample_looping(&self, frame: f32, frame_size: u32, looped: bool) -> Option<TrackValue> {
+        let values = self.keys.iter().map(key_value).collect::<Option<Vec<TrackValue>>>()?;
+
+        match timing(&self.key_format)? {
+            Timing::Fixed => values.first().copied(),
+            Timing::Table { step } => sample_table(&values, frame, step, looped),
+            Timing::Keyed { bezier } => {
+                // A negative frame marks the end of the track rather than a key
+                let (frames, values): (Vec<f32>, Vec<TrackValue>) = self
+                    .keys
+                    .iter()
+                    .map(key_frame)
+                    .zip(values)
+                    .filter_map(|(key_frame, value)| Some((key_frame.filter(|&key_frame| key_frame >= 0)? as f32, value)))
+                    .unzip();
+
+                sample_keyed(&frames, &values, frame * frame_size.max(1) as f32, bezier)
+            }
+        }
+    }
+}
+
+impl NuccAnm {
+    /// The length of the animation in frames
+    pub fn length(&self) -> f32 {
+        self.frame_count as f32 / self.frame_size.max(1) as f32
+    }
+
+    /// The frame actually played at `frame`: wrapped around the length if the animation loops, clamped to it otherwise
+    pub fn playback_frame(&self, frame: f32) -> f32 {
+        let length = self.length();
+
+        if length <= 0.0 {
+            0.0
+        } else if self.is_looped {
+            frame.rem_euclid(length)
+        } else {
+            frame.clamp(0.0, length)
+        }
+    }
+
+    /// The value of every track of `entry` at `frame`, keyed by track index.
+    /// Tracks that can't be sampled are left out.
+    pub fn sample_entry(&self, entry: &AnmEntry, frame: f32) -> IndexMap<u16, TrackValue> {
+        let frame = self.playback_frame(frame);
+
+        entry
+            .tracks
+            .iter()
+            .filter_map(|track| Some((track.track_index, track.sample_looping(frame, self.frame_size, self.is_looped)?)))
+            .collect()
+    }
+}
+
+fn sample_table(values: &[TrackValue], frame: f32, step: bool, looped: bool) -> Option<TrackValue> {
+    let last = values.len().checked_sub(1)?;
+    let frame = match looped {
+        true => frame.rem_euclid(values.len() as f32),
+        false => frame.clamp(0.0, last as f32),
+    };
+
+    let index = (frame.floor() as usize).min(last);
+    let t = frame - index as f32;
+
+    // Looping, the last key leads back into the first
+    let next = match looped && index == last {
+        true => values.first(),
+        false => values.get(index + 1),
+    };
+
+    match next {
+        Some(next) if !step && t > 0.0 => Some(values[index].interpolate(next, t)),
+        _ => Some(values[index]),
+    }
+}
+
+/// `frames` are in ticks, as is `tick`
+fn sample_keyed(frames: &[f32], values: &[TrackValue], tick: f32, bezier: bool) -> Option<TrackValue> {
+    // The first key after `tick`
+    let next = frames.partition_point(|&frame| frame <= tick);
+
+    if next == 0 {
+        return values.first().copied();
+    }
+
+    if next == frames.len() {
+        return values.last().copied();
+    }
+
+    let index = next - 1;
+    let t = (tick - frames[index]) / (frames[next] - frames[index]);
+
+    if !bezier {
+        return Some(values[index].interpolate(&values[next], t));
+    }
+
+    // The format has no handles, so they're placed along the neighbouring keys like a Catmull-Rom spline
+    let before = values[index.saturating_sub(1)].components();
+    let start = values[index].components();
+    let end = values[next].components();
+    let after = values.get(next + 1).unwrap_or(&values[next]).components();
+
+    Some(values[index].map(|i, _| {
+        let control_start = start[i] + (end[i] - before[i]) / 6.0;
+        let control_end = end[i] - (after[i] - start[i]) / 6.0;
+
+        cubic_bezier(start[i], control_start, control_end, end[i], t)
+    }))
+}
+
+fn cubic_bezier(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let u = 1.0 - t;
+
+    u * u * u * p0 + 3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t * p3
+}
+
+/// Spherical interpolation along the shorter arc, falling back to a normalized lerp for nearly equal rotations
+fn slerp(from: &[f32; 4], to: &[f32], t: f32) -> [f32; 4] {
+    let mut dot: f32 = (0..4).map(|i| from[i] * to[i]).sum();
+
+    // q and -q are the same rotation, so take whichever is closer
+    let sign = if dot < 0.0 { -1.0 } else { 1.0 };
+    dot *= sign;
+
+    let (from_weight, to_weight) = if dot > 0.9995 {
+        (1.0 - t, t)
+    } else {
+        let angle = dot.acos();
+        let sin = angle.sin();
+
+        (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+    };
+
+    let result: [f32; 4] = std::array::from_fn(|i| from[i] * from_weight + to[i] * sign * to_weight);
+    let length = result.iter().map(|value| value * value).sum::<f32>().sqrt();
+
+    if length > 0.0 {
+        result.map(|value| value / length)
+    } else {
+        result
+    }
+}
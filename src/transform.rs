@@ -0,0 +1,97 @@
+//! # Payload transforms
+//! Files whose header has the `encrypted` flag set store everything after the header (the index and every chunk)
+//! in a transformed form. Transforms are registered per flag value; reading decodes the payload before parsing it,
+//! and writing encodes it again when the document's layout still carries the flag.
+//!
+use bytes::Bytes;
+use hashbrown::HashMap;
+use std::io;
+use std::sync::{Arc, LazyLock, RwLock};
+
+use super::error::{XfbinError, XfbinResult};
+
+/// Size of `XfbinHeader`: magic, version, flag and padding
+const HEADER_SIZE: usize = 0x10;
+const FLAG_OFFSET: usize = 0x08;
+const MAGIC: &[u8] = b"NUCC";
+
+pub trait PayloadTransform: Send + Sync {
+    /// Turn the payload as stored in the file into a plain index and chunks
+    fn decode(&self, payload: &[u8]) -> XfbinResult<Vec<u8>>;
+
+    /// The inverse of `decode`
+    fn encode(&self, payload: &[u8]) -> XfbinResult<Vec<u8>>;
+}
+
+static TRANSFORMS: LazyLock<RwLock<HashMap<u16, Arc<dyn PayloadTransform>>>> = LazyLock::new(Default::default);
+
+/// Use `transform` for files whose `encrypted` flag is `flag`, replacing any transform registered for it before
+pub fn register_transform<T: PayloadTransform + 'static>(flag: u16, transform: T) {
+    TRANSFORMS.write().unwrap().insert(flag, Arc::new(transform));
+}
+
+/// Returns whether a transform was registered for `flag`
+pub fn unregister_transform(flag: u16) -> bool {
+    TRANSFORMS.write().unwrap().remove(&flag).is_some()
+}
+
+fn transform_for(flag: u16) -> XfbinResult<Arc<dyn PayloadTransform>> {
+    TRANSFORMS
+        .read()
+        .unwrap()
+        .get(&flag)
+        .cloned()
+        .ok_or(XfbinError::MissingTransform { flag })
+}
+
+/// The `encrypted` flag of a whole file, or 0 if it's too short to have a header
+fn header_flag(buf: &[u8]) -> u16 {
+    buf.get(FLAG_OFFSET..FLAG_OFFSET + 2)
+        .map(|flag| u16::from_be_bytes([flag[0], flag[1]]))
+        .unwrap_or_default()
+}
+
+/// Decode the payload of a whole file if it's flagged, returning the plain file with the flag cleared and the flag.
+/// Plain files are returned as they are.
+pub(crate) fn decode_file(buf: Bytes) -> XfbinResult<(Bytes, u16)> {
+    let flag = header_flag(&buf);
+    if flag == 0 {
+        return Ok((buf, 0));
+    }
+
+    // Plain files are checked by the parser; flagged ones have to be before their payload is handed to a transform
+    if buf.len() < HEADER_SIZE {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+
+    if &buf[..MAGIC.len()] != MAGIC {
+        return Err(XfbinError::BadMagic {
+            offset: 0,
+            found: format!("{:?}", &buf[..MAGIC.len()]),
+        });
+    }
+
+    let payload = transform_for(flag)?.decode(&buf[HEADER_SIZE..])?;
+
+    let mut decoded = Vec::with_capacity(HEADER_SIZE + payload.len());
+    decoded.extend_from_slice(&buf[..HEADER_SIZE]);
+    decoded[FLAG_OFFSET..FLAG_OFFSET + 2].fill(0);
+    decoded.extend(payload);
+
+    Ok((decoded.into(), flag))
+}
+
+/// Encode the payload of a whole written file if its header is flagged
+pub(crate) fn encode_file(mut buf: Vec<u8>) -> XfbinResult<Vec<u8>> {
+    let flag = header_flag(&buf);
+    if flag == 0 {
+        return Ok(buf);
+    }
+
+    let payload = transform_for(flag)?.encode(&buf[HEADER_SIZE..])?;
+
+    buf.truncate(HEADER_SIZE);
+    buf.extend(payload);
+
+    Ok(buf)
+}
@@ -0,0 +1,188 @@
+//! # Unpack
+//! A document laid out as a directory tree, so it can be edited with ordinary tools and repacked afterwards.
+//! The root holds an `xfbin.json` manifest listing one folder per page. Each page folder has a `page.json`
+//! with the page's struct infos and references, and one file per struct: raw `.bin` payloads for binaries
+//! and unknown chunks, whose headers stay in `page.json`, and JSON for every typed struct.
+//!
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use super::error::XfbinResult;
+use super::json::{check_format_version, sanitize, struct_from_versioned_json_str, struct_to_json_string, JSON_FORMAT_VERSION};
+use super::nucc::*;
+use super::nucc_chunk::NuccChunkType;
+use super::xfbin::{Xfbin, XfbinLayout, XfbinPage};
+
+const MANIFEST: &str = "xfbin.json";
+const PAGE_MANIFEST: &str = "page.json";
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    format_version: u32,
+    version: u16,
+    layout: Option<XfbinLayout>,
+
+    /// Page folder names, in page order
+    pages: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PageManifest {
+    struct_infos: Vec<NuccStructInfo>,
+    struct_references: Vec<NuccStructReference>,
+    structs: Vec<StructFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "snake_case")]
+enum StructFile {
+    Binary {
+        file: String,
+        struct_info: NuccStructInfo,
+        version: u16,
+    },
+    Unknown {
+        file: String,
+        struct_info: NuccStructInfo,
+        version: u16,
+        chunk_type: String,
+    },
+    Json {
+        file: String,
+    },
+}
+
+/// Write `xfbin` into `dirpath`, creating it if needed. Files from an earlier unpack are overwritten.
+pub(crate) fn unpack(xfbin: &Xfbin, dirpath: &Path) -> XfbinResult<()> {
+    fs::create_dir_all(dirpath)?;
+
+    let mut manifest = Manifest {
+        format_version: JSON_FORMAT_VERSION,
+        version: xfbin.version,
+        layout: xfbin.layout.clone(),
+        pages: Vec::new(),
+    };
+
+    for (page_index, page) in xfbin.pages.iter().enumerate() {
+        let page_name = format!("page_{:03}", page_index);
+        let page_dir = dirpath.join(&page_name);
+        fs::create_dir_all(&page_dir)?;
+
+        let mut page_manifest = PageManifest {
+            struct_infos: page.struct_infos.clone(),
+            struct_references: page.struct_references.clone(),
+            structs: Vec::new(),
+        };
+
+        for (struct_index, nucc_struct) in page.structs.iter().enumerate() {
+            let stem = format!("{:03}_{}", struct_index, sanitize(&nucc_struct.struct_info().chunk_name));
+
+            let struct_file = match nucc_struct.chunk_type() {
+                NuccChunkType::NuccChunkBinary => {
+                    let binary = downcast_struct::<NuccBinary>(nucc_struct.clone_boxed(), NuccChunkType::NuccChunkBinary)?;
+                    let file = format!("{}.bin", stem);
+                    fs::write(page_dir.join(&file), &binary.data)?;
+
+                    StructFile::Binary {
+                        file,
+                        struct_info: binary.struct_info,
+                        version: binary.version,
+                    }
+                }
+
+                NuccChunkType::NuccChunkUnknown => {
+                    let unknown = downcast_struct::<NuccUnknown>(nucc_struct.clone_boxed(), NuccChunkType::NuccChunkUnknown)?;
+                    let file = format!("{}.bin", stem);
+                    fs::write(page_dir.join(&file), &unknown.data)?;
+
+                    StructFile::Unknown {
+                        file,
+                        struct_info: unknown.struct_info,
+                        version: unknown.version,
+                        chunk_type: unknown.chunk_type,
+                    }
+                }
+
+                _ => {
+                    let file = format!("{}.json", stem);
+                    fs::write(page_dir.join(&file), struct_to_json_string(nucc_struct.as_ref())?)?;
+
+                    StructFile::Json { file }
+                }
+            };
+
+            page_manifest.structs.push(struct_file);
+        }
+
+        fs::write(page_dir.join(PAGE_MANIFEST), serde_json::to_string_pretty(&page_manifest)?)?;
+        manifest.pages.push(page_name);
+    }
+
+    fs::write(dirpath.join(MANIFEST), serde_json::to_string_pretty(&manifest)?)?;
+
+    Ok(())
+}
+
+/// Rebuild a document from a directory written by `unpack`, following its manifests
+pub(crate) fn repack(dirpath: &Path) -> XfbinResult<Xfbin> {
+    let manifest = serde_json::from_slice::<Manifest>(&fs::read(dirpath.join(MANIFEST))?)?;
+
+    check_format_version(manifest.format_version)?;
+
+    let pages = manifest
+        .pages
+        .iter()
+        .map(|page_name| repack_page(&dirpath.join(page_name), manifest.format_version))
+        .collect::<XfbinResult<Vec<_>>>()?;
+
+    Ok(Xfbin {
+        version: manifest.version,
+        pages,
+        layout: manifest.layout,
+    })
+}
+
+fn repack_page(page_dir: &Path, format_version: u32) -> XfbinResult<XfbinPage> {
+    let page_manifest = serde_json::from_slice::<PageManifest>(&fs::read(page_dir.join(PAGE_MANIFEST))?)?;
+
+    let structs = page_manifest
+        .structs
+        .into_iter()
+        .map(|struct_file| -> XfbinResult<Box<dyn NuccStruct>> {
+            Ok(match struct_file {
+                StructFile::Binary {
+                    file,
+                    struct_info,
+                    version,
+                } => Box::new(NuccBinary {
+                    struct_info,
+                    version,
+                    data: fs::read(page_dir.join(file))?.into(),
+                }),
+
+                StructFile::Unknown {
+                    file,
+                    struct_info,
+                    version,
+                    chunk_type,
+                } => Box::new(NuccUnknown {
+                    struct_info,
+                    version,
+                    data: fs::read(page_dir.join(file))?.into(),
+                    chunk_type,
+                }),
+
+                StructFile::Json { file } => {
+                    struct_from_versioned_json_str(&fs::read_to_string(page_dir.join(file))?, format_version)?
+                }
+            })
+        })
+        .collect::<XfbinResult<Vec<_>>>()?;
+
+    Ok(XfbinPage {
+        structs,
+        struct_infos: page_manifest.struct_infos,
+        struct_references: page_manifest.struct_references,
+    })
+}
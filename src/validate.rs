@@ -0,0 +1,522 @@
+//! # Validation
+//! Structural checks that report every problem in a file or document at once, instead of stopping at the first
+//! (or leaving it to the game to crash). Raw files are scanned piece by piece and checked index first, so files that fail
+//! to load, even truncated ones, can still be inspected;
+//! documents are checked struct by struct, and then packed to check the index the writer would produce for them.
+//!
+use binrw::{BinReaderExt, BinResult};
+use hashbrown::HashSet;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::fmt;
+use std::io::{Cursor, Seek, SeekFrom};
+use std::str::FromStr;
+
+use super::codec;
+use super::nucc::{downcast_chunk, NuccAnm, NuccStructInfo};
+use super::error::XfbinError;
+use super::nucc_chunk::nucc_chunk_anm::{read_key, AnmClump, AnmCoord, NuccAnmKeyFormat};
+use super::nucc_chunk::{NuccChunkPage, NuccChunkType};
+use super::transform;
+use super::xfbin::{Xfbin, XfbinIndexTables, XfbinPage};
+use super::xfbin_file::{XfbinChunk, XfbinChunkHeader, XfbinFile, XfbinHeader, XfbinIndex};
+
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Suspicious, but the file can still be loaded
+    Warning,
+    /// The file can't be loaded, or loads with broken data
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub page_index: Option<usize>,
+
+    /// The struct the finding is about, if it's about one
+    pub struct_info: Option<NuccStructInfo>,
+    pub message: String,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Finding {
+    fn __repr__(&self) -> String {
+        format!("Finding({})", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.severity)?;
+
+        if let Some(page_index) = self.page_index {
+            write!(f, "page {}: ", page_index)?;
+        }
+
+        if let Some(struct_info) = &self.struct_info {
+            write!(f, "{} ({}): ", struct_info.chunk_name, struct_info.chunk_type)?;
+        }
+
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Whether any of `findings` would keep the file from loading
+pub fn has_errors(findings: &[Finding]) -> bool {
+    findings.iter().any(|finding| finding.severity == Severity::Error)
+}
+
+#[derive(Default)]
+struct Findings {
+    findings: Vec<Finding>,
+    page_index: Option<usize>,
+    struct_info: Option<NuccStructInfo>,
+}
+
+impl Findings {
+    fn push(&mut self, severity: Severity, message: String) {
+        self.findings.push(Finding {
+            severity,
+            page_index: self.page_index,
+            struct_info: self.struct_info.clone(),
+            message,
+        });
+    }
+
+    fn error(&mut self, message: String) {
+        self.push(Severity::Error, message);
+    }
+
+    fn warning(&mut self, message: String) {
+        self.push(Severity::Warning, message);
+    }
+
+    fn check_index(&mut self, what: &str, index: usize, len: usize) -> bool {
+        if index >= len {
+            self.error(format!("{} {} is out of range (length {})", what, index, len));
+            return false;
+        }
+
+        true
+    }
+}
+
+impl Xfbin {
+    /// Check the document for anything that would produce a file the game can't load, or loads wrong
+    pub fn validate(&self) -> Vec<Finding> {
+        let mut findings = Findings::default();
+
+        let mut seen = HashSet::new();
+        for (page_index, page) in self.pages.iter().enumerate() {
+            findings.page_index = Some(page_index);
+
+            validate_page(page, &mut seen, &mut findings);
+        }
+
+        findings.page_index = None;
+        findings.struct_info = None;
+
+        // The writer derives the index, so check the one it would write
+        match XfbinFile::try_from(self.clone()) {
+            Ok(xfbin_file) => validate_file(&xfbin_file, &mut findings),
+            Err(e) => findings.error(format!("the file can't be written: {}", e)),
+        }
+
+        findings.findings
+    }
+}
+
+/// Check a raw file's index and chunk headers, and if those hold up, every struct in it
+pub(crate) fn validate_xfbin_buf(buf: Vec<u8>) -> Vec<Finding> {
+    let mut findings = Findings::default();
+
+    let xfbin_file = match scan_file(buf, &mut findings) {
+        Some(xfbin_file) => xfbin_file,
+        None => return findings.findings,
+    };
+
+    validate_file(&xfbin_file, &mut findings);
+
+    if has_errors(&findings.findings) {
+        return findings.findings;
+    }
+
+    match Xfbin::try_from(xfbin_file) {
+        Ok(xfbin) => {
+            let mut seen = HashSet::new();
+            for (page_index, page) in xfbin.pages.iter().enumerate() {
+                findings.page_index = Some(page_index);
+
+                validate_page(page, &mut seen, &mut findings);
+            }
+        }
+        Err(e) => findings.error(format!("the file can't be read: {}", e)),
+    }
+
+    findings.findings
+}
+
+/// Read the header, index and chunks one at a time like the lazy reader does, reporting the first one that can't be read.
+/// `None` if the file is unusable before the chunks start; otherwise the chunks up to the broken one.
+fn scan_file(buf: Vec<u8>, findings: &mut Findings) -> Option<XfbinFile> {
+    let (buf, encrypted) = match transform::decode_file(buf.into()) {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            findings.error(format!("the file can't be decoded: {}", e));
+            return None;
+        }
+    };
+
+    let end = buf.len() as u64;
+    let mut reader = Cursor::new(&buf[..]);
+
+    let mut header = match reader.read_be::<XfbinHeader>() {
+        Ok(header) => header,
+        Err(e) => {
+            findings.error(format!("the header can't be read: {}", e));
+            return None;
+        }
+    };
+    header.encrypted = encrypted;
+
+    let index = match reader.read_be::<XfbinIndex>() {
+        Ok(index) => index,
+        Err(e) => {
+            findings.error(format!("the index can't be read: {}", e));
+            return None;
+        }
+    };
+
+    let mut chunks = Vec::new();
+    while reader.position() < end {
+        let chunk_index = chunks.len();
+        let offset = reader.position();
+
+        let chunk = match reader.read_be::<XfbinChunkHeader>() {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                findings.error(format!("chunk {} header at offset {:#x} can't be read: {}", chunk_index, offset, e));
+                break;
+            }
+        };
+
+        let start = reader.position() as usize;
+        let left = buf.len() - start;
+        if chunk.size as usize > left {
+            findings.error(format!(
+                "chunk {} at offset {:#x} is {} bytes, but only {} are left in the file",
+                chunk_index, offset, chunk.size, left
+            ));
+            break;
+        }
+
+        reader.set_position((start + chunk.size as usize) as u64);
+
+        chunks.push(XfbinChunk {
+            offset,
+            chunk_map_index: chunk.chunk_map_index,
+            version: chunk.version,
+            data: buf.slice(start..start + chunk.size as usize),
+        });
+    }
+
+    Some(XfbinFile { header, index, chunks })
+}
+
+fn validate_page<'a>(page: &'a XfbinPage, seen: &mut HashSet<&'a NuccStructInfo>, findings: &mut Findings) {
+    findings.struct_info = None;
+
+    let mut page_struct_infos = HashSet::new();
+    for struct_info in &page.struct_infos {
+        if !page_struct_infos.insert(struct_info) {
+            findings.error(format!("struct info {} is listed more than once in the page", struct_info));
+        }
+    }
+
+    // References can only point at chunk maps the page lists, or that the writer adds for its structs
+    for reference in &page.struct_references {
+        let listed = page_struct_infos.contains(&reference.struct_info)
+            || page.structs.iter().any(|nucc_struct| nucc_struct.struct_info() == &reference.struct_info);
+
+        if !listed {
+            findings.error(format!(
+                "reference \"{}\" points at {}, which isn't in the page's struct infos",
+                reference.chunk_name, reference.struct_info
+            ));
+        }
+    }
+
+    for nucc_struct in &page.structs {
+        let struct_info = nucc_struct.struct_info();
+        findings.struct_info = Some(struct_info.clone());
+
+        if !seen.insert(struct_info) {
+            findings.warning("another struct has the same struct info, so lookups and references only find the first".to_string());
+        }
+
+        let type_matches = match nucc_struct.chunk_type() {
+            NuccChunkType::NuccChunkUnknown => true,
+            NuccChunkType::NuccChunkCustom => codec::handles(&struct_info.chunk_type, nucc_struct.as_ref()),
+            chunk_type => NuccChunkType::from_str(&struct_info.chunk_type).unwrap_or_default() == chunk_type,
+        };
+
+        if !type_matches {
+            findings.error(format!("chunk type \"{}\" doesn't match the struct's type {}", struct_info.chunk_type, nucc_struct.chunk_type()));
+        }
+
+        if !page.struct_infos.is_empty() && !page_struct_infos.contains(struct_info) {
+            findings.warning("the struct info isn't in the page's struct infos, so the page's chunk map count changes when written".to_string());
+        }
+
+        if let Some(anm) = nucc_struct.downcast_ref::<NuccAnm>() {
+            validate_anm(anm, page.struct_infos.len(), findings);
+        }
+    }
+}
+
+fn validate_anm(anm: &NuccAnm, struct_info_count: usize, findings: &mut Findings) {
+    // Clumps, bones, models and other entries are chunk map indices into the page's struct infos
+    for clump in &anm.clumps {
+        findings.check_index("clump chunk map index", clump.clump_index as usize, struct_info_count);
+
+        for &index in clump.bone_material_indices.iter().chain(&clump.model_indices) {
+            findings.check_index("clump bone/model chunk map index", index as usize, struct_info_count);
+        }
+    }
+
+    for &index in anm.other_entries_indices.iter().chain(&anm.unk_entry_indices) {
+        findings.check_index("entry chunk map index", index as usize, struct_info_count);
+    }
+
+    for coord_parent in &anm.coord_parents {
+        validate_coord(anm, &coord_parent.parent, "coord parent", findings);
+        validate_coord(anm, &coord_parent.child, "coord parent child", findings);
+    }
+
+    for (entry_index, entry) in anm.entries.iter().enumerate() {
+        validate_coord(anm, &entry.coord, &format!("entry {}", entry_index), findings);
+
+        for (track_index, track) in entry.tracks.iter().enumerate() {
+            if let Some(key_index) = track.mismatched_key() {
+                findings.error(format!(
+                    "entry {} track {}: key {} is a {:?}, which doesn't match key format {:?}",
+                    entry_index, track_index, key_index, track.keys[key_index], track.key_format
+                ));
+            }
+        }
+    }
+}
+
+/// Check the track headers of a raw anm chunk against the keys after them. The reader only goes by the frame counts,
+/// and the writer recomputes the track sizes, so a header that's off (or too small to hold its track) only shows up here.
+fn scan_anm_tracks(data: &[u8], findings: &mut Findings) -> BinResult<()> {
+    let mut reader = Cursor::new(data);
+
+    // Skip frame_count and frame_size, then read the counts up to the entries
+    reader.set_position(8);
+    let [entry_count, _is_looped, clump_count, other_entry_count, unk_entry_count, coord_count]: [u16; 6] = reader.read_be()?;
+
+    for _ in 0..clump_count {
+        reader.read_be::<AnmClump>()?;
+    }
+
+    let indices_size = (other_entry_count as i64 + unk_entry_count as i64) * 4;
+    reader.seek(SeekFrom::Current(indices_size + coord_count as i64 * 8))?;
+
+    for entry_index in 0..entry_count as usize {
+        // Skip the coord and entry format
+        reader.seek(SeekFrom::Current(6))?;
+
+        let track_count: u16 = reader.read_be()?;
+        let mut headers = Vec::with_capacity(track_count as usize);
+        for _ in 0..track_count {
+            headers.push(reader.read_be::<(u16, NuccAnmKeyFormat, u16, u16)>()?);
+        }
+
+        for (track_index, (_, key_format, frame_count, track_size)) in headers.into_iter().enumerate() {
+            let start = reader.position();
+            for _ in 0..frame_count {
+                read_key(&mut reader, &key_format)?;
+            }
+            let keys_size = reader.position() - start;
+
+            if keys_size > u16::MAX as u64 {
+                findings.error(
+                    XfbinError::TrackTooLarge {
+                        entry_index,
+                        track_index,
+                        key_count: frame_count as usize,
+                        key_format,
+                    }
+                    .to_string(),
+                );
+            } else if keys_size != track_size as u64 {
+                findings.error(format!(
+                    "entry {} track {}: the header's track size is {} bytes, but its frame count of {} keys of key format {:?} takes {}",
+                    entry_index, track_index, track_size, frame_count, key_format, keys_size
+                ));
+            }
+        }
+
+        reader.set_position((reader.position() + 3) & !3);
+    }
+
+    Ok(())
+}
+
+/// Coords with a clump index of -1 refer to the anm's other entries instead of a clump's bones
+fn validate_coord(anm: &NuccAnm, coord: &AnmCoord, what: &str, findings: &mut Findings) {
+    let coord_index = coord.coord_index as usize;
+
+    match coord.clump_index {
+        -1 => {
+            findings.check_index(&format!("{} other entry index", what), coord_index, anm.other_entries_indices.len());
+        }
+        clump_index if clump_index < 0 => {
+            findings.error(format!("{} has an invalid clump index {}", what, clump_index));
+        }
+        clump_index => {
+            let clump_index = clump_index as usize;
+
+            if findings.check_index(&format!("{} clump index", what), clump_index, anm.clumps.len()) {
+                let AnmClump { bone_material_indices, .. } = &anm.clumps[clump_index];
+                findings.check_index(&format!("{} coord index", what), coord_index, bone_material_indices.len());
+            }
+        }
+    }
+}
+
+fn validate_file(xfbin_file: &XfbinFile, findings: &mut Findings) {
+    let index = &xfbin_file.index;
+    findings.page_index = None;
+    findings.struct_info = None;
+
+    for chunk_map in &index.chunk_maps {
+        findings.check_index("chunk type index", chunk_map.chunk_type_index as usize, index.chunk_types.len());
+        findings.check_index("filepath index", chunk_map.filepath_index as usize, index.filepaths.len());
+        findings.check_index("chunk name index", chunk_map.chunk_name_index as usize, index.chunk_names.len());
+    }
+
+    for reference in &index.chunk_references {
+        findings.check_index("chunk reference name index", reference.chunk_name_index as usize, index.chunk_names.len());
+        findings.check_index("chunk reference map index", reference.chunk_map_index as usize, index.chunk_maps.len());
+    }
+
+    for &chunk_map_index in &index.chunk_map_indices {
+        findings.check_index("chunk map", chunk_map_index as usize, index.chunk_maps.len());
+    }
+
+    if has_errors(&findings.findings) {
+        return;
+    }
+
+    let tables = match XfbinIndexTables::new(index) {
+        Ok(tables) => tables,
+        Err(e) => return findings.error(e.to_string()),
+    };
+
+    let mut seen = HashSet::new();
+    for struct_info in &tables.struct_infos {
+        if !seen.insert(struct_info) {
+            findings.warning(format!("chunk map {} is listed more than once in the index", struct_info));
+        }
+    }
+
+    // Walk the chunks like the reader does; a page's chunk map count is only known once its page chunk is reached
+    let mut page_index = 0;
+    let mut struct_infos_index = 0;
+    let mut struct_references_index = 0;
+    let mut page_chunk_map_indices = Vec::new();
+
+    for (chunk_index, chunk) in xfbin_file.chunks.iter().enumerate() {
+        findings.page_index = Some(page_index);
+        findings.struct_info = None;
+
+        let mapped_index = struct_infos_index + chunk.chunk_map_index as usize;
+        if !findings.check_index(
+            &format!("chunk {} map index", chunk_index),
+            mapped_index,
+            tables.struct_infos_mapped.len(),
+        ) {
+            continue;
+        }
+
+        let struct_info = &tables.struct_infos_mapped[mapped_index];
+        page_chunk_map_indices.push((chunk_index, chunk.chunk_map_index as usize));
+
+        match NuccChunkType::from_str(&struct_info.chunk_type).unwrap_or_default() {
+            NuccChunkType::NuccChunkPage => {}
+            NuccChunkType::NuccChunkAnm => {
+                findings.struct_info = Some(struct_info.clone());
+
+                // Anm chunks that can't be read at all are reported when the structs are read
+                let _ = scan_anm_tracks(&chunk.data, findings);
+                continue;
+            }
+            _ => continue,
+        }
+
+        let page = chunk
+            .clone()
+            .unpack(chunk_index, struct_info)
+            .and_then(|parsed| downcast_chunk::<NuccChunkPage>(parsed, NuccChunkType::NuccChunkPage));
+
+        let page = match page {
+            Ok(page) => page,
+            Err(e) => return findings.error(e.to_string()),
+        };
+
+        let map_index_count = page.map_index_count as usize;
+        for (chunk_index, chunk_map_index) in page_chunk_map_indices.drain(..) {
+            if chunk_map_index >= map_index_count {
+                findings.error(format!(
+                    "chunk {} map index {} is outside its page's {} chunk maps",
+                    chunk_index, chunk_map_index, map_index_count
+                ));
+            }
+        }
+
+        page_index += 1;
+        struct_infos_index += map_index_count;
+        struct_references_index += page.reference_count as usize;
+    }
+
+    findings.page_index = None;
+
+    if !page_chunk_map_indices.is_empty() {
+        findings.warning(format!("{} chunks after the last page chunk don't belong to any page", page_chunk_map_indices.len()));
+    }
+
+    if struct_infos_index != tables.struct_infos_mapped.len() {
+        findings.error(format!(
+            "the page chunks cover {} chunk map indices, but the index has {}",
+            struct_infos_index,
+            tables.struct_infos_mapped.len()
+        ));
+    }
+
+    if struct_references_index != tables.struct_references.len() {
+        findings.error(format!(
+            "the page chunks cover {} references, but the index has {}",
+            struct_references_index,
+            tables.struct_references.len()
+        ));
+    }
+}
@@ -1,337 +1,699 @@
 use binrw::NullString;
 use indexmap::IndexMap;
 use itertools::Itertools;
-
-use pyo3::prelude::*;
-use pyo3::types::PyList;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::ops::Range;
+use std::str::FromStr;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::pyclass;
 
 
+use super::error::{get_indexed, XfbinError, XfbinResult};
 use super::nucc::*;
 use super::nucc_chunk::*;
+use super::query::StructFilter;
+use super::reference::{ReferenceResolver, ReferenceTarget, ResolvedReference};
 use super::xfbin_file::*;
 
 
-#[pyclass(module = "xfbin_lib")]
 #[derive(Debug, Clone)]
 pub struct Xfbin {
-
-    #[pyo3(get, set)]
     pub version: u16,
+    pub pages: Vec<XfbinPage>,
 
-   #[pyo3(get, set)]
-    pub pages: Py<PyList>
+    /// The layout of the file this was read from.
+    /// While present, writing preserves it so an unmodified file is reproduced byte for byte;
+    /// set it to `None` to rebuild the index from scratch instead.
+    pub layout: Option<XfbinLayout>,
 }
 
-#[pymethods]
-impl Xfbin {
-
-    #[new]
-    #[pyo3(signature = (version = None, pages = None))]
-    fn __new__(py: Python, version: Option<u16>, pages: Option<Py<PyList>>) -> Self {
+impl Default for Xfbin {
+    fn default() -> Self {
         Self {
-            version: version.unwrap_or(121),
-            pages : pages.unwrap_or(PyList::empty_bound(py).into())
+            version: 121,
+            pages: Vec::new(),
+            layout: None,
         }
     }
+}
 
-    fn __repr__(&self) -> PyResult<String> {
-        Python::with_gil(|py| 
-        {   
-           let pages: Vec<XfbinPage> = self.pages.extract(py).unwrap();
-           let pages: Vec<String> = pages.iter().map(|page| page.__repr__().unwrap()).collect();
+impl Xfbin {
+    /// Every struct in every page, in file order
+    pub fn structs(&self) -> impl Iterator<Item = &dyn NuccStruct> {
+        self.pages
+            .iter()
+            .flat_map(|page| page.structs.iter().map(|nucc_struct| nucc_struct.as_ref()))
+    }
 
-            Ok(format!(
-            "Xfbin(version={}, pages={})",
-            self.version, pages.join(", ")
-            ))
-           
-        })
+    pub fn structs_mut(&mut self) -> impl Iterator<Item = &mut dyn NuccStruct> {
+        self.pages
+            .iter_mut()
+            .flat_map(|page| page.structs.iter_mut().map(|nucc_struct| nucc_struct.as_mut()))
+    }
+
+    /// The first struct whose struct info matches `filter`
+    pub fn find(&self, filter: &StructFilter) -> Option<&dyn NuccStruct> {
+        self.structs().find(|nucc_struct| filter.matches(nucc_struct.struct_info()))
+    }
+
+    pub fn find_mut(&mut self, filter: &StructFilter) -> Option<&mut dyn NuccStruct> {
+        self.structs_mut().find(|nucc_struct| filter.matches(nucc_struct.struct_info()))
+    }
+
+    pub fn find_all<'a>(&'a self, filter: &'a StructFilter) -> impl Iterator<Item = &'a dyn NuccStruct> {
+        self.structs().filter(move |nucc_struct| filter.matches(nucc_struct.struct_info()))
+    }
+
+    pub fn get_by_name(&self, chunk_name: &str) -> Option<&dyn NuccStruct> {
+        self.structs().find(|nucc_struct| nucc_struct.struct_info().chunk_name == chunk_name)
+    }
+
+    pub fn get_by_name_mut(&mut self, chunk_name: &str) -> Option<&mut dyn NuccStruct> {
+        self.structs_mut().find(|nucc_struct| nucc_struct.struct_info().chunk_name == chunk_name)
+    }
+
+    /// Every struct of type `T`, e.g. `xfbin.structs_of::<NuccAnm>()`
+    pub fn structs_of<T: NuccStruct>(&self) -> impl Iterator<Item = &T> {
+        self.structs().filter_map(|nucc_struct| nucc_struct.downcast_ref::<T>())
+    }
+
+    pub fn structs_of_mut<T: NuccStruct>(&mut self) -> impl Iterator<Item = &mut T> {
+        self.structs_mut().filter_map(|nucc_struct| nucc_struct.downcast_mut::<T>())
+    }
+
+    /// Find what `reference`, from the page at `page_index`, points at
+    pub fn resolve_reference(&self, page_index: usize, reference: &NuccStructReference) -> ReferenceTarget {
+        self.reference_resolver().resolve(page_index, reference)
+    }
+
+    /// The struct `reference` points at, if it's in this file
+    pub fn follow_reference(&self, page_index: usize, reference: &NuccStructReference) -> Option<&dyn NuccStruct> {
+        let (page_index, struct_index) = self.reference_location(page_index, reference)?;
+
+        Some(self.pages[page_index].structs[struct_index].as_ref())
+    }
+
+    pub fn follow_reference_mut(&mut self, page_index: usize, reference: &NuccStructReference) -> Option<&mut dyn NuccStruct> {
+        let (page_index, struct_index) = self.reference_location(page_index, reference)?;
+
+        Some(self.pages[page_index].structs[struct_index].as_mut())
+    }
+
+    /// Every entry of every page's reference table, with what it resolves to
+    pub fn references(&self) -> Vec<ResolvedReference> {
+        self.reference_resolver().resolve_all(
+            self.pages
+                .iter()
+                .enumerate()
+                .map(|(page_index, page)| (page_index, page.struct_references.as_slice())),
+        )
     }
 
-    fn __str__(&self) -> PyResult<String> {
-        self.__repr__()
+    /// References whose struct should be in this file but isn't, typically left behind by an edit
+    pub fn dangling_references(&self) -> Vec<ResolvedReference> {
+        self.references()
+            .into_iter()
+            .filter(|resolved| resolved.target.is_dangling())
+            .collect()
+    }
+
+    fn reference_resolver(&self) -> ReferenceResolver<'_> {
+        ReferenceResolver::new(
+            self.pages
+                .iter()
+                .map(|page| page.structs.iter().map(|nucc_struct| nucc_struct.struct_info()).collect())
+                .collect(),
+        )
+    }
+
+    fn reference_location(&self, page_index: usize, reference: &NuccStructReference) -> Option<(usize, usize)> {
+        match self.resolve_reference(page_index, reference) {
+            ReferenceTarget::InPage { struct_index } => Some((page_index, struct_index)),
+            ReferenceTarget::OtherPage { page_index, struct_index } => Some((page_index, struct_index)),
+            ReferenceTarget::External { .. } | ReferenceTarget::Dangling {} => None,
+        }
     }
 }
 
-#[pyclass]
-#[derive(Debug, Clone)]
-pub struct XfbinPage {
-    #[pyo3(get, set)]
-    pub structs: Py<PyList>,
+/// Header and index values that can't be derived from the pages alone.
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XfbinLayout {
+    pub encrypted: u16,
+    pub index_version: u16,
+    pub min_page_size: u32,
 
-    #[pyo3(get, set)]
-    pub struct_infos: Py<PyList>,
+    pub chunk_types: Vec<String>,
+    pub filepaths: Vec<String>,
+    pub chunk_names: Vec<String>,
 
-    #[pyo3(get, set)]
-    pub struct_references: Py<PyList>
+    /// Every chunk map in the index, in file order.
+    pub struct_infos: Vec<NuccStructInfo>,
 }
 
+impl XfbinLayout {
+    /// Drop the chunk maps and strings that edits left unused, keeping the rest (duplicates included) in their original order
+    fn retain_used(&mut self, pages: &[XfbinPage]) {
+        let default_infos = [
+            NuccChunkNull::default_chunk_info(),
+            NuccChunkPage::default_chunk_info(),
+            NuccChunkIndex::default_chunk_info(),
+        ];
+
+        let struct_infos = pages
+            .iter()
+            .flat_map(|page| {
+                page.struct_infos
+                    .iter()
+                    .chain(page.structs.iter().map(|nucc_struct| nucc_struct.struct_info()))
+                    .chain(page.struct_references.iter().map(|struct_reference| &struct_reference.struct_info))
+            })
+            .chain(&default_infos)
+            .collect::<HashSet<_>>();
+
+        let chunk_types = struct_infos.iter().map(|struct_info| struct_info.chunk_type.as_str()).collect::<HashSet<_>>();
+        let filepaths = struct_infos.iter().map(|struct_info| struct_info.filepath.as_str()).collect::<HashSet<_>>();
+        let chunk_names = struct_infos
+            .iter()
+            .map(|struct_info| struct_info.chunk_name.as_str())
+            .chain(pages.iter().flat_map(|page| page.struct_references.iter().map(|struct_reference| struct_reference.chunk_name.as_str())))
+            .collect::<HashSet<_>>();
+
+        self.struct_infos.retain(|struct_info| struct_infos.contains(struct_info));
+        self.chunk_types.retain(|chunk_type| chunk_types.contains(chunk_type.as_str()));
+        self.filepaths.retain(|filepath| filepaths.contains(filepath.as_str()));
+        self.chunk_names.retain(|chunk_name| chunk_names.contains(chunk_name.as_str()));
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct XfbinPage {
+    pub structs: Vec<Box<dyn NuccStruct>>,
+    pub struct_infos: Vec<NuccStructInfo>,
+    pub struct_references: Vec<NuccStructReference>,
+}
 
-#[pymethods]
 impl XfbinPage {
+    pub fn has_unknown_chunk(&self) -> bool {
+        self.structs.iter().any(|nucc_struct| nucc_struct.chunk_type() == NuccChunkType::NuccChunkUnknown)
+    }
 
-    #[new]
-    #[pyo3(signature = (structs = None, struct_infos = None, struct_references = None))]
-    fn __new__(py: Python, structs: Option<Py<PyList>>, struct_infos: Option<Py<PyList>>, struct_references: Option<Py<PyList>>) -> Self {
-        XfbinPage {
-            structs: structs.unwrap_or(PyList::empty_bound(py).into()),
-            struct_infos: struct_infos.unwrap_or(PyList::empty_bound(py).into()),
-            struct_references: struct_references.unwrap_or(PyList::empty_bound(py).into())
-        }
+    pub fn has_anm_chunk(&self) -> bool {
+        self.structs.iter().any(|nucc_struct| nucc_struct.chunk_type() == NuccChunkType::NuccChunkAnm)
     }
-    
 
-    fn __repr__(&self) -> PyResult<String> {
-        Python::with_gil(|py| {
-            let struct_infos: Vec<NuccStructInfo> = self.struct_infos.extract(py)?;
-            let struct_references: Vec<NuccStructReference> = self.struct_references.extract(py)?;
-            let structs: Vec<Box<dyn NuccStruct>> = self.structs.extract(py).unwrap();
+    /// Whether the page's chunks refer to its struct infos by position, so existing entries must keep their index
+    pub fn addresses_struct_infos(&self) -> bool {
+        self.has_unknown_chunk() || self.has_anm_chunk()
+    }
 
-            Ok(format!(
-                "XfbinPage(structs={:?}, struct_infos={:?}, struct_references={:?})",
-                structs, struct_infos, struct_references
-            ))
-        })
+    /// Add a struct to the end of the page, registering its struct info if the page doesn't have it yet
+    pub fn add_struct(&mut self, nucc_struct: Box<dyn NuccStruct>) {
+        self.tables().insert(nucc_struct.struct_info());
+        self.structs.push(nucc_struct);
     }
 
-    fn __str__(&self) -> PyResult<String> {
-        self.__repr__()
+    /// Remove the struct at `index`, dropping its struct info once nothing else in the page uses it
+    pub fn remove_struct(&mut self, index: usize) -> XfbinResult<Box<dyn NuccStruct>> {
+        get_indexed("page struct", &self.structs, index)?;
+
+        let nucc_struct = self.structs.remove(index);
+        let in_use = self.uses_struct_info(nucc_struct.struct_info());
+        self.tables().release(nucc_struct.struct_info(), in_use);
+
+        Ok(nucc_struct)
     }
 
-    pub fn has_unknown_chunk(&self) -> bool {
-        let structs: Vec<Box<dyn NuccStruct>> = Python::with_gil(|py| {
-            self.structs.extract(py).unwrap()
-        });
+    /// Put `nucc_struct` in place of the struct at `index`, returning the old one.
+    /// If the old struct info isn't shared, the new one takes over its slot and the references pointing at it.
+    pub fn replace_struct(&mut self, index: usize, nucc_struct: Box<dyn NuccStruct>) -> XfbinResult<Box<dyn NuccStruct>> {
+        get_indexed("page struct", &self.structs, index)?;
+
+        let new_struct_info = nucc_struct.struct_info().clone();
+        let old_struct = std::mem::replace(&mut self.structs[index], nucc_struct);
 
-        structs.iter().any(|nucc_struct| nucc_struct.chunk_type() == NuccChunkType::NuccChunkUnknown)
+        let in_use = self.uses_struct_info(old_struct.struct_info());
+        self.tables().replace(old_struct.struct_info(), &new_struct_info, in_use);
+
+        Ok(old_struct)
     }
 
-    pub fn has_anm_chunk(&self) -> bool {
-        let structs: Vec<Box<dyn NuccStruct>> = Python::with_gil(|py| {
-            self.structs.extract(py).unwrap()
-        });
+    /// Move the struct at `from` so it ends up at `to`; only the chunk order changes
+    pub fn move_struct(&mut self, from: usize, to: usize) -> XfbinResult<()> {
+        get_indexed("page struct", &self.structs, from)?;
+        get_indexed("page struct", &self.structs, to)?;
+
+        let nucc_struct = self.structs.remove(from);
+        self.structs.insert(to, nucc_struct);
 
-        structs.iter().any(|nucc_struct| nucc_struct.chunk_type() == NuccChunkType::NuccChunkAnm)
+        Ok(())
     }
 
+    /// Add a reference unless the page already has it, registering the struct info it points at
+    pub fn add_reference(&mut self, reference: NuccStructReference) {
+        if self.struct_references.contains(&reference) {
+            return;
+        }
+
+        self.tables().insert(&reference.struct_info);
+        self.struct_references.push(reference);
+    }
 
+    fn uses_struct_info(&self, struct_info: &NuccStructInfo) -> bool {
+        self.structs.iter().any(|nucc_struct| nucc_struct.struct_info() == struct_info)
+    }
+
+    pub(crate) fn tables(&mut self) -> PageTables<'_> {
+        PageTables {
+            addressed_by_index: self.addresses_struct_infos(),
+            struct_infos: &mut self.struct_infos,
+            struct_references: &mut self.struct_references,
+        }
+    }
+
+    /// Split the page into its structs and the struct info / reference maps used to repack them.
+    /// The maps are seeded from the page's own tables, exactly as they are when `preserve_order` is set
+    /// or when its chunks refer to other structs by index, and otherwise behind a leading null entry.
     #[allow(clippy::type_complexity)]
-    pub fn destructure(&self) -> (
+    pub fn destructure(self, preserve_order: bool) -> (
         Vec<Box<dyn NuccStruct>>,
-        IndexMap<NuccStructInfo, u32>,
-        IndexMap<NuccStructReference, u32>,
+        IndexTable<NuccStructInfo>,
+        IndexTable<NuccStructReference>,
     ) {
+        let struct_infos = if preserve_order || self.addresses_struct_infos() {
+            IndexTable::from_entries(self.struct_infos)
+        } else {
+            // The loader expects the null chunk's map first in every page
+            let mut struct_infos = IndexTable::default();
+
+            for struct_info in std::iter::once(NuccChunkNull::default_chunk_info()).chain(self.struct_infos) {
+                struct_infos.index_of(&struct_info);
+            }
 
-        let structs: Vec<Box<dyn NuccStruct>> = Python::with_gil(|py| {
-            self.structs.extract(py).unwrap()
-        });
+            struct_infos
+        };
 
-        let mut struct_infos = IndexMap::<NuccStructInfo, u32>::new();
-        let mut struct_references = IndexMap::<NuccStructReference, u32>::new();
+        (self.structs, struct_infos, IndexTable::from_entries(self.struct_references))
+    }
+}
 
-        let struct_infos_vec: Vec<NuccStructInfo> = Python::with_gil(|py| {
-            self.struct_infos.extract(py).unwrap()
-        });
+/// A page's struct info and reference tables, as the edit operations keep them in step with its structs.
+pub(crate) struct PageTables<'a> {
+    pub struct_infos: &'a mut Vec<NuccStructInfo>,
+    pub struct_references: &'a mut Vec<NuccStructReference>,
+    pub addressed_by_index: bool,
+}
 
-        let struct_references_vec: Vec<NuccStructReference> = Python::with_gil(|py| {
-            self.struct_references.extract(py).unwrap()
-        });
+impl PageTables<'_> {
+    /// Register `struct_info` if it's missing, ahead of the page and index chunk entries the writer keeps last
+    pub(crate) fn insert(&mut self, struct_info: &NuccStructInfo) {
+        if self.struct_infos.contains(struct_info) {
+            return;
+        }
 
-        if self.has_unknown_chunk() || self.has_anm_chunk() {
-            struct_infos.extend(struct_infos_vec.iter().enumerate().map(|(i, s)| (s.clone(), i as u32)));
-            struct_references.extend(struct_references_vec.iter().enumerate().map(|(i, s)| ((*s).clone(), i as u32)));
+        if self.struct_infos.is_empty() {
+            self.struct_infos.push(NuccChunkNull::default_chunk_info());
         }
 
-        (structs, struct_infos, struct_references)
+        let position = self
+            .struct_infos
+            .iter()
+            .position(|struct_info| {
+                let chunk_type = NuccChunkType::from_str(&struct_info.chunk_type).unwrap_or_default();
+                chunk_type == NuccChunkType::NuccChunkPage || chunk_type == NuccChunkType::NuccChunkIndex
+            })
+            .unwrap_or(self.struct_infos.len());
+
+        self.struct_infos.insert(position, struct_info.clone());
     }
 
+    /// Drop `struct_info` after its struct was removed, unless a struct or reference still uses it
+    /// or removing it would shift the indices the page's chunks refer to
+    pub(crate) fn release(&mut self, struct_info: &NuccStructInfo, in_use: bool) {
+        let referenced = self
+            .struct_references
+            .iter()
+            .any(|reference| &reference.struct_info == struct_info);
+
+        if in_use || referenced || self.addressed_by_index {
+            return;
+        }
+
+        self.struct_infos.retain(|existing| existing != struct_info);
+    }
+
+    /// Swap `old` for `new` after a struct was replaced
+    pub(crate) fn replace(&mut self, old: &NuccStructInfo, new: &NuccStructInfo, in_use: bool) {
+        if old == new {
+            return;
+        }
+
+        let position = self.struct_infos.iter().position(|existing| existing == old);
+
+        match position {
+            Some(position) if !in_use && !self.struct_infos.contains(new) => {
+                // Take over the old slot so anything addressing it by index or by reference follows the replacement
+                self.struct_infos[position] = new.clone();
+
+                for reference in self.struct_references.iter_mut().filter(|reference| &reference.struct_info == old) {
+                    reference.struct_info = new.clone();
+                }
+            }
+            _ => {
+                self.insert(new);
+                self.release(old, in_use);
+            }
+        }
+    }
 }
 
-    
-impl From<XfbinFile> for Xfbin {
-    fn from(xfbin: XfbinFile) -> Self {
-        let mut pages = Vec::new();
 
-        // Create a new XfbinPage PyObj
-        let mut page = Python::with_gil(
-            |py| XfbinPage::__new__(py, None, None, None)
-        );
+/// The index tables of a file, with the chunk maps and references resolved to struct infos.
+pub(crate) struct XfbinIndexTables {
+    pub chunk_types: Vec<String>,
+    pub filepaths: Vec<String>,
+    pub chunk_names: Vec<String>,
+
+    pub struct_infos: Vec<NuccStructInfo>,
+    pub struct_references: Vec<NuccStructReference>,
+
+    /// Struct infos in chunk map index order, which is what chunks and pages index into.
+    pub struct_infos_mapped: Vec<NuccStructInfo>,
+}
 
-        let chunk_names = xfbin
-            .index
+impl XfbinIndexTables {
+    pub(crate) fn new(index: &XfbinIndex) -> XfbinResult<Self> {
+        let chunk_names = index
             .chunk_names
             .iter()
             .map(|s| s.to_string())
             .collect::<Vec<String>>();
 
-        let chunk_types = xfbin
-            .index
+        let chunk_types = index
             .chunk_types
             .iter()
             .map(|s| s.to_string())
             .collect::<Vec<String>>();
 
-        let filepaths = xfbin
-            .index
+        let filepaths = index
             .filepaths
             .iter()
             .map(|s| s.to_string())
             .collect::<Vec<String>>();
 
-        let mut struct_infos_index: usize = 0;
-        let mut struct_references_index: usize = 0;
-
-        let struct_infos = Vec::<NuccStructInfo>::from(XfbinChunkMapConverter {
-            chunk_maps: xfbin.index.chunk_maps.clone(),
+        let struct_infos = Vec::<NuccStructInfo>::try_from(XfbinChunkMapConverter {
+            chunk_maps: index.chunk_maps.clone(),
             chunk_names: chunk_names.clone(),
             chunk_types: chunk_types.clone(),
             filepaths: filepaths.clone(),
-        });
+        })?;
 
-        let struct_references = Vec::<NuccStructReference>::from(XfbinChunkReferenceConverter {
-            references: xfbin.index.chunk_references,
-            chunk_names,
+        let struct_references = Vec::<NuccStructReference>::try_from(XfbinChunkReferenceConverter {
+            references: index.chunk_references.clone(),
+            chunk_names: chunk_names.clone(),
             struct_infos: struct_infos.clone(),
-        });
+        })?;
 
-        let struct_infos_mapped = xfbin
-            .index
+        let struct_infos_mapped = index
             .chunk_map_indices
             .iter()
-            .map(|&i| struct_infos[i as usize].clone())
-            .collect::<Vec<NuccStructInfo>>();
+            .map(|&i| get_indexed("chunk map", &struct_infos, i as usize).cloned())
+            .collect::<XfbinResult<Vec<NuccStructInfo>>>()?;
+
+        Ok(Self {
+            chunk_types,
+            filepaths,
+            chunk_names,
+            struct_infos,
+            struct_references,
+            struct_infos_mapped,
+        })
+    }
+
+    /// An empty page holding the struct infos and references in the given ranges.
+    pub(crate) fn page(&self, struct_infos: Range<usize>, struct_references: Range<usize>) -> XfbinResult<XfbinPage> {
+        let struct_infos = self
+            .struct_infos_mapped
+            .get(struct_infos.clone())
+            .ok_or_else(|| XfbinError::index_out_of_range(
+                "chunk map index",
+                struct_infos.end,
+                self.struct_infos_mapped.len(),
+            ))?
+            .to_vec();
+
+        let struct_references = self
+            .struct_references
+            .get(struct_references.clone())
+            .ok_or_else(|| XfbinError::index_out_of_range(
+                "chunk reference",
+                struct_references.end,
+                self.struct_references.len(),
+            ))?
+            .to_vec();
+
+        Ok(XfbinPage {
+            structs: Vec::new(),
+            struct_infos,
+            struct_references,
+        })
+    }
+
+    /// The page of the structs after the last page chunk, over the struct infos and references no page claimed
+    pub(crate) fn trailing_page(&self, struct_infos_start: usize, struct_references_start: usize) -> XfbinResult<XfbinPage> {
+        self.page(
+            struct_infos_start..self.struct_infos_mapped.len(),
+            struct_references_start..self.struct_references.len(),
+        )
+    }
+
+    /// Convert a decoded chunk into its struct, tagging it with its struct info.
+    pub(crate) fn to_struct(
+        &self,
+        nucc_chunk: Box<dyn NuccChunk>,
+        chunk_index: usize,
+        offset: u64,
+        struct_info: NuccStructInfo,
+    ) -> XfbinResult<Box<dyn NuccStruct>> {
+        let mut parsed_struct = Box::<dyn NuccStruct>::try_from(NuccStructConverter {
+            nucc_chunk,
+            struct_infos: self.struct_infos_mapped.clone(),
+            struct_references: self.struct_references.clone(),
+        })
+        .map_err(|e| XfbinError::chunk_decode(chunk_index, &struct_info, offset, e))?;
+
+        *parsed_struct.struct_info_mut() = struct_info;
 
-        for chunk in xfbin.chunks {
-            let NuccStructInfo {
-                chunk_name,
-                chunk_type,
-                filepath,
-            } = &struct_infos_mapped[struct_infos_index + chunk.chunk_map_index as usize].clone();
+        Ok(parsed_struct)
+    }
+}
 
-            let parsed = chunk.unpack(chunk_type);
+impl TryFrom<XfbinFile> for Xfbin {
+    type Error = XfbinError;
 
-            match parsed.chunk_type() {
+    fn try_from(xfbin: XfbinFile) -> XfbinResult<Self> {
+        let mut pages = Vec::new();
+        let mut pending = Vec::new();
+
+        let mut struct_infos_index: usize = 0;
+        let mut struct_references_index: usize = 0;
+
+        let tables = XfbinIndexTables::new(&xfbin.index)?;
+        let struct_infos_mapped = &tables.struct_infos_mapped;
+
+        // Page chunks decide which struct infos each chunk indexes into, so walk them in order first
+        for (chunk_index, chunk) in xfbin.chunks.into_iter().enumerate() {
+            let struct_info = get_indexed(
+                "chunk map index",
+                struct_infos_mapped,
+                struct_infos_index + chunk.chunk_map_index as usize,
+            )?.clone();
+
+            match NuccChunkType::from_str(&struct_info.chunk_type).unwrap_or_default() {
                 NuccChunkType::NuccChunkNull => continue,
                 NuccChunkType::NuccChunkPage => {
+                    let offset = chunk.offset;
+                    let parsed = chunk.unpack(chunk_index, &struct_info)?;
+
                     let NuccChunkPage {
                         version: _,
                         map_index_count: struct_infos_count,
                         reference_count: struct_references_count,
-                    } = parsed.downcast::<NuccChunkPage>().map(|c| *c).ok().unwrap();
+                    } = downcast_chunk::<NuccChunkPage>(parsed, NuccChunkType::NuccChunkPage)
+                        .map_err(|e| XfbinError::chunk_decode(chunk_index, &struct_info, offset, e))?;
 
                     let struct_infos_count = struct_infos_count as usize;
                     let struct_references_count = struct_references_count as usize;
 
-                    // Convert Vec<NuccStructInfo> to Py<PyList>
-                    let struct_infos = Python::with_gil(|py| {
-                        PyList::new_bound(py, struct_infos_mapped[struct_infos_index..(struct_infos_index + struct_infos_count)]
-                            .iter()
-                            .map(|struct_info| Py::new(py, struct_info.clone()).unwrap())
-                        ).into()
-                    });
-
-                    page.struct_infos = struct_infos;
-
-                    // Convert Vec<NuccStructReference> to Py<PyList>
-                    let struct_references: Py<PyList> = Python::with_gil(|py| {
-                        PyList::new_bound(py, struct_references[struct_references_index
-                            ..(struct_references_index + struct_references_count)]
-                            .iter()
-                            .map(|struct_reference| Py::new(py, struct_reference.clone()).unwrap())
-                        ).into()
-                    });
-
-                    page.struct_references = struct_references;
-
-                    pages.push(page);
-                    
-                    page = Python::with_gil(
-                        |py| XfbinPage::__new__(py, None, None, None)
-                    );
+                    pages.push(tables.page(
+                        struct_infos_index..(struct_infos_index + struct_infos_count),
+                        struct_references_index..(struct_references_index + struct_references_count),
+                    )?);
 
                     struct_infos_index += struct_infos_count;
                     struct_references_index += struct_references_count;
-
-                    continue;
                 }
-                _ => (),
+                _ => pending.push((pages.len(), chunk_index, chunk, struct_info)),
             }
+        }
 
-            let mut parsed_struct = Box::<dyn NuccStruct>::from(NuccStructConverter {
-                nucc_chunk: parsed,
-                struct_infos: struct_infos_mapped.clone(),
-                struct_references: struct_references.clone(),
-            });
-            
-            
-        
-            let struct_info = parsed_struct.struct_info_mut();
-            struct_info.chunk_name = chunk_name.clone();
-            struct_info.filepath = filepath.clone();
-            struct_info.chunk_type = chunk_type.clone();
-
+        // Every other chunk is independent of the rest, so they can be decoded in any order
+        let decode = |(page_index, chunk_index, chunk, struct_info): (usize, usize, XfbinChunk, NuccStructInfo)| {
+            let offset = chunk.offset;
+            let parsed = chunk.unpack(chunk_index, &struct_info)?;
 
-            let structs: Py<PyList> = Python::with_gil(|py| {
-                let mut structs: Vec<PyObject> = page.structs.extract(py).unwrap();
+            Ok((page_index, tables.to_struct(parsed, chunk_index, offset, struct_info)?))
+        };
 
-                let py_struct: Py<PyAny> = Python::with_gil(|py| {
-                    let py_struct = parsed_struct.into_py(py);
-                    
-                    py_struct 
-                });
-                
-                structs.push(py_struct.into());
-                PyList::new_bound(py, structs).into()
-            });
+        #[cfg(feature = "parallel")]
+        let decoded = pending.into_par_iter().map(decode).collect::<XfbinResult<Vec<_>>>()?;
 
+        #[cfg(not(feature = "parallel"))]
+        let decoded = pending.into_iter().map(decode).collect::<XfbinResult<Vec<_>>>()?;
 
-            page.structs = structs;
+        // Structs after the last page chunk are kept in a page of their own, which gets a page chunk when written
+        if decoded.iter().any(|(page_index, _)| *page_index == pages.len()) {
+            pages.push(tables.trailing_page(struct_infos_index, struct_references_index)?);
+        }
 
+        for (page_index, nucc_struct) in decoded {
+            pages[page_index].structs.push(nucc_struct);
         }
 
-        let pages = Python::with_gil(|py| {
-            PyList::new_bound(py, pages.iter().map(|page| {
-                Py::new(py, page.clone()).unwrap()
-            })).into()
-        });
+        let layout = XfbinLayout {
+            encrypted: xfbin.header.encrypted,
+            index_version: xfbin.index.version,
+            min_page_size: xfbin.index.min_page_size,
+            chunk_types: tables.chunk_types,
+            filepaths: tables.filepaths,
+            chunk_names: tables.chunk_names,
+            struct_infos: tables.struct_infos,
+        };
 
-        Self {
+        Ok(Self {
             version: xfbin.header.version as u16,
             pages,
+            layout: Some(layout),
+        })
+    }
+}
+
+/// An index table in the order it's written in, duplicates included, so the entries after a duplicate keep their
+/// index. Entries are looked up by the index they first appear at.
+#[derive(Debug, Clone)]
+pub struct IndexTable<T> {
+    entries: Vec<T>,
+    indices: IndexMap<T, u32>,
+}
+
+impl<T> Default for IndexTable<T> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            indices: IndexMap::new(),
         }
     }
 }
 
+impl<T: Clone + Eq + std::hash::Hash> IndexTable<T> {
+    pub fn from_entries(entries: Vec<T>) -> Self {
+        let mut indices = IndexMap::new();
+
+        for (i, entry) in entries.iter().enumerate() {
+            indices.entry(entry.clone()).or_insert(i as u32);
+        }
+
+        Self { entries, indices }
+    }
+
+    /// The first index of `entry`, which is appended if the table doesn't have it yet
+    pub fn index_of(&mut self, entry: &T) -> u32 {
+        if let Some(&index) = self.indices.get(entry) {
+            return index;
+        }
+
+        let index = self.entries.len() as u32;
+        self.entries.push(entry.clone());
+        self.indices.insert(entry.clone(), index);
+
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[T] {
+        &self.entries
+    }
+
+    /// The first index of every entry, as chunks look them up
+    pub fn indices(&self) -> &IndexMap<T, u32> {
+        &self.indices
+    }
+
+    pub fn into_entries(self) -> Vec<T> {
+        self.entries
+    }
+}
+
+fn pack_chunk(
+    boxed: Box<dyn NuccChunk>,
+    struct_info: &NuccStructInfo,
+    version: u16,
+    chunk_map_index: u32,
+) -> XfbinResult<XfbinChunk> {
+    let mut chunk = XfbinChunk::repack(boxed, version)
+        .map_err(|e| XfbinError::chunk_encode(struct_info, e))?;
+    chunk.chunk_map_index = chunk_map_index;
+
+    Ok(chunk)
+}
+
 fn repack_struct(
     boxed: Box<dyn NuccChunk>,
     struct_info: NuccStructInfo,
-    page_struct_infos: &mut IndexMap<NuccStructInfo, u32>,
-) -> XfbinChunk {
-    let struct_info_index = page_struct_infos.len() as u32;
+    version: u16,
+    page_struct_infos: &mut IndexTable<NuccStructInfo>,
+) -> XfbinResult<XfbinChunk> {
+    let chunk_map_index = page_struct_infos.index_of(&struct_info);
 
-    let chunk_map_index = *page_struct_infos
-        .entry(struct_info)
-        .or_insert(struct_info_index);
+    pack_chunk(boxed, &struct_info, version, chunk_map_index)
+}
 
-    let mut chunk = XfbinChunk::repack(boxed);
-    chunk.chunk_map_index = chunk_map_index;
+impl TryFrom<Xfbin> for XfbinFile {
+    type Error = XfbinError;
 
-    chunk
-}
+    fn try_from(xfbin: Xfbin) -> XfbinResult<Self> {
+        let lossless = xfbin.layout.is_some();
+        let mut layout = xfbin.layout.unwrap_or_default();
 
-impl From<Xfbin> for XfbinFile {
-    fn from(xfbin: Xfbin) -> Self {
-        let mut header = XfbinHeader::default();
-        header.version = 121 as u32;
+        // Removed or renamed structs shouldn't leave their chunk maps and strings behind
+        layout.retain_used(&xfbin.pages);
 
-        let mut index = XfbinIndex::default();
-        index.version = 121 as u16;
+        let header = XfbinHeader {
+            version: xfbin.version as u32,
+            encrypted: layout.encrypted,
+        };
+
+        let mut index = XfbinIndex {
+            version: if lossless { layout.index_version } else { xfbin.version },
+            ..Default::default()
+        };
 
         let mut min_page_size = 0;
 
         let mut chunks = vec![];
 
-        let mut struct_infos_map = IndexMap::<NuccStructInfo, u32>::new();
+        // Seed the chunk maps with the original ones so their order survives the round trip
+        let mut struct_infos_map = IndexTable::from_entries(layout.struct_infos);
 
         let mut chunk_map_indices = vec![];
         let mut struct_references_vec = vec![];
@@ -339,57 +701,70 @@ impl From<Xfbin> for XfbinFile {
         let null_chunk = repack_struct(
             Box::new(NuccChunkNull(xfbin.version)),
             NuccChunkNull::default_chunk_info(),
+            xfbin.version,
             &mut struct_infos_map,
-        );
+        )?;
 
         chunks.push(null_chunk);
 
-
-        // Convert Py<PyList> to Vec<XfbinPage>
-        let pages: Vec<XfbinPage> = Python::with_gil(|py| {
-            xfbin.pages.extract(py).unwrap()
-        });
-
-        for page in pages {
-            let (page_structs, mut page_struct_infos, page_struct_references) = page.destructure();
+        for page in xfbin.pages {
+            let (page_structs, mut page_struct_infos, page_struct_references) = page.destructure(lossless);
 
             let null_chunk = repack_struct(
                 Box::new(NuccChunkNull(xfbin.version)),
                 NuccChunkNull::default_chunk_info(),
+                xfbin.version,
                 &mut page_struct_infos,
-            );
+            )?;
 
             chunks.push(null_chunk);
 
-            for nucc_struct in page_structs {
+            // Hand out chunk map indices in struct order first, so the structs can be encoded in any order
+            let pending = page_structs
+                .into_iter()
+                .map(|nucc_struct| {
+                    let chunk_map_index = page_struct_infos.index_of(nucc_struct.struct_info());
+                    let version = if lossless { nucc_struct.version() } else { xfbin.version };
+
+                    (nucc_struct, chunk_map_index, version)
+                })
+                .collect_vec();
+
+            let encode = |(nucc_struct, chunk_map_index, version): (Box<dyn NuccStruct>, u32, u16)| {
                 let struct_info = nucc_struct.struct_info().clone();
 
-                let boxed = Box::<dyn NuccChunk>::from(NuccChunkConverter {
+                let boxed = Box::<dyn NuccChunk>::try_from(NuccChunkConverter {
                     nucc_struct,
-                    struct_info_map: page_struct_infos.clone(),
-                    struct_reference_map: page_struct_references.clone(),
-                });
+                    struct_info_map: page_struct_infos.indices().clone(),
+                    struct_reference_map: page_struct_references.indices().clone(),
+                })
+                .map_err(|e| XfbinError::chunk_encode(&struct_info, e))?;
 
-                chunks.push(repack_struct(boxed, struct_info, &mut page_struct_infos));
-            }
+                pack_chunk(boxed, &struct_info, version, chunk_map_index)
+            };
+
+            #[cfg(feature = "parallel")]
+            chunks.extend(pending.into_par_iter().map(encode).collect::<XfbinResult<Vec<_>>>()?);
+
+            #[cfg(not(feature = "parallel"))]
+            chunks.extend(pending.into_iter().map(encode).collect::<XfbinResult<Vec<_>>>()?);
 
             // Add nuccChunkPage map
             repack_struct(
                 Box::new(NuccChunkPage::default()),
                 NuccChunkPage::default_chunk_info(),
+                xfbin.version,
                 &mut page_struct_infos,
-            );
+            )?;
 
             // Add nuccChunkIndex map
             repack_struct(
                 Box::new(NuccChunkIndex),
                 NuccChunkIndex::default_chunk_info(),
+                xfbin.version,
                 &mut page_struct_infos,
-            );
-
-         
+            )?;
 
-          
             // Create final nuccChunkPage
             let page_chunk = repack_struct(
                 Box::new(NuccChunkPage {
@@ -398,115 +773,59 @@ impl From<Xfbin> for XfbinFile {
                     reference_count: page_struct_references.len() as u32,
                 }),
                 NuccChunkPage::default_chunk_info(),
+                xfbin.version,
                 &mut page_struct_infos,
-            );
+            )?;
 
             chunks.push(page_chunk);
 
+            for struct_info in page_struct_infos.entries() {
+                chunk_map_indices.push(struct_infos_map.index_of(struct_info));
+            }
 
-            for struct_info in page_struct_infos
-            .clone()
-            .into_iter()
-            .sorted_by_key(|(_, v)| *v)
-            .map(|(k, _)| k)
-        {
-            let struct_info_index = struct_infos_map.len() as u32;
-            chunk_map_indices.push(
-                *struct_infos_map
-                    .entry(struct_info)
-                    .or_insert(struct_info_index),
-            );
-
-        }
-
-            struct_references_vec.extend(
-                page_struct_references
-                    .into_iter()
-                    .sorted_by_key(|(_, v)| *v)
-                    .map(|(k, _)| k),
-            );
+            struct_references_vec.extend(page_struct_references.into_entries());
 
             // Get the smallest ie the minimum page_struct_infos length for structs that are not Null or Page
             if min_page_size == 0 || page_struct_infos.len() > min_page_size {
-                min_page_size = page_struct_infos.len() - 3;
+                min_page_size = page_struct_infos.len().saturating_sub(3);
             }
-            
-
         }
 
-        let mut chunk_type_map = IndexMap::new();
-        let mut file_path_map = IndexMap::new();
-        let mut chunk_name_map = IndexMap::new();
+        // Seed the string tables the same way, so strings keep their original indices
+        let mut chunk_type_map = IndexTable::from_entries(layout.chunk_types);
+        let mut file_path_map = IndexTable::from_entries(layout.filepaths);
+        let mut chunk_name_map = IndexTable::from_entries(layout.chunk_names);
 
+        // Referenced structs need a chunk map too, so register them before the chunk maps are written
+        let reference_chunk_map_indices = struct_references_vec
+            .iter()
+            .map(|struct_reference| struct_infos_map.index_of(&struct_reference.struct_info))
+            .collect_vec();
 
         let chunk_maps = struct_infos_map
-            .clone()
-            .into_iter()
-            .sorted_by_key(|(_, v)| *v)
-            .map(|(struct_info, _)| {
-                let mut chunk_type_index = chunk_type_map.len() as u32;
-                chunk_type_index = *chunk_type_map
-                    .entry(struct_info.chunk_type.clone())
-                    .or_insert(chunk_type_index);
-
-                let mut filepath_index = file_path_map.len() as u32;
-                filepath_index = *file_path_map
-                    .entry(struct_info.filepath.clone())
-                    .or_insert(filepath_index);
-
-                let mut chunk_name_index = chunk_name_map.len() as u32;
-                chunk_name_index = *chunk_name_map
-                    .entry(struct_info.chunk_name.clone())
-                    .or_insert(chunk_name_index);
-
-                XfbinChunkMap {
-                    chunk_type_index,
-                    filepath_index,
-                    chunk_name_index,
-                }
+            .entries()
+            .iter()
+            .map(|struct_info| XfbinChunkMap {
+                chunk_type_index: chunk_type_map.index_of(&struct_info.chunk_type),
+                filepath_index: file_path_map.index_of(&struct_info.filepath),
+                chunk_name_index: chunk_name_map.index_of(&struct_info.chunk_name),
             })
             .collect::<Vec<XfbinChunkMap>>();
 
         let chunk_references = struct_references_vec
             .iter()
-            .map(|struct_reference| {
-                let mut chunk_name_index = chunk_name_map.len() as u32;
-                chunk_name_index = *chunk_name_map
-                    .entry(struct_reference.chunk_name.clone())
-                    .or_insert(chunk_name_index);
-
-                let mut chunk_map_index = struct_infos_map.len() as u32;
-                chunk_map_index = *struct_infos_map
-                    .entry(struct_reference.struct_info.clone())
-                    .or_insert(chunk_map_index);
-
-                XfbinChunkReference {
-                    chunk_name_index,
-                    chunk_map_index,
-                }
+            .zip(reference_chunk_map_indices)
+            .map(|(struct_reference, chunk_map_index)| XfbinChunkReference {
+                chunk_name_index: chunk_name_map.index_of(&struct_reference.chunk_name),
+                chunk_map_index,
             })
             .collect::<Vec<XfbinChunkReference>>();
 
-        let chunk_types = chunk_type_map
-            .into_iter()
-            .sorted_by_key(|(_, v)| *v)
-            .map(|(k, _)| NullString::from(k))
-            .collect_vec();
-
-        let filepaths = file_path_map
-            .into_iter()
-            .sorted_by_key(|(_, v)| *v)
-            .map(|(k, _)| NullString::from(k))
-            .collect_vec();
-
-        let chunk_names = chunk_name_map
-            .into_iter()
-            .sorted_by_key(|(_, v)| *v)
-            .map(|(k, _)| NullString::from(k))
-            .collect_vec();
-        
+        let chunk_types = chunk_type_map.into_entries().into_iter().map(NullString::from).collect_vec();
+        let filepaths = file_path_map.into_entries().into_iter().map(NullString::from).collect_vec();
+        let chunk_names = chunk_name_map.into_entries().into_iter().map(NullString::from).collect_vec();
 
-        index.min_page_size = min_page_size as u32;
+        index.min_page_size = if lossless { layout.min_page_size } else { min_page_size as u32 };
         index.chunk_types = chunk_types;
         index.filepaths = filepaths;
         index.chunk_names = chunk_names;
@@ -516,12 +835,10 @@ impl From<Xfbin> for XfbinFile {
         index.chunk_references = chunk_references;
         index.chunk_map_indices = chunk_map_indices;
 
-        let xfbin_file = Self {
+        Ok(Self {
             header,
             index,
             chunks,
-        };
-
-        xfbin_file
+        })
     }
 }
\ No newline at end of file
@@ -1,11 +1,16 @@
-use binrw::{binrw, until_eof, NullString};
+use binrw::{binrw, io::Cursor, until_eof, BinReaderExt, BinResult, NullString, ReadOptions};
+use bytes::Bytes;
+#[cfg(feature = "python")]
 use pyo3::pyclass;
+use std::io::{self, Read, Seek};
 use std::mem::size_of;
 
+use super::error::{XfbinError, XfbinResult};
+use super::nucc::NuccStructInfo;
 use super::nucc_chunk::{NuccChunk, NuccChunkType};
 
-#[pyclass]
 #[binrw]
+#[cfg_attr(feature = "python", pyclass)]
 #[derive(Debug, Clone, Default)]
 pub struct XfbinFile {
     pub header: XfbinHeader,
@@ -16,6 +21,38 @@ pub struct XfbinFile {
 }
 
 
+impl XfbinFile {
+    /// Parse a whole file held in memory. Chunk data is sliced out of `buf` rather than copied.
+    pub fn from_bytes(buf: Bytes) -> XfbinResult<Self> {
+        let mut reader = Cursor::new(&buf[..]);
+
+        let header = reader.read_be::<XfbinHeader>()?;
+        let index = reader.read_be::<XfbinIndex>()?;
+
+        let mut chunks = Vec::new();
+        while (reader.position() as usize) < buf.len() {
+            let chunk = reader.read_be::<XfbinChunkHeader>()?;
+
+            let start = reader.position() as usize;
+            let end = start + chunk.size as usize;
+            if end > buf.len() {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+
+            reader.set_position(end as u64);
+
+            chunks.push(XfbinChunk {
+                offset: chunk.offset,
+                chunk_map_index: chunk.chunk_map_index,
+                version: chunk.version,
+                data: buf.slice(start..end),
+            });
+        }
+
+        Ok(Self { header, index, chunks })
+    }
+}
+
 #[binrw]
 #[brw(magic = b"NUCC")]
 #[derive(Debug, Clone, Default)]
@@ -95,8 +132,9 @@ impl XfbinIndex {
 
         let string_sizes = chunk_types_size + filepaths_size + chunk_names_size;
 
-        0x28 + string_sizes + (4 - (string_sizes % 4)) // Add the header size, size of the strings buffer, and the aligned size of the strings
+        0x28 + string_sizes + ((4 - (string_sizes % 4)) % 4) // Add the header size, size of the strings buffer, and the padding that aligns it
         + (self.chunk_maps.len() as u32 * size_of::<XfbinChunkMap>() as u32)
+        + (self.chunk_references.len() as u32 * size_of::<XfbinChunkReference>() as u32)
         + (self.chunk_map_indices.len() as u32 * size_of::<u32>() as u32)
     }
 }
@@ -119,6 +157,11 @@ pub struct XfbinChunkMap {
 #[binrw]
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct XfbinChunk {
+    /// Byte offset of the chunk header in the file, used for error reporting
+    #[br(parse_with = stream_position)]
+    #[bw(ignore)]
+    pub offset: u64,
+
     #[bw(calc = data.len() as u32)]
     pub size: u32,
 
@@ -127,22 +170,49 @@ pub struct XfbinChunk {
     #[brw(pad_after = 2)]
     pub version: u16,
 
-    #[br(count = size)]
-    pub data: Vec<u8>,
+    #[br(count = size, map = |data: Vec<u8>| data.into())]
+    #[bw(map = |data: &Bytes| data.to_vec())]
+    pub data: Bytes,
+}
+
+/// The fixed-size header in front of each chunk's data, for scanning a file without reading the data.
+#[binrw]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct XfbinChunkHeader {
+    #[br(parse_with = stream_position)]
+    #[bw(ignore)]
+    pub offset: u64,
+
+    pub size: u32,
+
+    pub chunk_map_index: u32,
+
+    #[brw(pad_after = 2)]
+    pub version: u16,
+}
+
+impl XfbinChunkHeader {
+    /// Size of the header in bytes; the chunk's data starts right after it
+    pub const SIZE: u64 = 12;
+}
+
+fn stream_position<R: Read + Seek>(reader: &mut R, _: &ReadOptions, _: ()) -> BinResult<u64> {
+    Ok(reader.stream_position()?)
 }
 
 impl XfbinChunk {
-    pub fn unpack(self, chunk_type: &str) -> Box<dyn NuccChunk> {
-        NuccChunkType::read_data(self.data, chunk_type, self.version)
-            .map(|(_, chunk)| chunk)
-            .unwrap()
-    }
+    pub fn unpack(self, chunk_index: usize, struct_info: &NuccStructInfo) -> XfbinResult<Box<dyn NuccChunk>> {
+        let offset = self.offset;
 
-    pub fn repack(boxed: Box<dyn NuccChunk>) -> Self {
-        let mut result = Self::default();
-        result.version = 121;
-        result.data = NuccChunkType::write_data(boxed, result.version).unwrap();
+        NuccChunkType::read_data(self.data, &struct_info.chunk_type, self.version)
+            .map_err(|e| XfbinError::chunk_decode(chunk_index, struct_info, offset, e))
+    }
 
-        result
+    pub fn repack(boxed: Box<dyn NuccChunk>, version: u16) -> XfbinResult<Self> {
+        Ok(Self {
+            version,
+            data: NuccChunkType::write_data(boxed)?.into(),
+            ..Default::default()
+        })
     }
 }
@@ -0,0 +1,299 @@
+//! # Lazy reader
+//! Parses only the header and index of an xfbin up front, then scans the chunk headers to record where each chunk lives.
+//! Chunks are decoded one at a time, when a caller asks for them by name, type or filepath.
+//! Reading from a shared buffer (such as a memory-mapped file) keeps raw payloads as views into it instead of copying them.
+//!
+use binrw::BinReaderExt;
+use bytes::Bytes;
+#[cfg(feature = "python")]
+use pyo3::pyclass;
+use std::fs::File;
+use std::io::{self, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::str::FromStr;
+
+use super::error::{get_indexed, XfbinError, XfbinResult};
+use super::nucc::{downcast_chunk, NuccStruct, NuccStructInfo};
+use super::nucc_chunk::{NuccChunkPage, NuccChunkType};
+use super::transform;
+use super::xfbin::{Xfbin, XfbinIndexTables, XfbinLayout, XfbinPage};
+use super::xfbin_file::{XfbinChunk, XfbinChunkHeader, XfbinHeader, XfbinIndex};
+
+/// Something an xfbin can be read from.
+pub trait XfbinSource: Read + Seek {
+    /// Read `size` bytes starting at `offset`.
+    /// Sources that already hold the file in memory can hand out a view instead of copying.
+    fn read_bytes_at(&mut self, offset: u64, size: usize) -> io::Result<Bytes> {
+        self.seek(SeekFrom::Start(offset))?;
+
+        let mut data = vec![0; size];
+        self.read_exact(&mut data)?;
+
+        Ok(data.into())
+    }
+}
+
+impl XfbinSource for File {}
+impl<R: Read + Seek> XfbinSource for BufReader<R> {}
+impl XfbinSource for Cursor<Vec<u8>> {}
+impl XfbinSource for Cursor<&[u8]> {}
+
+impl XfbinSource for Cursor<Bytes> {
+    fn read_bytes_at(&mut self, offset: u64, size: usize) -> io::Result<Bytes> {
+        let start = offset as usize;
+        let buffer = self.get_ref();
+
+        if start + size > buffer.len() {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        Ok(buffer.slice(start..start + size))
+    }
+}
+
+/// Where a chunk lives in the file, recorded without decoding it.
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone)]
+pub struct XfbinChunkEntry {
+    /// Position of the chunk among all chunks in the file, including null and page chunks
+    pub chunk_index: usize,
+    pub page_index: usize,
+    pub struct_info: NuccStructInfo,
+    pub version: u16,
+
+    /// Byte offset of the chunk header in the file
+    pub offset: u64,
+    pub size: u32,
+}
+
+impl XfbinChunkEntry {
+    pub fn chunk_type(&self) -> NuccChunkType {
+        NuccChunkType::from_str(&self.struct_info.chunk_type).unwrap_or_default()
+    }
+}
+
+pub struct XfbinReader<R> {
+    reader: R,
+    header: XfbinHeader,
+    index_version: u16,
+    min_page_size: u32,
+    tables: XfbinIndexTables,
+    entries: Vec<XfbinChunkEntry>,
+
+    /// Pages with their struct infos and references, but no structs
+    pages: Vec<XfbinPage>,
+
+    /// Where the struct infos and references of the last page end
+    struct_infos_end: usize,
+    struct_references_end: usize,
+}
+
+impl<R: XfbinSource> XfbinReader<R> {
+    /// Read from a plain file. Encrypted files can't be decoded piecemeal, so they have to go through `from_bytes`.
+    pub fn new(mut reader: R) -> XfbinResult<Self> {
+        let header = reader.read_be::<XfbinHeader>()?;
+        if header.encrypted != 0 {
+            return Err(XfbinError::EncryptedStream { flag: header.encrypted });
+        }
+
+        let index = reader.read_be::<XfbinIndex>()?;
+        let tables = XfbinIndexTables::new(&index)?;
+
+        let chunks_start = reader.stream_position()?;
+        let end = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(chunks_start))?;
+
+        let mut entries = Vec::new();
+        let mut pages = Vec::new();
+        let mut struct_infos_index: usize = 0;
+        let mut struct_references_index: usize = 0;
+        let mut chunk_index = 0;
+
+        while reader.stream_position()? < end {
+            let chunk = reader.read_be::<XfbinChunkHeader>()?;
+
+            let struct_info = get_indexed(
+                "chunk map index",
+                &tables.struct_infos_mapped,
+                struct_infos_index + chunk.chunk_map_index as usize,
+            )?.clone();
+
+            match NuccChunkType::from_str(&struct_info.chunk_type).unwrap_or_default() {
+                NuccChunkType::NuccChunkNull => {
+                    reader.seek(SeekFrom::Current(chunk.size as i64))?;
+                }
+                NuccChunkType::NuccChunkPage => {
+                    // Page chunks are tiny, and they have to be decoded to know where the next page's struct infos start
+                    let mut data = vec![0; chunk.size as usize];
+                    reader.read_exact(&mut data)?;
+
+                    let offset = chunk.offset;
+                    let parsed = XfbinChunk {
+                        offset,
+                        chunk_map_index: chunk.chunk_map_index,
+                        version: chunk.version,
+                        data: data.into(),
+                    }
+                    .unpack(chunk_index, &struct_info)?;
+
+                    let page = downcast_chunk::<NuccChunkPage>(parsed, NuccChunkType::NuccChunkPage)
+                        .map_err(|e| XfbinError::chunk_decode(chunk_index, &struct_info, offset, e))?;
+
+                    let struct_infos_count = page.map_index_count as usize;
+                    let struct_references_count = page.reference_count as usize;
+
+                    pages.push(tables.page(
+                        struct_infos_index..(struct_infos_index + struct_infos_count),
+                        struct_references_index..(struct_references_index + struct_references_count),
+                    )?);
+
+                    struct_infos_index += struct_infos_count;
+                    struct_references_index += struct_references_count;
+                }
+                _ => {
+                    entries.push(XfbinChunkEntry {
+                        chunk_index,
+                        page_index: pages.len(),
+                        struct_info,
+                        version: chunk.version,
+                        offset: chunk.offset,
+                        size: chunk.size,
+                    });
+
+                    reader.seek(SeekFrom::Current(chunk.size as i64))?;
+                }
+            }
+
+            chunk_index += 1;
+        }
+
+        Ok(Self {
+            reader,
+            header,
+            index_version: index.version,
+            min_page_size: index.min_page_size,
+            tables,
+            entries,
+            pages,
+            struct_infos_end: struct_infos_index,
+            struct_references_end: struct_references_index,
+        })
+    }
+
+    pub fn version(&self) -> u16 {
+        self.header.version as u16
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Every chunk that decodes to a struct, in file order
+    pub fn entries(&self) -> &[XfbinChunkEntry] {
+        &self.entries
+    }
+
+    pub fn entry_by_name(&self, name: &str) -> Option<&XfbinChunkEntry> {
+        self.entries.iter().find(|entry| entry.struct_info.chunk_name == name)
+    }
+
+    pub fn entries_by_type(&self, chunk_type: NuccChunkType) -> impl Iterator<Item = &XfbinChunkEntry> {
+        self.entries.iter().filter(move |entry| entry.chunk_type() == chunk_type)
+    }
+
+    pub fn entries_by_filepath<'a>(&'a self, filepath: &'a str) -> impl Iterator<Item = &'a XfbinChunkEntry> {
+        self.entries.iter().filter(move |entry| entry.struct_info.filepath == filepath)
+    }
+
+    /// Decode the struct of the entry at `entry_index` in `entries()`
+    pub fn read_entry(&mut self, entry_index: usize) -> XfbinResult<Box<dyn NuccStruct>> {
+        let entry = get_indexed("chunk entry", &self.entries, entry_index)?.clone();
+
+        let parsed = self
+            .reader
+            .read_bytes_at(entry.offset + XfbinChunkHeader::SIZE, entry.size as usize)
+            .map_err(XfbinError::from)
+            .and_then(|data| NuccChunkType::read_data(data, &entry.struct_info.chunk_type, entry.version))
+            .map_err(|e| XfbinError::chunk_decode(entry.chunk_index, &entry.struct_info, entry.offset, e))?;
+
+        self.tables.to_struct(parsed, entry.chunk_index, entry.offset, entry.struct_info)
+    }
+
+    /// Decode every chunk, giving the same result as reading the whole file eagerly
+    pub fn into_xfbin(mut self) -> XfbinResult<Xfbin> {
+        // Like the eager reader, structs after the last page chunk get a page of their own
+        if self.entries.iter().any(|entry| entry.page_index == self.pages.len()) {
+            let page = self.tables.trailing_page(self.struct_infos_end, self.struct_references_end)?;
+            self.pages.push(page);
+        }
+
+        for entry_index in 0..self.entries.len() {
+            let page_index = self.entries[entry_index].page_index;
+
+            let nucc_struct = self.read_entry(entry_index)?;
+            self.pages[page_index].structs.push(nucc_struct);
+        }
+
+        let layout = XfbinLayout {
+            encrypted: self.header.encrypted,
+            index_version: self.index_version,
+            min_page_size: self.min_page_size,
+            chunk_types: self.tables.chunk_types,
+            filepaths: self.tables.filepaths,
+            chunk_names: self.tables.chunk_names,
+            struct_infos: self.tables.struct_infos,
+        };
+
+        Ok(Xfbin {
+            version: self.header.version as u16,
+            pages: self.pages,
+            layout: Some(layout),
+        })
+    }
+
+    /// Decode the first struct named `name`, if there is one
+    pub fn read_by_name(&mut self, name: &str) -> XfbinResult<Option<Box<dyn NuccStruct>>> {
+        self.entries
+            .iter()
+            .position(|entry| entry.struct_info.chunk_name == name)
+            .map(|entry_index| self.read_entry(entry_index))
+            .transpose()
+    }
+
+    pub fn read_by_type(&mut self, chunk_type: NuccChunkType) -> XfbinResult<Vec<Box<dyn NuccStruct>>> {
+        self.read_matching(|entry| entry.chunk_type() == chunk_type)
+    }
+
+    pub fn read_by_filepath(&mut self, filepath: &str) -> XfbinResult<Vec<Box<dyn NuccStruct>>> {
+        self.read_matching(|entry| entry.struct_info.filepath == filepath)
+    }
+
+    fn read_matching<F>(&mut self, predicate: F) -> XfbinResult<Vec<Box<dyn NuccStruct>>>
+    where
+        F: Fn(&XfbinChunkEntry) -> bool,
+    {
+        let entry_indices = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| predicate(entry))
+            .map(|(entry_index, _)| entry_index)
+            .collect::<Vec<usize>>();
+
+        entry_indices
+            .into_iter()
+            .map(|entry_index| self.read_entry(entry_index))
+            .collect()
+    }
+}
+
+impl XfbinReader<Cursor<Bytes>> {
+    /// Read from a whole file in memory, decoding its payload first if it's encrypted
+    pub fn from_bytes(buf: Bytes) -> XfbinResult<Self> {
+        let (buf, encrypted) = transform::decode_file(buf)?;
+
+        let mut reader = Self::new(Cursor::new(buf))?;
+        reader.header.encrypted = encrypted;
+
+        Ok(reader)
+    }
+}
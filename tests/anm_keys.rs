@@ -0,0 +1,157 @@
+mod common;
+
+use common::{document, struct_info};
+use xfbin_lib::nucc_chunk::nucc_chunk_anm::*;
+use xfbin_lib::nucc_chunk::NuccChunkType;
+use xfbin_lib::*;
+
+use NuccAnmKeyFormat as F;
+
+const KEY_FORMATS: [NuccAnmKeyFormat; 22] = [
+    F::Vector3Fixed,
+    F::Vector3Linear,
+    F::Vector3Bezier,
+    F::EulerXYZFixed,
+    F::EulerInterpolated,
+    F::QuaternionLinear,
+    F::FloatFixed,
+    F::FloatLinear,
+    F::Vector2Fixed,
+    F::Vector2Linear,
+    F::OpacityShortTable,
+    F::ScaleShortTable,
+    F::QuaternionShortTable,
+    F::ColorRGBTable,
+    F::Vector3Table,
+    F::FloatTable,
+    F::QuaternionTable,
+    F::FloatTableNoInterp,
+    F::Vector3ShortLinear,
+    F::Vector3TableNoInterp,
+    F::QuaternionShortTableNoInterp,
+    F::OpacityShortTableNoInterp,
+];
+
+/// A distinct key in the variant `key_format` stores
+fn key(key_format: &NuccAnmKeyFormat, i: i32) -> NuccAnmKey {
+    let value = i as f32 + 0.5;
+    let short = (i % 100) as i16 - 2;
+
+    match key_format {
+        F::Vector3Fixed | F::EulerXYZFixed | F::EulerInterpolated | F::Vector3Table | F::Vector3TableNoInterp => {
+            NuccAnmKey::Vec3 { values: (value, -value, 2.0 * value) }
+        }
+        F::Vector3Linear | F::Vector3Bezier => NuccAnmKey::Vec3Linear { frame: i * 100, values: (value, -value, 2.0 * value) },
+        F::QuaternionLinear => NuccAnmKey::Vec4Linear { frame: i * 100, values: (value, -value, 2.0 * value, 1.0) },
+        F::QuaternionTable => NuccAnmKey::Vec4 { values: (value, -value, 2.0 * value, 1.0) },
+        F::QuaternionShortTable | F::QuaternionShortTableNoInterp => NuccAnmKey::ShortVec4 { values: (short, 2, 3, 0x4000) },
+        F::Vector2Fixed => NuccAnmKey::Vec2 { values: (value, -value) },
+        F::Vector2Linear => NuccAnmKey::Vec2Linear { frame: i * 100, values: (value, -value) },
+        F::FloatFixed | F::FloatTable | F::FloatTableNoInterp => NuccAnmKey::Float { values: value },
+        F::FloatLinear => NuccAnmKey::FloatLinear { frame: i * 100, values: value },
+        F::OpacityShortTable | F::OpacityShortTableNoInterp => NuccAnmKey::I16Vec { values: short },
+        F::ScaleShortTable => NuccAnmKey::I16Vec3 { values: (short, 2, 0x1000) },
+        F::Vector3ShortLinear => NuccAnmKey::I16Vec3Linear { frame: i * 100, values: (short, 2, 3) },
+        F::ColorRGBTable => NuccAnmKey::Color { values: ((i % 100) as u8, 128, 255) },
+        F::Unknown => NuccAnmKey::Unknown {},
+    }
+}
+
+fn track(track_index: u16, key_format: NuccAnmKeyFormat, key_count: i32) -> AnmTrack {
+    AnmTrack {
+        track_index,
+        keys: (0..key_count).map(|i| key(&key_format, i)).collect(),
+        key_format,
+    }
+}
+
+fn entry(tracks: Vec<AnmTrack>) -> AnmEntry {
+    AnmEntry {
+        coord: AnmCoord { clump_index: -1, coord_index: 0 },
+        entry_format: EntryFormat::Coord,
+        tracks,
+    }
+}
+
+fn anm_file(entries: Vec<AnmEntry>) -> XfbinResult<Vec<u8>> {
+    let anm = NuccAnm {
+        struct_info: struct_info("anm", "nuccChunkAnm"),
+        frame_count: 300,
+        other_entries_indices: vec![0],
+        entries,
+        ..Default::default()
+    };
+
+    write_xfbin_buf(document(vec![Box::new(anm)]))
+}
+
+fn tracks(entries: &[AnmEntry]) -> Vec<Vec<AnmTrack>> {
+    entries.iter().map(|entry| entry.tracks.clone()).collect()
+}
+
+fn read_tracks(buf: Vec<u8>) -> Vec<Vec<AnmTrack>> {
+    let xfbin = read_xfbin_buf(buf).unwrap();
+
+    tracks(&xfbin.get_by_name("anm").unwrap().downcast_ref::<NuccAnm>().unwrap().entries)
+}
+
+#[test]
+fn every_key_format_round_trips() {
+    for key_format in KEY_FORMATS {
+        // Tracks of odd sizes in between, so the entries after them have to be aligned
+        let entries = vec![
+            entry(vec![track(0, F::ColorRGBTable, 1)]),
+            entry(vec![track(0, key_format.clone(), 3), track(1, F::OpacityShortTable, 1)]),
+            entry(vec![track(0, F::ColorRGBTable, 1)]),
+        ];
+
+        let buf = anm_file(entries.clone()).unwrap();
+        assert_eq!(read_tracks(buf.clone()), tracks(&entries), "{:?}", key_format);
+
+        // The headers are derived from the tracks, so they come out the same too
+        let reread = read_xfbin_buf(buf.clone()).unwrap();
+        assert_eq!(write_xfbin_buf(reread).unwrap(), buf, "{:?}", key_format);
+    }
+}
+
+#[test]
+fn unknown_key_formats_are_rejected_when_writing() {
+    let result = anm_file(vec![entry(vec![track(0, F::Unknown, 1)])]);
+
+    let Err(XfbinError::ChunkEncode { source, .. }) = result else {
+        panic!("{:?}", result.map(|_| ()));
+    };
+    assert!(matches!(*source, XfbinError::KeyFormatMismatch { key_format: F::Unknown, .. }));
+}
+
+#[test]
+fn unknown_key_formats_are_rejected_when_reading() {
+    // The chunk itself doesn't check its keys, so it can be written with a track the reader can't skip over
+    let chunk = NuccChunkAnm {
+        version: 121,
+        frame_count: 100,
+        frame_size: 100,
+        entries: vec![entry(vec![track(0, F::FloatFixed, 1), track(1, F::Unknown, 1)])],
+        ..Default::default()
+    };
+
+    let data = NuccChunkType::write_data(Box::new(chunk)).unwrap();
+    let result = NuccChunkType::read_data(data.into(), "nuccChunkAnm", 121);
+
+    assert!(matches!(result, Err(XfbinError::Parse(_))), "{:?}", result.map(|chunk| chunk.chunk_type()));
+}
+
+#[test]
+fn tracks_too_large_for_their_header_are_rejected() {
+    // 0x4000 float keys are one byte past what the header's u16 track size can hold, 0x10000 keys past its key count
+    for (key_format, key_count) in [(F::FloatTable, 0x4000), (F::OpacityShortTable, 0x10000)] {
+        let result = anm_file(vec![entry(vec![track(0, key_format.clone(), key_count)])]);
+
+        let Err(XfbinError::ChunkEncode { source, .. }) = result else {
+            panic!("{:?}", result.map(|_| ()));
+        };
+        assert!(matches!(*source, XfbinError::TrackTooLarge { .. }), "{:?}", source);
+    }
+
+    assert!(anm_file(vec![entry(vec![track(0, F::FloatTable, 0x3FFF)])]).is_ok());
+}
@@ -0,0 +1,181 @@
+mod common;
+
+use binrw::{io::Cursor, BinReaderExt};
+use common::{document, struct_info};
+use xfbin_lib::nucc::{NuccAnmStrm, NuccAnmStrmFrame};
+use xfbin_lib::nucc_chunk::nucc_chunk_anm::AnmCoord;
+use xfbin_lib::nucc_chunk::nucc_chunk_anmstrm::{AnmStrmClump, AnmStrmFrameInfo};
+use xfbin_lib::nucc_chunk::nucc_chunk_anmstrmframe::*;
+use xfbin_lib::nucc_chunk::nucc_helper::{Vector3, Vector4};
+use xfbin_lib::xfbin_file::XfbinFile;
+use xfbin_lib::*;
+
+fn vector3(v: f32) -> Vector3 {
+    Vector3 { x: v, y: v + 1.0, z: v + 2.0 }
+}
+
+fn vector4(v: f32) -> Vector4 {
+    Vector4 { x: v, y: v + 1.0, z: v + 2.0, w: v + 3.0 }
+}
+
+fn entry(coord_index: u16, entry_format: u16, entry_size: u16, entry_data: Entry) -> AnmStrmEntry {
+    AnmStrmEntry {
+        coord: AnmCoord { clump_index: 0, coord_index },
+        entry_format,
+        entry_size,
+        entry_data,
+    }
+}
+
+fn frame(entries: Vec<AnmStrmEntry>) -> NuccAnmStrmFrame {
+    NuccAnmStrmFrame {
+        struct_info: struct_info("frame_7", "nuccChunkAnmStrmFrame"),
+        version: 121,
+        frame_number: 7,
+        unknown: 3,
+        entries,
+        trailing: Vec::new(),
+    }
+}
+
+fn read_frame(buf: Vec<u8>) -> NuccAnmStrmFrame {
+    let xfbin = read_xfbin_buf(buf).unwrap();
+
+    xfbin.get_by_name("frame_7").unwrap().downcast_ref::<NuccAnmStrmFrame>().unwrap().clone()
+}
+
+#[test]
+fn frames_with_every_entry_format_round_trip() {
+    let entries = vec![
+        entry(0, 1, 0x30, Entry::Bone(AnmEntryBone {
+            frame_count: 1,
+            location: vector3(1.0),
+            rotation: vector4(4.0),
+            scale: vector3(8.0),
+            toggled: 1.0,
+        })),
+        entry(1, 2, 0x30, Entry::Camera(AnmEntryCamera {
+            frame_count: 1,
+            location: vector3(1.0),
+            rotation: vector4(4.0),
+            fov: 45.0,
+            scale: vector3(8.0),
+        })),
+        entry(2, 4, 0x44, Entry::Material(AnmEntryMaterial {
+            frame_count: 1,
+            ambient_color: std::array::from_fn(|i| i as f32),
+        })),
+        entry(3, 5, 0x20, Entry::LightDirc(AnmEntryLightDirc {
+            frame_count: 1,
+            color: vector3(0.5),
+            intensity: 2.0,
+            direction: vector4(0.25),
+        })),
+        entry(4, 6, 0x24, Entry::LightPoint(AnmEntryLightPoint {
+            frame_count: 1,
+            color: vector3(0.5),
+            position: vector3(10.0),
+            intensity: 2.0,
+            radius: 5.0,
+            falloff: 0.5,
+        })),
+        entry(5, 8, 0x14, Entry::Ambient(AnmEntryAmbient {
+            frame_count: 1,
+            color: vector3(0.5),
+            intensity: 2.0,
+        })),
+        entry(6, 12, 0x10, Entry::MorphModel(AnmEntryMorphModel {
+            frame_count: 3,
+            morph_weight: vec![0.0, 0.5, 1.0],
+        })),
+    ];
+
+    let buf = write_xfbin_buf(document(vec![Box::new(frame(entries.clone()))])).unwrap();
+    let read = read_frame(buf.clone());
+
+    assert_eq!(read.frame_number, 7);
+    assert_eq!(read.unknown, 3);
+    assert_eq!(read.entries, entries);
+
+    assert_eq!(write_xfbin_buf(read_xfbin_buf(buf.clone()).unwrap()).unwrap(), buf);
+}
+
+#[test]
+fn frame_entries_are_written_after_the_frame_header() {
+    let ambient = Entry::Ambient(AnmEntryAmbient {
+        frame_count: 1,
+        color: vector3(0.5),
+        intensity: 2.0,
+    });
+    let buf = write_xfbin_buf(document(vec![Box::new(frame(vec![entry(9, 8, 0x14, ambient)]))])).unwrap();
+
+    let expected = [
+        &7u32.to_be_bytes()[..],
+        &1u16.to_be_bytes(),
+        &3u16.to_be_bytes(),
+        // Coord, entry format and size
+        &0i16.to_be_bytes(),
+        &9u16.to_be_bytes(),
+        &8u16.to_be_bytes(),
+        &0x14u16.to_be_bytes(),
+        &1i32.to_be_bytes(),
+        &0.5f32.to_be_bytes(),
+        &1.5f32.to_be_bytes(),
+        &2.5f32.to_be_bytes(),
+        &2.0f32.to_be_bytes(),
+    ]
+    .concat();
+
+    let xfbin_file: XfbinFile = Cursor::new(buf).read_be().unwrap();
+    assert!(xfbin_file.chunks.iter().any(|chunk| chunk.data == expected));
+}
+
+#[test]
+fn unknown_entry_formats_fail_to_read() {
+    let ambient = Entry::Ambient(AnmEntryAmbient {
+        frame_count: 1,
+        color: vector3(0.5),
+        intensity: 2.0,
+    });
+    let mut buf = write_xfbin_buf(document(vec![Box::new(frame(vec![entry(9, 8, 0x14, ambient)]))])).unwrap();
+
+    // Entry format 8 right after coord index 9
+    let position = buf.windows(4).position(|w| w == [0, 9, 0, 8]).unwrap();
+    buf[position + 3] = 3;
+
+    assert!(matches!(read_xfbin_buf(buf), Err(XfbinError::ChunkDecode { .. })));
+}
+
+#[test]
+fn anmstrms_keep_their_clumps_indices_and_frame_infos() {
+    let anmstrm = NuccAnmStrm {
+        struct_info: struct_info("anmstrm", "nuccChunkAnmStrm"),
+        frame_count: 200,
+        frame_size: 100,
+        is_looped: true,
+        looped_flag: 1,
+        clumps: vec![AnmStrmClump {
+            clump_index: 1,
+            bone_material_indices: vec![2, 3],
+            model_indices: vec![4],
+        }],
+        other_entry_indices: vec![5, 6],
+        unk_entry_count: 1,
+        entries: vec![
+            AnmStrmFrameInfo { unknown: 0, frame_offset: 0, frame_number: 0 },
+            AnmStrmFrameInfo { unknown: 0, frame_offset: 1, frame_number: 100 },
+        ],
+        ..Default::default()
+    };
+
+    let buf = write_xfbin_buf(document(vec![Box::new(anmstrm.clone())])).unwrap();
+    let xfbin = read_xfbin_buf(buf.clone()).unwrap();
+    let read = xfbin.get_by_name("anmstrm").unwrap().downcast_ref::<NuccAnmStrm>().unwrap();
+
+    assert_eq!((read.frame_count, read.frame_size, read.is_looped), (200, 100, true));
+    assert_eq!(read.clumps, anmstrm.clumps);
+    assert_eq!((&read.other_entry_indices, read.unk_entry_count), (&anmstrm.other_entry_indices, 1));
+    assert_eq!(read.entries, anmstrm.entries);
+
+    assert_eq!(write_xfbin_buf(xfbin).unwrap(), buf);
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use common::{sample_file, TempDir};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use xfbin_lib::*;
+
+/// A directory holding the sample file as `sample.xfbin`
+fn sample_dir(name: &str) -> (TempDir, PathBuf) {
+    let dir = TempDir::new(&format!("cli_{}", name));
+    let file = dir.join("sample.xfbin");
+    fs::write(&file, sample_file()).unwrap();
+
+    (dir, file)
+}
+
+fn xfbin<I: AsRef<std::ffi::OsStr>>(args: impl IntoIterator<Item = I>) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_xfbin")).args(args).output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn path(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+#[test]
+fn info_summarizes_the_file() {
+    let (_dir, file) = sample_dir("info");
+
+    let out = stdout(&xfbin(["info", path(&file)]));
+
+    assert!(out.starts_with("version: 121\npages: 2\nstructs: 4\n"), "{}", out);
+    assert!(out.contains("  nuccChunkAnm: 1\n"), "{}", out);
+    assert!(out.contains("references: 0 (0 dangling)"), "{}", out);
+}
+
+#[test]
+fn list_prints_the_matching_structs() {
+    let (_dir, file) = sample_dir("list");
+
+    assert_eq!(stdout(&xfbin(["list", path(&file)])).lines().count(), 4);
+    assert_eq!(
+        stdout(&xfbin(["list", path(&file), "--type", "nuccChunkA*"])),
+        "0\t1\tnuccChunkAmbient\tambient\tc/test.bin\n1\t1\tnuccChunkAnm\tanm\tc/test.bin\n"
+    );
+}
+
+#[test]
+fn extracted_structs_can_be_replaced() {
+    let (dir, file) = sample_dir("replace");
+    let extracted = dir.join("bin.bin");
+    let edited = dir.join("edited.xfbin");
+
+    stdout(&xfbin(["extract", path(&file), path(&extracted), "--name", "bin"]));
+    assert_eq!(fs::read(&extracted).unwrap(), [1, 2, 3, 4, 5]);
+
+    fs::write(&extracted, [9, 9]).unwrap();
+    stdout(&xfbin(["replace", path(&file), "bin", path(&extracted), "-o", path(&edited)]));
+
+    let xfbin = read_xfbin(&edited).unwrap();
+    assert_eq!(xfbin.get_by_name("bin").unwrap().downcast_ref::<NuccBinary>().unwrap().data.to_vec(), [9, 9]);
+}
+
+#[test]
+fn extracted_directories_repack_to_the_same_file() {
+    let (dir, file) = sample_dir("repack");
+    let unpacked = dir.join("unpacked");
+    let repacked = dir.join("repacked.xfbin");
+
+    stdout(&xfbin(["extract", path(&file), path(&unpacked)]));
+    stdout(&xfbin(["repack", path(&unpacked), path(&repacked)]));
+
+    assert_eq!(fs::read(&repacked).unwrap(), fs::read(&file).unwrap());
+}
+
+#[test]
+fn dump_json_prints_a_readable_document() {
+    let (_dir, file) = sample_dir("dump_json");
+
+    let out = stdout(&xfbin(["dump-json", path(&file)]));
+
+    let xfbin = from_json_str(&out).unwrap();
+    assert!(xfbin.get_by_name("ambient").is_some());
+}
+
+#[test]
+fn validate_exits_with_1_on_errors() {
+    let (_dir, file) = sample_dir("validate");
+
+    assert_eq!(stdout(&xfbin(["validate", path(&file)])), "");
+
+    let buf = fs::read(&file).unwrap();
+    fs::write(&file, &buf[..buf.len() - 2]).unwrap();
+
+    let output = xfbin(["validate", path(&file)]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!output.stdout.is_empty());
+}
+
+#[test]
+fn diff_exits_with_1_on_differences() {
+    let (dir, file) = sample_dir("diff");
+    let other = dir.join("other.xfbin");
+
+    assert_eq!(stdout(&xfbin(["diff", path(&file), path(&file)])), "");
+
+    let mut changed = read_xfbin_buf(sample_file()).unwrap();
+    changed.pages[0].remove_struct(0).unwrap();
+    write_xfbin(changed, &other).unwrap();
+
+    let output = xfbin(["diff", path(&file), path(&other)]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("- bin (nuccChunkBinary) c/test.bin"));
+}
+
+#[test]
+fn failed_commands_exit_with_2() {
+    let dir = TempDir::new("cli_failed");
+
+    let output = xfbin(["info", path(&dir.join("missing.xfbin"))]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("error: can't read"));
+}
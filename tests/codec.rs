@@ -0,0 +1,128 @@
+mod common;
+
+use common::{binary, document, struct_info};
+use xfbin_lib::codec::{Bytes, IndexMap};
+use xfbin_lib::nucc::{NuccInfo, NuccUnknown};
+use xfbin_lib::nucc_chunk::{NuccChunk, NuccChunkType};
+use xfbin_lib::*;
+
+const CHUNK_TYPE: &str = "nuccChunkPointer";
+
+#[derive(Debug)]
+struct PointerChunk {
+    version: u16,
+    target: u32,
+}
+
+impl NuccChunk for PointerChunk {
+    fn chunk_type(&self) -> NuccChunkType {
+        NuccChunkType::NuccChunkCustom
+    }
+
+    fn version(&self) -> u16 {
+        self.version
+    }
+}
+
+/// A struct pointing at another struct of its page
+#[derive(Debug, Clone)]
+struct Pointer {
+    struct_info: NuccStructInfo,
+    version: u16,
+    target: NuccStructInfo,
+}
+
+impl NuccInfo for Pointer {
+    fn struct_info(&self) -> &NuccStructInfo {
+        &self.struct_info
+    }
+
+    fn struct_info_mut(&mut self) -> &mut NuccStructInfo {
+        &mut self.struct_info
+    }
+}
+
+impl NuccStruct for Pointer {
+    fn chunk_type(&self) -> NuccChunkType {
+        NuccChunkType::NuccChunkCustom
+    }
+
+    fn version(&self) -> u16 {
+        self.version
+    }
+}
+
+struct PointerCodec;
+
+impl NuccChunkCodec for PointerCodec {
+    type Chunk = PointerChunk;
+    type Struct = Pointer;
+
+    fn decode(&self, data: Bytes, version: u16) -> XfbinResult<PointerChunk> {
+        let target = u32::from_be_bytes(data[..4].try_into().unwrap());
+
+        Ok(PointerChunk { version, target })
+    }
+
+    fn encode(&self, chunk: PointerChunk) -> XfbinResult<Vec<u8>> {
+        Ok(chunk.target.to_be_bytes().to_vec())
+    }
+
+    fn to_struct(&self, chunk: PointerChunk, struct_infos: &[NuccStructInfo], _: &[NuccStructReference]) -> XfbinResult<Pointer> {
+        Ok(Pointer {
+            struct_info: Default::default(),
+            version: chunk.version,
+            target: struct_infos[chunk.target as usize].clone(),
+        })
+    }
+
+    fn to_chunk(
+        &self,
+        nucc_struct: Pointer,
+        struct_info_map: &IndexMap<NuccStructInfo, u32>,
+        _: &IndexMap<NuccStructReference, u32>,
+    ) -> XfbinResult<PointerChunk> {
+        Ok(PointerChunk {
+            version: nucc_struct.version,
+            target: struct_info_map.get(&nucc_struct.target).copied().unwrap_or_default(),
+        })
+    }
+}
+
+/// A file with a binary and a pointer chunk targeting it, written before the codec is registered
+fn pointer_file() -> Vec<u8> {
+    let xfbin = document(vec![binary("target", vec![1, 2, 3, 4])]);
+
+    // Find where the binary's chunk map ended up, and point at it
+    let mut xfbin = read_xfbin_buf(write_xfbin_buf(xfbin).unwrap()).unwrap();
+    let target_index = xfbin.pages[0]
+        .struct_infos
+        .iter()
+        .position(|struct_info| struct_info.chunk_name == "target")
+        .unwrap() as u32;
+
+    xfbin.pages[0].add_struct(Box::new(NuccUnknown {
+        struct_info: struct_info("pointer", CHUNK_TYPE),
+        version: 121,
+        data: target_index.to_be_bytes().to_vec().into(),
+        chunk_type: CHUNK_TYPE.to_string(),
+    }));
+
+    write_xfbin_buf(xfbin).unwrap()
+}
+
+#[test]
+fn custom_structs_are_written_to_json_in_their_encoded_form() {
+    let original = pointer_file();
+    register_codec(CHUNK_TYPE, PointerCodec);
+
+    let xfbin = read_xfbin_buf(original.clone()).unwrap();
+    let pointer = xfbin.get_by_name("pointer").unwrap();
+    assert!(pointer.downcast_ref::<Pointer>().is_some());
+
+    let json = to_json_string(&xfbin).unwrap();
+    assert!(json.contains(CHUNK_TYPE));
+
+    let imported = from_json_str(&json).unwrap();
+    assert_eq!(write_xfbin_buf(imported).unwrap(), original);
+}
@@ -0,0 +1,94 @@
+//! Fixtures shared by the integration tests. Each test crate uses a different subset of them.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use xfbin_lib::nucc::{NuccAmbient, NuccLightDirc};
+use xfbin_lib::*;
+
+pub fn struct_info(chunk_name: &str, chunk_type: &str) -> NuccStructInfo {
+    NuccStructInfo {
+        chunk_name: chunk_name.to_string(),
+        chunk_type: chunk_type.to_string(),
+        filepath: "c/test.bin".to_string(),
+    }
+}
+
+pub fn binary(chunk_name: &str, data: Vec<u8>) -> Box<dyn NuccStruct> {
+    Box::new(NuccBinary {
+        struct_info: struct_info(chunk_name, "nuccChunkBinary"),
+        version: 121,
+        data: data.into(),
+    })
+}
+
+/// A document with `structs` in a single page
+pub fn document(structs: Vec<Box<dyn NuccStruct>>) -> Xfbin {
+    let mut page = XfbinPage::default();
+    for nucc_struct in structs {
+        page.add_struct(nucc_struct);
+    }
+
+    Xfbin {
+        pages: vec![page],
+        ..Default::default()
+    }
+}
+
+/// A document with a few struct types spread over two pages, written once so it has a layout to preserve
+pub fn sample_file() -> Vec<u8> {
+    let mut first = XfbinPage::default();
+    first.add_struct(binary("bin", vec![1, 2, 3, 4, 5]));
+    first.add_struct(Box::new(NuccAmbient {
+        struct_info: struct_info("ambient", "nuccChunkAmbient"),
+        version: 121,
+        data: vec![7; 16],
+        trailing: Vec::new(),
+    }));
+
+    let mut second = XfbinPage::default();
+    second.add_struct(Box::new(NuccLightDirc {
+        struct_info: struct_info("light", "nuccChunkLightDirc"),
+        version: 121,
+        data: vec![9; 64],
+        trailing: Vec::new(),
+    }));
+    second.add_struct(Box::new(NuccAnm {
+        struct_info: struct_info("anm", "nuccChunkAnm"),
+        ..Default::default()
+    }));
+
+    let xfbin = Xfbin {
+        pages: vec![first, second],
+        ..Default::default()
+    };
+
+    write_xfbin_buf(xfbin).unwrap()
+}
+
+/// An empty directory for one test, removed when dropped
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("xfbin_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,136 @@
+use xfbin_lib::nucc_chunk::nucc_chunk_anm::*;
+use xfbin_lib::*;
+
+fn float_table(values: &[f32]) -> AnmTrack {
+    AnmTrack {
+        track_index: 0,
+        key_format: NuccAnmKeyFormat::FloatTable,
+        keys: values.iter().map(|&values| NuccAnmKey::Float { values }).collect(),
+    }
+}
+
+#[test]
+fn keyed_targets_end_with_a_terminator() {
+    let track = float_table(&[0.0, 1.0, 4.0]);
+    let conversion = track.convert(NuccAnmKeyFormat::FloatLinear, 3, 100).unwrap();
+
+    assert_eq!(
+        conversion.track.keys,
+        vec![
+            NuccAnmKey::FloatLinear { frame: 0, values: 0.0 },
+            NuccAnmKey::FloatLinear { frame: 100, values: 1.0 },
+            NuccAnmKey::FloatLinear { frame: 200, values: 4.0 },
+            NuccAnmKey::FloatLinear { frame: -1, values: 4.0 },
+        ]
+    );
+    assert_eq!(conversion.max_error, 0.0);
+}
+
+#[test]
+fn table_targets_have_no_terminator() {
+    let track = float_table(&[0.0, 1.0, 4.0]);
+
+    let linear = track.convert(NuccAnmKeyFormat::FloatLinear, 3, 100).unwrap().track;
+    let conversion = linear.convert(NuccAnmKeyFormat::FloatTable, 3, 100).unwrap();
+
+    assert_eq!(conversion.track, track);
+    assert_eq!(conversion.max_error, 0.0);
+}
+
+#[test]
+fn converting_between_kinds_of_values_is_rejected() {
+    let result = float_table(&[0.0]).convert(NuccAnmKeyFormat::Vector3Table, 1, 100);
+
+    assert!(matches!(result, Err(XfbinError::UnsupportedConversion { .. })));
+}
+
+#[test]
+fn short_linear_tracks_are_not_converted_either_way() {
+    let track = AnmTrack {
+        track_index: 0,
+        key_format: NuccAnmKeyFormat::Vector3Table,
+        keys: vec![NuccAnmKey::Vec3 { values: (1.0, 2.0, 3.0) }],
+    };
+    let result = track.convert(NuccAnmKeyFormat::Vector3ShortLinear, 1, 100);
+    assert!(matches!(result, Err(XfbinError::UnsupportedConversion { .. })));
+
+    let short = AnmTrack {
+        track_index: 0,
+        key_format: NuccAnmKeyFormat::Vector3ShortLinear,
+        keys: vec![NuccAnmKey::I16Vec3Linear { frame: 0, values: (1, 2, 3) }],
+    };
+    let result = short.convert(NuccAnmKeyFormat::Vector3Table, 1, 100);
+    assert!(matches!(result, Err(XfbinError::UnsupportedConversion { .. })));
+}
+
+#[test]
+fn only_float_tables_are_reduced() {
+    let color = AnmTrack {
+        track_index: 1,
+        key_format: NuccAnmKeyFormat::ColorRGBTable,
+        keys: vec![NuccAnmKey::Color { values: (10, 20, 30) }; 11],
+    };
+
+    let mut anm = NuccAnm {
+        frame_count: 1000,
+        frame_size: 100,
+        entries: vec![AnmEntry {
+            coord: AnmCoord { clump_index: -1, coord_index: 0 },
+            entry_format: EntryFormat::Coord,
+            tracks: vec![float_table(&[2.0; 11]), color.clone()],
+        }],
+        ..Default::default()
+    };
+
+    let tolerance = Tolerance { value: 0.01, angle: 0.01 };
+    assert_eq!(anm.reduce_tables(&tolerance), 1);
+
+    let tracks = &anm.entries[0].tracks;
+    assert_eq!(tracks[0].key_format, NuccAnmKeyFormat::FloatLinear);
+    assert_eq!(tracks[1], color);
+}
+
+#[test]
+fn reduced_tracks_stay_within_tolerance() {
+    let values = (0..60).map(|frame| (frame as f32 / 10.0).sin()).collect::<Vec<f32>>();
+    let track = float_table(&values);
+
+    for value in [0.001, 0.01, 0.1] {
+        let tolerance = Tolerance { value, angle: 0.0 };
+        let reduced = track.reduce(&tolerance, values.len(), 100).unwrap();
+
+        assert!(reduced.max_error <= value, "{} > {}", reduced.max_error, value);
+        assert!(reduced.track.keys.len() < values.len());
+        assert_eq!(reduced.track.keys.last(), Some(&NuccAnmKey::FloatLinear { frame: -1, values: values[59] }));
+    }
+}
+
+#[test]
+fn reduced_rotations_stay_within_the_angle_tolerance() {
+    // Speeding up around z, then tilting around x
+    let keys = (0..40)
+        .map(|frame| {
+            let (z, x) = ((frame * frame) as f32 / 800.0, (frame as f32 / 40.0).powi(3) / 2.0);
+            let (sin_z, cos_z) = z.sin_cos();
+            let (sin_x, cos_x) = x.sin_cos();
+
+            NuccAnmKey::Vec4 {
+                values: (sin_x * cos_z, -sin_x * sin_z, cos_x * sin_z, cos_x * cos_z),
+            }
+        })
+        .collect::<Vec<NuccAnmKey>>();
+
+    let track = AnmTrack {
+        track_index: 0,
+        key_format: NuccAnmKeyFormat::QuaternionTable,
+        keys,
+    };
+
+    let angle = 0.5f32.to_radians();
+    let reduced = track.reduce(&Tolerance { value: 0.0, angle }, 40, 100).unwrap();
+
+    assert!(reduced.max_error <= angle, "{} > {}", reduced.max_error, angle);
+    assert_eq!(reduced.track.key_format, NuccAnmKeyFormat::QuaternionLinear);
+    assert!(reduced.track.keys.len() < 40);
+    assert!(matches!(reduced.track.keys.last(), Some(NuccAnmKey::Vec4Linear { frame: -1, .. })));
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use common::{binary, document, struct_info};
+use xfbin_lib::nucc_chunk::nucc_chunk_anm::{AnmCoord, AnmEntry, AnmTrack, EntryFormat, NuccAnmKey, NuccAnmKeyFormat};
+use xfbin_lib::*;
+
+fn anm(entry_format: EntryFormat, keys: Vec<f32>) -> Box<dyn NuccStruct> {
+    Box::new(NuccAnm {
+        struct_info: struct_info("anm", "nuccChunkAnm"),
+        entries: vec![AnmEntry {
+            coord: AnmCoord { clump_index: -1, coord_index: 0 },
+            entry_format,
+            tracks: vec![AnmTrack {
+                track_index: 0,
+                key_format: NuccAnmKeyFormat::FloatFixed,
+                keys: keys.into_iter().map(|values| NuccAnmKey::Float { values }).collect(),
+            }],
+        }],
+        ..Default::default()
+    })
+}
+
+#[test]
+fn identical_documents_have_no_differences() {
+    let old = document(vec![binary("bin", vec![1, 2, 3]), anm(EntryFormat::Coord, vec![1.0, 2.0])]);
+    let new = document(vec![binary("bin", vec![1, 2, 3]), anm(EntryFormat::Coord, vec![1.0, 2.0])]);
+
+    let diff = old.diff(&new).unwrap();
+
+    assert!(diff.is_empty(), "{}", diff);
+}
+
+#[test]
+fn added_removed_and_modified_structs_are_listed() {
+    let old = document(vec![binary("kept", vec![1, 2, 3]), binary("removed", vec![4])]);
+    let new = document(vec![binary("kept", vec![1, 9, 3, 4]), binary("added", vec![5])]);
+
+    let diff = old.diff(&new).unwrap();
+
+    let structs = diff
+        .structs
+        .iter()
+        .map(|struct_diff| (struct_diff.kind, struct_diff.struct_info.chunk_name.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(structs, [(DiffKind::Modified, "kept"), (DiffKind::Removed, "removed"), (DiffKind::Added, "added")]);
+
+    let fields = &diff.structs[0].fields;
+    assert_eq!(fields.len(), 1);
+    assert_eq!(fields[0].field, "payload");
+    // Binary payloads start with their size
+    assert_eq!(fields[0].new, "8 bytes, differing from offset 0x3");
+
+    // The strings only one side uses are listed too
+    let chunk_names = diff
+        .string_tables
+        .iter()
+        .map(|string| (string.kind, string.value.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(chunk_names, [(DiffKind::Removed, "removed"), (DiffKind::Added, "added")]);
+}
+
+#[test]
+fn changed_keys_are_listed_by_track() {
+    let old = document(vec![anm(EntryFormat::Coord, vec![1.0, 2.0, 3.0])]);
+    let new = document(vec![anm(EntryFormat::Coord, vec![1.0, 5.0])]);
+
+    let diff = old.diff(&new).unwrap();
+
+    assert_eq!(diff.structs.len(), 1);
+    let entries = &diff.structs[0].entries;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].kind, DiffKind::Modified);
+    assert_eq!(entries[0].entry_format, None);
+
+    let track = &entries[0].tracks[0];
+    assert_eq!(track.kind, DiffKind::Modified);
+    assert_eq!(track.key_format, None);
+
+    let keys = track.keys.iter().map(|key| (key.key_index, key.old.clone(), key.new.clone())).collect::<Vec<_>>();
+    assert_eq!(
+        keys,
+        [
+            (1, Some(NuccAnmKey::Float { values: 2.0 }), Some(NuccAnmKey::Float { values: 5.0 })),
+            (2, Some(NuccAnmKey::Float { values: 3.0 }), None),
+        ]
+    );
+
+    assert!(diff.to_string().contains("        ~ track 0, 2 keys differ\n"), "{}", diff);
+}
+
+#[test]
+fn entry_format_changes_are_listed() {
+    let old = document(vec![anm(EntryFormat::Coord, vec![1.0])]);
+    let new = document(vec![anm(EntryFormat::Camera, vec![1.0])]);
+
+    let diff = old.diff(&new).unwrap();
+
+    let entry = &diff.structs[0].entries[0];
+    assert_eq!(entry.entry_format, Some((EntryFormat::Coord, EntryFormat::Camera)));
+    assert!(entry.tracks.is_empty());
+
+    assert!(diff.to_string().contains("~ entry (clump -1, coord 0), entry format Coord -> Camera\n"), "{}", diff);
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use common::{binary, struct_info};
+use xfbin_lib::*;
+
+fn reference(chunk_name: &str) -> NuccStructReference {
+    NuccStructReference {
+        chunk_name: format!("{}_ref", chunk_name),
+        struct_info: struct_info(chunk_name, "nuccChunkBinary"),
+    }
+}
+
+/// Chunk names of the page's struct infos, with the null entry as ""
+fn table(page: &XfbinPage) -> Vec<&str> {
+    page.struct_infos.iter().map(|struct_info| struct_info.chunk_name.as_str()).collect()
+}
+
+fn structs(page: &XfbinPage) -> Vec<&str> {
+    page.structs.iter().map(|nucc_struct| nucc_struct.struct_info().chunk_name.as_str()).collect()
+}
+
+fn page(chunk_names: &[&str]) -> XfbinPage {
+    let mut page = XfbinPage::default();
+    for chunk_name in chunk_names {
+        page.add_struct(binary(chunk_name, vec![0; 4]));
+    }
+
+    page
+}
+
+#[test]
+fn added_structs_register_their_struct_info_once() {
+    let mut page = page(&["a", "b"]);
+    page.add_struct(binary("a", vec![0; 4]));
+
+    assert_eq!(structs(&page), ["a", "b", "a"]);
+    assert_eq!(table(&page), ["", "a", "b"]);
+}
+
+#[test]
+fn added_struct_infos_go_before_the_page_and_index_entries() {
+    let xfbin = Xfbin {
+        pages: vec![page(&["a"])],
+        ..Default::default()
+    };
+    let mut page = read_xfbin_buf(write_xfbin_buf(xfbin).unwrap()).unwrap().pages.remove(0);
+
+    page.add_struct(binary("b", vec![0; 4]));
+
+    assert_eq!(table(&page), ["", "a", "b", "Page0", "index"]);
+}
+
+#[test]
+fn removed_structs_release_struct_infos_nothing_else_uses() {
+    let mut page = page(&["a", "b", "b", "c"]);
+    page.add_reference(reference("c"));
+
+    assert_eq!(page.remove_struct(0).unwrap().struct_info().chunk_name, "a");
+    page.remove_struct(0).unwrap();
+    page.remove_struct(1).unwrap();
+
+    // The other "b" and the reference to "c" keep theirs
+    assert_eq!(structs(&page), ["b"]);
+    assert_eq!(table(&page), ["", "b", "c"]);
+}
+
+#[test]
+fn struct_infos_stay_put_in_pages_that_address_them_by_index() {
+    let mut page = page(&["a"]);
+    page.add_struct(Box::new(NuccAnm {
+        struct_info: struct_info("anm", "nuccChunkAnm"),
+        ..Default::default()
+    }));
+
+    page.remove_struct(0).unwrap();
+
+    assert_eq!(table(&page), ["", "a", "anm"]);
+}
+
+#[test]
+fn replacements_take_over_the_old_struct_info_and_its_references() {
+    let mut page = page(&["a", "b"]);
+    page.add_reference(reference("a"));
+
+    let old = page.replace_struct(0, binary("x", vec![0; 4])).unwrap();
+
+    assert_eq!(old.struct_info().chunk_name, "a");
+    assert_eq!(structs(&page), ["x", "b"]);
+    assert_eq!(table(&page), ["", "x", "b"]);
+    assert_eq!(page.struct_references[0].struct_info, struct_info("x", "nuccChunkBinary"));
+}
+
+#[test]
+fn replacements_with_a_registered_struct_info_release_the_old_one() {
+    let mut page = page(&["a", "b"]);
+
+    page.replace_struct(0, binary("b", vec![0; 4])).unwrap();
+
+    assert_eq!(structs(&page), ["b", "b"]);
+    assert_eq!(table(&page), ["", "b"]);
+}
+
+#[test]
+fn moving_a_struct_only_changes_the_struct_order() {
+    let mut page = page(&["a", "b", "c"]);
+
+    page.move_struct(0, 2).unwrap();
+
+    assert_eq!(structs(&page), ["b", "c", "a"]);
+    assert_eq!(table(&page), ["", "a", "b", "c"]);
+}
+
+#[test]
+fn references_register_the_struct_info_they_point_at_once() {
+    let mut page = page(&["a"]);
+
+    page.add_reference(reference("x"));
+    page.add_reference(reference("x"));
+
+    assert_eq!(page.struct_references, [reference("x")]);
+    assert_eq!(table(&page), ["", "a", "x"]);
+}
+
+#[test]
+fn edits_out_of_range_are_rejected() {
+    let mut page = page(&["a"]);
+
+    assert!(matches!(page.remove_struct(1), Err(XfbinError::IndexOutOfRange { .. })));
+    assert!(matches!(page.replace_struct(1, binary("x", vec![0; 4])), Err(XfbinError::IndexOutOfRange { .. })));
+    assert!(matches!(page.move_struct(0, 1), Err(XfbinError::IndexOutOfRange { .. })));
+
+    assert_eq!(structs(&page), ["a"]);
+    assert_eq!(table(&page), ["", "a"]);
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use common::{binary, document, struct_info, TempDir};
+use serde_json::Value;
+use xfbin_lib::json::JSON_FORMAT_VERSION;
+use xfbin_lib::nucc_chunk::nucc_chunk_anm::*;
+use xfbin_lib::nucc_chunk::NuccChunkType;
+use xfbin_lib::*;
+
+fn anm_document() -> Xfbin {
+    let track = |track_index, key_format, keys| AnmTrack { track_index, key_format, keys };
+
+    let anm = NuccAnm {
+        struct_info: struct_info("anm", "nuccChunkAnm"),
+        frame_count: 200,
+        entries: vec![AnmEntry {
+            coord: AnmCoord { clump_index: -1, coord_index: 0 },
+            entry_format: EntryFormat::Coord,
+            tracks: vec![
+                track(0, NuccAnmKeyFormat::Vector3Fixed, vec![NuccAnmKey::Vec3 { values: (1.0, 2.0, 3.0) }]),
+                track(2, NuccAnmKeyFormat::FloatTable, vec![NuccAnmKey::Float { values: 0.5 }; 3]),
+            ],
+        }],
+        ..Default::default()
+    };
+
+    document(vec![Box::new(anm)])
+}
+
+fn tracks(xfbin: &Xfbin) -> Vec<AnmTrack> {
+    xfbin.get_by_name("anm").unwrap().downcast_ref::<NuccAnm>().unwrap().entries[0].tracks.clone()
+}
+
+/// The document as version 1 wrote it, with the track indices and key formats in headers next to the tracks
+fn to_version_1(json: &str) -> String {
+    let mut value = serde_json::from_str::<Value>(json).unwrap();
+    value["format_version"] = 1.into();
+
+    let entry = &mut value["pages"][0]["structs"][0]["entries"][0];
+    let mut track_headers = Vec::new();
+
+    for track in entry["tracks"].as_array_mut().unwrap() {
+        let track = track.as_object_mut().unwrap();
+        let frame_count = track["keys"].as_array().unwrap().len();
+
+        track_headers.push(serde_json::json!({
+            "track_index": track.remove("track_index").unwrap(),
+            "key_format": track.remove("key_format").unwrap(),
+            "frame_count": frame_count,
+        }));
+    }
+
+    entry["track_headers"] = track_headers.into();
+
+    value.to_string()
+}
+
+#[test]
+fn version_1_documents_are_migrated() {
+    let xfbin = anm_document();
+    let json = to_version_1(&to_json_string(&xfbin).unwrap());
+
+    assert_eq!(tracks(&from_json_str(&json).unwrap()), tracks(&xfbin));
+}
+
+#[test]
+fn unknown_format_versions_are_rejected() {
+    let json = to_json_string(&anm_document()).unwrap();
+
+    for format_version in [0, JSON_FORMAT_VERSION + 1] {
+        let mut value = serde_json::from_str::<Value>(&json).unwrap();
+        value["format_version"] = format_version.into();
+
+        let result = from_json_str(&value.to_string());
+        assert!(
+            matches!(result, Err(XfbinError::UnsupportedFormatVersion { found, .. }) if found == format_version),
+            "{:?}",
+            result.map(|_| ())
+        );
+    }
+}
+
+#[test]
+fn sidecar_paths_outside_the_json_directory_are_rejected() {
+    let dir = TempDir::new("json_sidecar");
+    let filepath = dir.join("doc.json");
+    let xfbin = document(vec![binary("bin", vec![1, 2, 3])]);
+
+    write_xfbin_json(&xfbin, &filepath, PayloadMode::Sidecar).unwrap();
+    let json = std::fs::read_to_string(&filepath).unwrap();
+    assert!(read_xfbin_json(&filepath).is_ok());
+
+    let outside = std::env::temp_dir().join("outside.bin").to_string_lossy().into_owned();
+    for sidecar in ["../outside.bin", "doc.payloads/../../outside.bin", outside.as_str()] {
+        let mut value = serde_json::from_str::<Value>(&json).unwrap();
+        value["pages"][0]["structs"][0]["data"]["sidecar"] = sidecar.into();
+        std::fs::write(&filepath, value.to_string()).unwrap();
+
+        let result = read_xfbin_json(&filepath);
+        assert!(matches!(result, Err(XfbinError::Json(_))), "{}: {:?}", sidecar, result.map(|_| ()));
+    }
+}
+
+#[test]
+fn structs_are_read_back_by_their_type_tag() {
+    let nucc_struct = binary("bin", vec![1, 2, 3]);
+    let json = struct_to_json_string(nucc_struct.as_ref()).unwrap();
+    let value = serde_json::from_str::<Value>(&json).unwrap();
+    assert_eq!(value["type"], "nuccChunkBinary");
+
+    let read = struct_from_json_str(&json).unwrap();
+    assert_eq!(read.chunk_type(), NuccChunkType::NuccChunkBinary);
+    assert_eq!(struct_to_json_string(read.as_ref()).unwrap(), json);
+
+    for tag in ["nuccChunkPage", "nuccChunkNope"] {
+        let mut value = value.clone();
+        value["type"] = tag.into();
+
+        let result = struct_from_json_str(&value.to_string());
+        assert!(matches!(result, Err(XfbinError::UnsupportedType(ref found)) if found == tag), "{}", tag);
+    }
+
+    let mut untagged = value.clone();
+    untagged.as_object_mut().unwrap().remove("type");
+    assert!(matches!(struct_from_json_str(&untagged.to_string()), Err(XfbinError::Json(_))));
+}
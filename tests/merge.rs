@@ -0,0 +1,114 @@
+mod common;
+
+use common::{binary, document, struct_info};
+use xfbin_lib::*;
+
+fn data(xfbin: &Xfbin, chunk_name: &str) -> Vec<u8> {
+    xfbin.get_by_name(chunk_name).unwrap().downcast_ref::<NuccBinary>().unwrap().data.to_vec()
+}
+
+fn chunk_names(page: &XfbinPage) -> Vec<&str> {
+    page.structs.iter().map(|nucc_struct| nucc_struct.struct_info().chunk_name.as_str()).collect()
+}
+
+#[test]
+fn prefer_overlay_replaces_base_structs_in_place() {
+    let mut base = document(vec![binary("a", vec![1]), binary("b", vec![2])]);
+    let overlay = document(vec![binary("b", vec![3]), binary("c", vec![4])]);
+
+    let report = base.merge([overlay], MergePolicy::PreferOverlay).unwrap();
+
+    assert_eq!(report.count(MergeAction::Replaced), 1);
+    assert_eq!(report.count(MergeAction::Inserted), 1);
+
+    // Replaced structs stay where they were; new ones get a page of their own
+    assert_eq!(base.pages.len(), 2);
+    assert_eq!(chunk_names(&base.pages[0]), ["a", "b"]);
+    assert_eq!(chunk_names(&base.pages[1]), ["c"]);
+    assert_eq!(data(&base, "b"), [3]);
+    assert_eq!(report.entries[0].page_index, Some(0));
+    assert_eq!(report.entries[1].page_index, Some(1));
+}
+
+#[test]
+fn later_overlays_win_under_prefer_overlay() {
+    let mut base = document(vec![binary("a", vec![1])]);
+    let overlays = [document(vec![binary("a", vec![2])]), document(vec![binary("a", vec![3])])];
+
+    base.merge(overlays, MergePolicy::PreferOverlay).unwrap();
+
+    assert_eq!(data(&base, "a"), [3]);
+}
+
+#[test]
+fn prefer_base_keeps_base_structs() {
+    let mut base = document(vec![binary("a", vec![1])]);
+    let overlay = document(vec![binary("a", vec![2]), binary("c", vec![4])]);
+
+    let report = base.merge([overlay], MergePolicy::PreferBase).unwrap();
+
+    assert_eq!(report.count(MergeAction::KeptBase), 1);
+    assert_eq!(report.entries[0].page_index, None);
+    assert_eq!(data(&base, "a"), [1]);
+    assert_eq!(data(&base, "c"), [4]);
+}
+
+#[test]
+fn fail_reports_a_conflict_and_leaves_the_base_untouched() {
+    let mut base = document(vec![binary("a", vec![1])]);
+    let overlay = document(vec![binary("c", vec![4]), binary("a", vec![2])]);
+
+    let error = base.merge([overlay], MergePolicy::Fail).unwrap_err();
+
+    assert!(matches!(error, XfbinError::MergeConflict { overlay_index: 0, ref chunk_name, .. } if chunk_name == "a"), "{}", error);
+    assert_eq!(base.pages.len(), 1);
+    assert!(base.get_by_name("c").is_none());
+}
+
+#[test]
+fn rename_keeps_both_structs_and_retargets_the_overlays_references() {
+    let mut base = document(vec![binary("a", vec![1]), binary("a_1", vec![2])]);
+
+    let mut overlay = document(vec![binary("a", vec![3])]);
+    overlay.pages[0].add_reference(NuccStructReference {
+        chunk_name: "ref".to_string(),
+        struct_info: struct_info("a", "nuccChunkBinary"),
+    });
+
+    let report = base.merge([overlay], MergePolicy::Rename).unwrap();
+
+    // "a_1" is taken, so the overlay's struct becomes "a_2"
+    assert_eq!(report.count(MergeAction::Renamed), 1);
+    assert_eq!(report.entries[0].renamed_to, Some(struct_info("a_2", "nuccChunkBinary")));
+    assert_eq!(data(&base, "a"), [1]);
+    assert_eq!(data(&base, "a_2"), [3]);
+
+    let page = &base.pages[1];
+    assert_eq!(page.struct_references[0].struct_info, struct_info("a_2", "nuccChunkBinary"));
+    assert!(page.struct_infos.contains(&struct_info("a_2", "nuccChunkBinary")));
+    assert!(!page.struct_infos.contains(&struct_info("a", "nuccChunkBinary")));
+}
+
+#[test]
+fn replaced_pages_get_the_overlays_references() {
+    let mut base = document(vec![binary("a", vec![1]), binary("b", vec![2])]);
+    base.pages.push(document(vec![binary("c", vec![3])]).pages.remove(0));
+
+    // Two overlay structs replace structs in page 0 and one in page 1, out of page order
+    let mut overlay = document(vec![binary("a", vec![4]), binary("c", vec![5]), binary("b", vec![6])]);
+    let reference = NuccStructReference {
+        chunk_name: "ref".to_string(),
+        struct_info: struct_info("a", "nuccChunkBinary"),
+    };
+    overlay.pages[0].add_reference(reference.clone());
+
+    base.merge([overlay], MergePolicy::PreferOverlay).unwrap();
+
+    assert_eq!(base.pages.len(), 2);
+    for page in &base.pages {
+        assert_eq!(page.struct_references, std::slice::from_ref(&reference));
+    }
+
+    let target = base.follow_reference(1, &reference).unwrap();
+    assert_eq!(target.downcast_ref::<NuccBinary>().unwrap().data.to_vec(), [4]);
+}
@@ -0,0 +1,78 @@
+import struct
+import unittest
+
+import xfbin_lib as x
+
+
+class Counter:
+    """A chunk holding a single big-endian u32"""
+
+    def __init__(self, value, struct_info=None):
+        self.value = value
+        self.struct_info = struct_info
+
+    @classmethod
+    def from_bytes(cls, data, version):
+        instance = cls(struct.unpack(">I", bytes(data))[0])
+        instance.read_version = version
+        return instance
+
+    def to_bytes(self):
+        return struct.pack(">I", self.value)
+
+
+class Broken(Counter):
+    def to_bytes(self):
+        raise ValueError("can't encode")
+
+
+def document(structs):
+    return x.Xfbin(121, [x.XfbinPage(structs, [], [])])
+
+
+class RegisterCodecTest(unittest.TestCase):
+    def test_registered_classes_round_trip_through_their_methods(self):
+        x.register_codec("nuccChunkCounter", Counter)
+        struct_info = x.NuccStructInfo("counter", "nuccChunkCounter", "c/test.bin")
+
+        buf = bytes(x.write_xfbin_buf(document([Counter(0x12345678, struct_info)])))
+        self.assertIn(bytes.fromhex("12345678"), buf)
+
+        counter = x.read_xfbin_buf(buf).pages[0].structs[0]
+        self.assertIsInstance(counter, Counter)
+        self.assertEqual(counter.value, 0x12345678)
+        self.assertEqual(counter.read_version, 121)
+        self.assertEqual(counter.struct_info.chunk_name, "counter")
+        self.assertEqual(counter.version, 121)
+
+        counter.value = 7
+        reread = x.read_xfbin_buf(x.write_xfbin_buf(document([counter]))).pages[0].structs[0]
+        self.assertEqual(reread.value, 7)
+
+    def test_classes_without_both_methods_are_rejected(self):
+        class NoToBytes:
+            @classmethod
+            def from_bytes(cls, data, version):
+                return cls()
+
+        class NoFromBytes:
+            def to_bytes(self):
+                return b""
+
+        for cls in [NoToBytes, NoFromBytes]:
+            with self.subTest(cls=cls.__name__):
+                with self.assertRaises(TypeError):
+                    x.register_codec("nuccChunkRejected", cls)
+
+    def test_exceptions_from_to_bytes_fail_the_write(self):
+        x.register_codec("nuccChunkBroken", Broken)
+        struct_info = x.NuccStructInfo("broken", "nuccChunkBroken", "c/test.bin")
+
+        with self.assertRaises(x.ChunkEncodeError) as raised:
+            x.write_xfbin_buf(document([Broken(1, struct_info)]))
+
+        self.assertIn("to_bytes: can't encode", str(raised.exception))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,65 @@
+import unittest
+
+import xfbin_lib as x
+
+
+def struct_info(chunk_name):
+    return x.NuccStructInfo(chunk_name, "nuccChunkBinary", "c/test.bin")
+
+
+def binary(chunk_name):
+    return x.NuccBinary(struct_info(chunk_name), 121, [0] * 4)
+
+
+def table(page):
+    return [struct_info.chunk_name for struct_info in page.struct_infos]
+
+
+class PageEditTest(unittest.TestCase):
+    def setUp(self):
+        self.page = x.XfbinPage()
+        self.a, self.b = binary("a"), binary("b")
+        self.page.add_struct(self.a)
+        self.page.add_struct(self.b)
+
+    def test_added_structs_keep_their_identity(self):
+        structs = self.page.structs
+
+        self.assertIs(structs[0], self.a)
+        self.assertIs(structs[1], self.b)
+        self.assertEqual(table(self.page), ["", "a", "b"])
+
+    def test_removing_returns_the_struct_and_releases_its_struct_info(self):
+        removed = self.page.remove_struct(0)
+
+        self.assertIs(removed, self.a)
+        self.assertEqual(self.page.structs, [self.b])
+        self.assertEqual(table(self.page), ["", "b"])
+
+    def test_replacing_takes_over_the_old_slot(self):
+        replacement = binary("x")
+
+        old = self.page.replace_struct(0, replacement)
+
+        self.assertIs(old, self.a)
+        self.assertIs(self.page.structs[0], replacement)
+        self.assertEqual(table(self.page), ["", "x", "b"])
+
+    def test_moving_only_changes_the_struct_order(self):
+        self.page.move_struct(0, 1)
+
+        self.assertEqual(self.page.structs, [self.b, self.a])
+        self.assertEqual(table(self.page), ["", "a", "b"])
+
+    def test_edits_out_of_range_are_rejected(self):
+        with self.assertRaises(x.IndexOutOfRangeError):
+            self.page.remove_struct(2)
+
+        with self.assertRaises(x.IndexOutOfRangeError):
+            self.page.move_struct(0, 2)
+
+        self.assertEqual(self.page.structs, [self.a, self.b])
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,73 @@
+"""Run against the built module, e.g. `maturin develop` and then `python -m unittest discover tests/python`."""
+import sys
+import threading
+import unittest
+
+import xfbin_lib as x
+
+
+def binary_file(count, size):
+    structs = [
+        x.NuccBinary(x.NuccStructInfo(f"bin{i}", "nuccChunkBinary", "c/test.bin"), 121, [i % 256] * size)
+        for i in range(count)
+    ]
+
+    return bytes(x.write_xfbin_buf(x.Xfbin(121, [x.XfbinPage(structs, [], [])])))
+
+
+class GilTest(unittest.TestCase):
+    def test_reading_releases_the_gil(self):
+        buf = binary_file(500, 1024)
+
+        count = 0
+        stop = threading.Event()
+
+        def spin():
+            nonlocal count
+            while not stop.is_set():
+                count += 1
+
+        # Long enough that the spinning thread can only run while the read has let go of the GIL
+        interval = sys.getswitchinterval()
+        sys.setswitchinterval(0.5)
+        spinner = threading.Thread(target=spin)
+        spinner.start()
+
+        try:
+            before = count
+            x.read_xfbin_buf(buf)
+            after = count
+        finally:
+            stop.set()
+            spinner.join()
+            sys.setswitchinterval(interval)
+
+        self.assertGreater(after, before)
+
+    def test_files_can_be_read_and_written_from_several_threads(self):
+        buf = binary_file(200, 64)
+        results = [None] * 8
+
+        def round_trip(i):
+            results[i] = bytes(x.write_xfbin_buf(x.read_xfbin_buf(buf)))
+
+        threads = [threading.Thread(target=round_trip, args=(i,)) for i in range(len(results))]
+        for thread in threads:
+            thread.start()
+        for thread in threads:
+            thread.join()
+
+        self.assertEqual(results, [buf] * len(results))
+
+
+class ErrorTest(unittest.TestCase):
+    def test_io_errors_are_os_errors(self):
+        with self.assertRaises(OSError) as caught:
+            x.read_xfbin("/nonexistent/file.xfbin")
+
+        self.assertIsInstance(caught.exception, x.XfbinIoError)
+        self.assertIsInstance(caught.exception, x.XfbinException)
+
+
+if __name__ == "__main__":
+    unittest.main()
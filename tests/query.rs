@@ -0,0 +1,100 @@
+mod common;
+
+use common::struct_info;
+use xfbin_lib::*;
+
+fn binary(chunk_name: &str, filepath: &str) -> Box<dyn NuccStruct> {
+    Box::new(NuccBinary {
+        struct_info: NuccStructInfo {
+            filepath: filepath.to_string(),
+            ..struct_info(chunk_name, "nuccChunkBinary")
+        },
+        version: 121,
+        data: vec![0; 4].into(),
+    })
+}
+
+fn anm(chunk_name: &str, filepath: &str) -> Box<dyn NuccStruct> {
+    Box::new(NuccAnm {
+        struct_info: NuccStructInfo {
+            filepath: filepath.to_string(),
+            ..struct_info(chunk_name, "nuccChunkAnm")
+        },
+        ..Default::default()
+    })
+}
+
+fn sample() -> Xfbin {
+    let page = |structs| XfbinPage {
+        structs,
+        ..Default::default()
+    };
+
+    Xfbin {
+        pages: vec![
+            page(vec![binary("1nrt_body", "c/1nrt/1nrt.bin"), anm("1nrt_idle", "c/1nrt/anm.bin")]),
+            page(vec![binary("2sak_body", "c/2sak/2sak.bin"), anm("2sak_idle", "c/2sak/anm.bin")]),
+        ],
+        ..Default::default()
+    }
+}
+
+fn names<'a>(structs: impl Iterator<Item = &'a dyn NuccStruct>) -> Vec<&'a str> {
+    structs.map(|nucc_struct| nucc_struct.struct_info().chunk_name.as_str()).collect()
+}
+
+#[test]
+fn structs_are_visited_in_file_order() {
+    let xfbin = sample();
+
+    assert_eq!(names(xfbin.structs()), ["1nrt_body", "1nrt_idle", "2sak_body", "2sak_idle"]);
+}
+
+#[test]
+fn exact_patterns_match_whole_fields() {
+    let xfbin = sample();
+
+    let filter = StructFilter::new().chunk_type(Pattern::exact("nuccChunkAnm"));
+    assert_eq!(names(xfbin.find_all(&filter)), ["1nrt_idle", "2sak_idle"]);
+
+    let filter = StructFilter::new().chunk_name(Pattern::exact("idle"));
+    assert!(xfbin.find(&filter).is_none());
+}
+
+#[test]
+fn glob_and_regex_patterns_can_be_combined() {
+    let xfbin = sample();
+
+    let filter = StructFilter::new()
+        .filepath(Pattern::glob("c/2sak/*").unwrap())
+        .chunk_name(Pattern::regex("_idle$").unwrap());
+
+    assert_eq!(names(xfbin.find_all(&filter)), ["2sak_idle"]);
+    assert_eq!(xfbin.find(&filter).unwrap().struct_info().chunk_name, "2sak_idle");
+}
+
+#[test]
+fn invalid_patterns_are_rejected() {
+    assert!(matches!(Pattern::glob("c/[1nrt"), Err(XfbinError::InvalidPattern { .. })));
+    assert!(matches!(Pattern::regex("(idle"), Err(XfbinError::InvalidPattern { .. })));
+}
+
+#[test]
+fn structs_can_be_looked_up_by_name_and_type() {
+    let mut xfbin = sample();
+
+    assert_eq!(xfbin.get_by_name("2sak_body").unwrap().chunk_type(), nucc_chunk::NuccChunkType::NuccChunkBinary);
+    assert!(xfbin.get_by_name("3kak_body").is_none());
+
+    assert_eq!(xfbin.structs_of::<NuccAnm>().count(), 2);
+    assert_eq!(xfbin.structs_of::<NuccCamera>().count(), 0);
+
+    for anm in xfbin.structs_of_mut::<NuccAnm>() {
+        anm.frame_count = 30;
+    }
+
+    xfbin.get_by_name_mut("1nrt_body").unwrap().downcast_mut::<NuccBinary>().unwrap().data = vec![1].into();
+
+    assert!(xfbin.structs_of::<NuccAnm>().all(|anm| anm.frame_count == 30));
+    assert_eq!(xfbin.find(&StructFilter::new().chunk_name(Pattern::exact("1nrt_body"))).unwrap().downcast_ref::<NuccBinary>().unwrap().data, vec![1]);
+}
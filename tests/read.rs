@@ -0,0 +1,16 @@
+mod common;
+
+use common::{binary, document};
+use xfbin_lib::*;
+
+#[test]
+fn eager_reads_share_the_file_buffer() {
+    let buf = write_xfbin_buf(document(vec![binary("bin", vec![0xAB; 64])])).unwrap();
+
+    let file = buf.as_ptr_range();
+    let xfbin = read_xfbin_buf(buf).unwrap();
+
+    let binary = xfbin.pages[0].structs[0].downcast_ref::<NuccBinary>().unwrap();
+    assert_eq!(binary.data, vec![0xAB; 64]);
+    assert!(file.contains(&binary.data.as_ptr()));
+}
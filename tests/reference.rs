@@ -0,0 +1,88 @@
+mod common;
+
+use common::{binary, struct_info};
+use xfbin_lib::*;
+
+fn reference(chunk_name: &str, filepath: &str) -> NuccStructReference {
+    NuccStructReference {
+        chunk_name: chunk_name.to_string(),
+        struct_info: NuccStructInfo {
+            filepath: filepath.to_string(),
+            ..struct_info(chunk_name, "nuccChunkBinary")
+        },
+    }
+}
+
+/// Two pages with a struct each; the first page refers to both, to a struct in another file and to a missing one
+fn sample() -> Xfbin {
+    let mut first = XfbinPage::default();
+    first.add_struct(binary("a", vec![0; 4]));
+    first.add_reference(reference("a", "c/test.bin"));
+    first.add_reference(reference("b", "c/test.bin"));
+    first.add_reference(reference("x", "c/other.bin"));
+    first.add_reference(reference("gone", "c/test.bin"));
+
+    let mut second = XfbinPage::default();
+    second.add_struct(binary("b", vec![0; 4]));
+
+    Xfbin {
+        pages: vec![first, second],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn references_resolve_to_where_their_struct_is() {
+    let xfbin = sample();
+    let targets = xfbin.references().into_iter().map(|resolved| resolved.target).collect::<Vec<_>>();
+
+    assert_eq!(
+        targets,
+        [
+            ReferenceTarget::InPage { struct_index: 0 },
+            ReferenceTarget::OtherPage { page_index: 1, struct_index: 0 },
+            ReferenceTarget::External { filepath: "c/other.bin".to_string() },
+            ReferenceTarget::Dangling {},
+        ]
+    );
+}
+
+#[test]
+fn structs_in_the_referring_page_come_first() {
+    let mut xfbin = sample();
+    xfbin.pages[1].add_struct(binary("a", vec![0; 4]));
+
+    let target = xfbin.resolve_reference(1, &reference("a", "c/test.bin"));
+
+    assert_eq!(target, ReferenceTarget::InPage { struct_index: 1 });
+}
+
+#[test]
+fn references_can_be_followed_to_their_struct() {
+    let mut xfbin = sample();
+
+    let followed = xfbin.follow_reference(0, &reference("b", "c/test.bin")).unwrap();
+    assert_eq!(followed.struct_info().chunk_name, "b");
+
+    assert!(xfbin.follow_reference(0, &reference("x", "c/other.bin")).is_none());
+    assert!(xfbin.follow_reference(0, &reference("gone", "c/test.bin")).is_none());
+
+    let binary = xfbin.follow_reference_mut(0, &reference("a", "c/test.bin")).unwrap();
+    binary.downcast_mut::<NuccBinary>().unwrap().data = vec![1].into();
+
+    assert_eq!(xfbin.pages[0].structs[0].downcast_ref::<NuccBinary>().unwrap().data, vec![1]);
+}
+
+#[test]
+fn removing_a_struct_leaves_its_references_dangling() {
+    let mut xfbin = sample();
+    assert_eq!(xfbin.dangling_references().len(), 1);
+
+    xfbin.pages[1].remove_struct(0).unwrap();
+
+    let dangling = xfbin.dangling_references();
+    let names = dangling.iter().map(|resolved| resolved.reference.chunk_name.as_str()).collect::<Vec<_>>();
+
+    assert_eq!(names, ["b", "gone"]);
+    assert_eq!((dangling[0].page_index, dangling[0].reference_index), (0, 1));
+}
@@ -0,0 +1,222 @@
+mod common;
+
+use binrw::{io::Cursor, BinReaderExt, BinWriterExt};
+use common::{sample_file, struct_info};
+use xfbin_lib::nucc::NuccAmbient;
+use xfbin_lib::xfbin_file::{XfbinChunk, XfbinFile};
+use xfbin_lib::*;
+
+/// The raw structure of a file, to make it look like one the library didn't write
+fn parse(buf: Vec<u8>) -> XfbinFile {
+    Cursor::new(buf).read_be().unwrap()
+}
+
+fn serialize(xfbin_file: &XfbinFile) -> Vec<u8> {
+    let mut writer = Cursor::new(Vec::new());
+    writer.write_be(xfbin_file).unwrap();
+
+    writer.into_inner()
+}
+
+/// The first chunk of the given type, found by walking the pages the way the reader does
+fn chunk_mut<'a>(xfbin_file: &'a mut XfbinFile, chunk_type: &str) -> &'a mut XfbinChunk {
+    let index = &xfbin_file.index;
+    let mut page_start = 0;
+
+    let position = xfbin_file
+        .chunks
+        .iter()
+        .position(|chunk| {
+            let chunk_map = &index.chunk_maps[index.chunk_map_indices[page_start + chunk.chunk_map_index as usize] as usize];
+            let found = index.chunk_types[chunk_map.chunk_type_index as usize].to_string();
+
+            if found == "nuccChunkPage" {
+                page_start += u32::from_be_bytes(chunk.data[..4].try_into().unwrap()) as usize;
+            }
+
+            found == chunk_type
+        })
+        .unwrap();
+
+    &mut xfbin_file.chunks[position]
+}
+
+fn assert_round_trips(buf: Vec<u8>) {
+    let xfbin = read_xfbin_buf(buf.clone()).unwrap();
+
+    assert_eq!(write_xfbin_buf(xfbin).unwrap(), buf);
+}
+
+#[test]
+fn unmodified_files_round_trip_byte_for_byte() {
+    let original = sample_file();
+
+    let xfbin = read_xfbin_buf(original.clone()).unwrap();
+    assert!(xfbin.layout.is_some());
+
+    assert_eq!(write_xfbin_buf(xfbin).unwrap(), original);
+}
+
+#[test]
+fn duplicate_chunk_maps_keep_the_indices_after_them() {
+    let mut xfbin = read_xfbin_buf(sample_file()).unwrap();
+
+    let layout = xfbin.layout.as_mut().unwrap();
+    let chunk_map_count = layout.struct_infos.len();
+    let duplicate = layout.struct_infos[2].clone();
+    layout.struct_infos.push(duplicate);
+
+    // A struct that isn't in the layout yet gets the first chunk map index after the duplicate
+    xfbin.pages[0].add_struct(Box::new(NuccBinary {
+        struct_info: struct_info("new", "nuccChunkBinary"),
+        version: 121,
+        data: vec![0xAA; 3].into(),
+    }));
+
+    let written = write_xfbin_buf(xfbin).unwrap();
+    let reread = read_xfbin_buf(written.clone()).unwrap();
+
+    // The duplicate and the new struct's chunk map
+    assert_eq!(reread.layout.as_ref().unwrap().struct_infos.len(), chunk_map_count + 2);
+    assert!(reread.get_by_name("new").is_some());
+    assert_eq!(write_xfbin_buf(reread).unwrap(), written);
+}
+
+#[test]
+fn header_versions_other_than_121_round_trip() {
+    let mut xfbin_file = parse(sample_file());
+    xfbin_file.header.version = 125;
+
+    for chunk in &mut xfbin_file.chunks {
+        chunk.version = 125;
+    }
+
+    assert_round_trips(serialize(&xfbin_file));
+}
+
+#[test]
+fn min_page_size_round_trips() {
+    let mut xfbin_file = parse(sample_file());
+    xfbin_file.index.min_page_size = 9;
+
+    assert_round_trips(serialize(&xfbin_file));
+}
+
+#[test]
+fn string_table_order_round_trips() {
+    let mut xfbin_file = parse(sample_file());
+
+    // Reverse the chunk names and point the chunk maps at their new positions
+    let index = &mut xfbin_file.index;
+    let last = index.chunk_names.len() as u32 - 1;
+    index.chunk_names.reverse();
+
+    for chunk_map in &mut index.chunk_maps {
+        chunk_map.chunk_name_index = last - chunk_map.chunk_name_index;
+    }
+
+    assert_round_trips(serialize(&xfbin_file));
+}
+
+/// Flips every payload byte, so the stored payload differs from the plain one
+struct Invert;
+
+impl PayloadTransform for Invert {
+    fn decode(&self, payload: &[u8]) -> XfbinResult<Vec<u8>> {
+        Ok(payload.iter().map(|b| !b).collect())
+    }
+
+    fn encode(&self, payload: &[u8]) -> XfbinResult<Vec<u8>> {
+        self.decode(payload)
+    }
+}
+
+#[test]
+fn encrypted_flag_round_trips() {
+    const FLAG: u16 = 0x5A;
+    register_transform(FLAG, Invert);
+
+    let mut xfbin_file = parse(sample_file());
+    xfbin_file.header.encrypted = FLAG;
+
+    let mut buf = serialize(&xfbin_file);
+    for b in &mut buf[0x10..] {
+        *b = !*b;
+    }
+
+    assert_round_trips(buf);
+}
+
+#[test]
+fn anm_frame_size_round_trips() {
+    let mut xfbin_file = parse(sample_file());
+
+    // frame_size follows frame_count at the start of the chunk
+    let anm = chunk_mut(&mut xfbin_file, "nuccChunkAnm");
+    let mut data = anm.data.to_vec();
+    data[4..8].copy_from_slice(&50u32.to_be_bytes());
+    anm.data = data.into();
+
+    let buf = serialize(&xfbin_file);
+    let xfbin = read_xfbin_buf(buf.clone()).unwrap();
+    assert_eq!(xfbin.get_by_name("anm").unwrap().downcast_ref::<NuccAnm>().unwrap().frame_size, 50);
+
+    assert_eq!(write_xfbin_buf(xfbin).unwrap(), buf);
+}
+
+#[test]
+fn loop_flags_other_than_0_and_1_round_trip() {
+    let mut xfbin_file = parse(sample_file());
+
+    // After frame_count, frame_size and entry_count
+    let anm = chunk_mut(&mut xfbin_file, "nuccChunkAnm");
+    let mut data = anm.data.to_vec();
+    data[10..12].copy_from_slice(&2u16.to_be_bytes());
+    anm.data = data.into();
+
+    assert_round_trips(serialize(&xfbin_file));
+}
+
+#[test]
+fn chunk_padding_is_kept() {
+    let mut xfbin_file = parse(sample_file());
+
+    let ambient = chunk_mut(&mut xfbin_file, "nuccChunkAmbient");
+    ambient.data = [&ambient.data[..], &[0xEE; 4]].concat().into();
+
+    let buf = serialize(&xfbin_file);
+    let xfbin = read_xfbin_buf(buf.clone()).unwrap();
+    assert_eq!(xfbin.get_by_name("ambient").unwrap().downcast_ref::<NuccAmbient>().unwrap().trailing, vec![0xEE; 4]);
+
+    assert_eq!(write_xfbin_buf(xfbin).unwrap(), buf);
+}
+
+#[test]
+fn removed_structs_leave_no_chunk_maps_or_strings_behind() {
+    let mut xfbin = read_xfbin_buf(sample_file()).unwrap();
+    xfbin.pages[0].remove_struct(1).unwrap();
+
+    let reread = read_xfbin_buf(write_xfbin_buf(xfbin).unwrap()).unwrap();
+    let layout = reread.layout.unwrap();
+
+    assert!(!layout.struct_infos.iter().any(|struct_info| struct_info.chunk_name == "ambient"));
+    assert!(!layout.chunk_names.contains(&"ambient".to_string()));
+    assert!(!layout.chunk_types.contains(&"nuccChunkAmbient".to_string()));
+}
+
+#[test]
+fn structs_after_the_last_page_chunk_are_kept() {
+    let mut xfbin_file = parse(sample_file());
+    xfbin_file.chunks.pop();
+    let buf = serialize(&xfbin_file);
+
+    let eager = read_xfbin_buf(buf.clone()).unwrap();
+    let lazy = XfbinReader::new(Cursor::new(buf)).unwrap().into_xfbin().unwrap();
+
+    for xfbin in [eager, lazy] {
+        assert_eq!(xfbin.pages.len(), 2);
+
+        let names = xfbin.pages[1].structs.iter().map(|nucc_struct| nucc_struct.struct_info().chunk_name.clone()).collect::<Vec<_>>();
+        assert_eq!(names, ["light", "anm"]);
+    }
+}
@@ -0,0 +1,155 @@
+use xfbin_lib::nucc_chunk::nucc_chunk_anm::*;
+use xfbin_lib::*;
+
+fn track(key_format: NuccAnmKeyFormat, keys: Vec<NuccAnmKey>) -> AnmTrack {
+    AnmTrack {
+        track_index: 0,
+        key_format,
+        keys,
+    }
+}
+
+fn floats(values: &[f32]) -> Vec<NuccAnmKey> {
+    values.iter().map(|&values| NuccAnmKey::Float { values }).collect()
+}
+
+fn float(value: Option<TrackValue>) -> f32 {
+    match value {
+        Some(TrackValue::Float(value)) => value,
+        value => panic!("{:?}", value),
+    }
+}
+
+fn assert_close(found: f32, expected: f32) {
+    assert!((found - expected).abs() < 1e-5, "{} != {}", found, expected);
+}
+
+#[test]
+fn tables_interpolate_between_frames() {
+    let track = track(NuccAnmKeyFormat::FloatTable, floats(&[0.0, 10.0, 30.0]));
+
+    assert_close(float(track.sample(0.5, 100)), 5.0);
+    assert_close(float(track.sample(1.25, 100)), 15.0);
+
+    // Past either end, the nearest key holds
+    assert_close(float(track.sample(-1.0, 100)), 0.0);
+    assert_close(float(track.sample(5.0, 100)), 30.0);
+}
+
+#[test]
+fn step_tables_hold_each_frame() {
+    let track = track(NuccAnmKeyFormat::FloatTableNoInterp, floats(&[0.0, 10.0, 30.0]));
+
+    assert_close(float(track.sample(0.9, 100)), 0.0);
+    assert_close(float(track.sample(1.0, 100)), 10.0);
+    assert_close(float(track.sample(1.5, 100)), 10.0);
+}
+
+#[test]
+fn short_tables_are_scaled_back_to_floats() {
+    let track = track(NuccAnmKeyFormat::OpacityShortTable, vec![NuccAnmKey::I16Vec { values: 0x4000 }]);
+
+    assert_close(float(track.sample(0.0, 100)), 0.5);
+}
+
+#[test]
+fn keyed_frames_are_ticks_of_frame_size() {
+    let keys = vec![
+        NuccAnmKey::FloatLinear { frame: 0, values: 0.0 },
+        NuccAnmKey::FloatLinear { frame: 200, values: 20.0 },
+        NuccAnmKey::FloatLinear { frame: -1, values: 20.0 },
+    ];
+    let track = track(NuccAnmKeyFormat::FloatLinear, keys);
+
+    // The second key is on frame 2 at 100 ticks per frame, and on frame 1 at 200
+    assert_close(float(track.sample(1.0, 100)), 10.0);
+    assert_close(float(track.sample(1.0, 200)), 20.0);
+    assert_close(float(track.sample(0.5, 200)), 10.0);
+
+    // The terminator isn't a key, so the last key holds after it
+    assert_close(float(track.sample(10.0, 100)), 20.0);
+}
+
+#[test]
+fn quaternions_take_the_shorter_arc() {
+    let half = std::f32::consts::FRAC_1_SQRT_2;
+
+    // The second key is a quarter turn around z, stored negated
+    let keys = vec![
+        NuccAnmKey::Vec4 { values: (0.0, 0.0, 0.0, 1.0) },
+        NuccAnmKey::Vec4 { values: (0.0, 0.0, -half, -half) },
+    ];
+    let track = track(NuccAnmKeyFormat::QuaternionTable, keys);
+
+    let Some(TrackValue::Quaternion([x, y, z, w])) = track.sample(0.5, 100) else {
+        panic!();
+    };
+
+    // An eighth turn, rather than the long way around
+    let eighth = std::f32::consts::FRAC_PI_8;
+    assert_close(x, 0.0);
+    assert_close(y, 0.0);
+    assert_close(z, eighth.sin());
+    assert_close(w, eighth.cos());
+}
+
+#[test]
+fn looped_animations_wrap_and_others_clamp() {
+    let mut anm = NuccAnm {
+        frame_count: 400,
+        frame_size: 100,
+        ..Default::default()
+    };
+
+    assert_close(anm.length(), 4.0);
+    assert_close(anm.playback_frame(5.0), 4.0);
+    assert_close(anm.playback_frame(-1.0), 0.0);
+
+    anm.is_looped = true;
+    assert_close(anm.playback_frame(5.0), 1.0);
+    assert_close(anm.playback_frame(-1.0), 3.0);
+}
+
+#[test]
+fn entries_are_sampled_at_their_playback_frame() {
+    let entry = AnmEntry {
+        coord: AnmCoord { clump_index: -1, coord_index: 0 },
+        entry_format: EntryFormat::Coord,
+        tracks: vec![AnmTrack {
+            track_index: 3,
+            ..track(NuccAnmKeyFormat::FloatTable, floats(&[0.0, 10.0, 20.0]))
+        }],
+    };
+
+    let anm = NuccAnm {
+        frame_count: 200,
+        frame_size: 100,
+        is_looped: true,
+        ..Default::default()
+    };
+
+    // Frame 2.5 wraps around to 0.5
+    let values = anm.sample_entry(&entry, 2.5);
+    assert_close(float(values.get(&3).copied()), 5.0);
+}
+
+#[test]
+fn looped_tables_wrap_from_the_last_key_to_the_first() {
+    let entry = AnmEntry {
+        coord: AnmCoord { clump_index: -1, coord_index: 0 },
+        entry_format: EntryFormat::Coord,
+        tracks: vec![track(NuccAnmKeyFormat::FloatTable, floats(&[0.0, 10.0, 20.0]))],
+    };
+
+    let mut anm = NuccAnm {
+        frame_count: 300,
+        frame_size: 100,
+        ..Default::default()
+    };
+
+    assert_close(float(anm.sample_entry(&entry, 2.5).get(&0).copied()), 20.0);
+
+    anm.is_looped = true;
+    assert_close(float(anm.sample_entry(&entry, 2.5).get(&0).copied()), 10.0);
+    assert_close(float(anm.sample_entry(&entry, 2.75).get(&0).copied()), 5.0);
+}
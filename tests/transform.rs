@@ -0,0 +1,27 @@
+use xfbin_lib::*;
+
+/// The start of a header with its `encrypted` flag set
+fn flagged_header(magic: &[u8; 4], len: usize) -> Vec<u8> {
+    let mut buf = vec![0; len];
+    buf[..4].copy_from_slice(magic);
+    buf[8..10].copy_from_slice(&1u16.to_be_bytes());
+
+    buf
+}
+
+#[test]
+fn short_flagged_files_are_an_unexpected_eof() {
+    for len in 10..16 {
+        match read_xfbin_buf(flagged_header(b"NUCC", len)) {
+            Err(XfbinError::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::UnexpectedEof),
+            result => panic!("{} bytes: {:?}", len, result.map(|_| ())),
+        }
+    }
+}
+
+#[test]
+fn flagged_files_have_their_magic_checked_before_decoding() {
+    let result = read_xfbin_buf(flagged_header(b"XFBN", 0x20));
+
+    assert!(matches!(result, Err(XfbinError::BadMagic { offset: 0, .. })));
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use common::{binary, struct_info, TempDir};
+use std::fs;
+use xfbin_lib::nucc::{NuccAmbient, NuccUnknown};
+use xfbin_lib::*;
+
+/// A binary and an ambient in one page, and an unknown chunk and an anm in another, read back so it has a layout
+fn sample_document() -> Xfbin {
+    let mut first = XfbinPage::default();
+    first.add_struct(binary("bin/with:odd chars", vec![1, 2, 3, 4, 5]));
+    first.add_struct(Box::new(NuccAmbient {
+        struct_info: struct_info("ambient", "nuccChunkAmbient"),
+        version: 121,
+        data: vec![7; 16],
+        trailing: Vec::new(),
+    }));
+
+    let mut second = XfbinPage::default();
+    second.add_struct(Box::new(NuccUnknown {
+        struct_info: struct_info("mystery", "nuccChunkMystery"),
+        version: 121,
+        data: vec![9; 12].into(),
+        chunk_type: "nuccChunkMystery".to_string(),
+    }));
+    second.add_struct(Box::new(NuccAnm {
+        struct_info: struct_info("anm", "nuccChunkAnm"),
+        frame_count: 100,
+        ..Default::default()
+    }));
+
+    let xfbin = Xfbin {
+        pages: vec![first, second],
+        ..Default::default()
+    };
+
+    read_xfbin_buf(write_xfbin_buf(xfbin).unwrap()).unwrap()
+}
+
+#[test]
+fn unpacked_documents_repack_to_the_same_file() {
+    let dir = TempDir::new("unpack_round_trip");
+    let xfbin = sample_document();
+    let buf = write_xfbin_buf(xfbin.clone()).unwrap();
+
+    unpack_to_dir(&xfbin, dir.path()).unwrap();
+
+    assert_eq!(write_xfbin_buf(repack_from_dir(dir.path()).unwrap()).unwrap(), buf);
+}
+
+#[test]
+fn structs_are_laid_out_one_file_each() {
+    let dir = TempDir::new("unpack_layout");
+    unpack_to_dir(&sample_document(), dir.path()).unwrap();
+
+    let mut files = Vec::new();
+    for page in ["page_000", "page_001"] {
+        let mut names = fs::read_dir(dir.join(page))
+            .unwrap()
+            .map(|entry| format!("{}/{}", page, entry.unwrap().file_name().to_string_lossy()))
+            .collect::<Vec<_>>();
+        names.sort();
+        files.extend(names);
+    }
+
+    assert!(dir.join("xfbin.json").is_file());
+    assert_eq!(
+        files,
+        [
+            "page_000/000_bin_with_odd_chars.bin",
+            "page_000/001_ambient.json",
+            "page_000/page.json",
+            "page_001/000_mystery.bin",
+            "page_001/001_anm.json",
+            "page_001/page.json",
+        ]
+    );
+
+    // Raw payloads are written as they are
+    assert_eq!(fs::read(dir.join("page_000/000_bin_with_odd_chars.bin")).unwrap(), [1, 2, 3, 4, 5]);
+    assert_eq!(fs::read(dir.join("page_001/000_mystery.bin")).unwrap(), [9; 12]);
+}
+
+#[test]
+fn edited_files_are_picked_up_when_repacking() {
+    let dir = TempDir::new("unpack_edit");
+    unpack_to_dir(&sample_document(), dir.path()).unwrap();
+
+    fs::write(dir.join("page_000/000_bin_with_odd_chars.bin"), [8, 8]).unwrap();
+
+    let anm_path = dir.join("page_001/001_anm.json");
+    let anm_json = fs::read_to_string(&anm_path).unwrap().replace("\"frame_count\": 100", "\"frame_count\": 250");
+    fs::write(&anm_path, anm_json).unwrap();
+
+    let xfbin = repack_from_dir(dir.path()).unwrap();
+
+    let binary = xfbin.get_by_name("bin/with:odd chars").unwrap().downcast_ref::<NuccBinary>().unwrap();
+    assert_eq!(binary.data.to_vec(), [8, 8]);
+    assert_eq!(xfbin.get_by_name("anm").unwrap().downcast_ref::<NuccAnm>().unwrap().frame_count, 250);
+
+    let unknown = xfbin.get_by_name("mystery").unwrap().downcast_ref::<NuccUnknown>().unwrap();
+    assert_eq!(unknown.chunk_type, "nuccChunkMystery");
+}
+
+#[test]
+fn missing_manifests_are_io_errors() {
+    let dir = TempDir::new("unpack_missing");
+    unpack_to_dir(&sample_document(), dir.path()).unwrap();
+
+    fs::remove_file(dir.join("page_001/page.json")).unwrap();
+
+    assert!(matches!(repack_from_dir(dir.path()), Err(XfbinError::Io(_))));
+}
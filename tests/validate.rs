@@ -0,0 +1,99 @@
+mod common;
+
+use binrw::{io::Cursor, BinReaderExt, BinWriterExt};
+use common::{document, sample_file, struct_info};
+use xfbin_lib::nucc_chunk::nucc_chunk_anm::{AnmCoord, AnmEntry, AnmTrack, EntryFormat, NuccAnmKey, NuccAnmKeyFormat};
+use xfbin_lib::xfbin_file::XfbinFile;
+use xfbin_lib::*;
+
+#[test]
+fn intact_files_have_no_findings() {
+    assert_eq!(validate_xfbin_buf(sample_file()).unwrap(), Vec::new());
+}
+
+#[test]
+fn truncated_files_are_reported_as_findings() {
+    let buf = sample_file();
+
+    // Cut into the last chunk, and then into the index
+    for len in [buf.len() - 2, 0x30] {
+        let findings = validate_xfbin_buf(buf[..len].to_vec()).unwrap();
+
+        assert!(has_errors(&findings), "{:?}", findings);
+    }
+}
+
+#[test]
+fn bad_magic_is_reported_as_a_finding() {
+    let mut buf = sample_file();
+    buf[..4].copy_from_slice(b"XXXX");
+
+    let findings = validate_xfbin_buf(buf).unwrap();
+
+    assert_eq!(findings.len(), 1);
+    assert!(findings[0].message.starts_with("the header can't be read"));
+}
+
+/// A file with one anm, its only track holding two keys of `first_key`
+fn anm_file(first_key: f32) -> Vec<u8> {
+    let anm = NuccAnm {
+        struct_info: struct_info("anm", "nuccChunkAnm"),
+        other_entries_indices: vec![0],
+        entries: vec![AnmEntry {
+            coord: AnmCoord { clump_index: -1, coord_index: 0 },
+            entry_format: EntryFormat::Coord,
+            tracks: vec![AnmTrack {
+                track_index: 0,
+                key_format: NuccAnmKeyFormat::FloatFixed,
+                keys: vec![NuccAnmKey::Float { values: first_key }; 2],
+            }],
+        }],
+        ..Default::default()
+    };
+
+    write_xfbin_buf(document(vec![Box::new(anm)])).unwrap()
+}
+
+#[test]
+fn intact_anm_tracks_have_no_findings() {
+    assert_eq!(validate_xfbin_buf(anm_file(1234.5)).unwrap(), Vec::new());
+}
+
+#[test]
+fn track_sizes_that_dont_match_the_frame_count_are_reported() {
+    let mut buf = anm_file(1234.5);
+
+    // The header's frame count and track size come right before the keys
+    let keys = buf.windows(4).position(|w| w == 1234.5f32.to_be_bytes()).unwrap();
+    assert_eq!(buf[keys - 4..keys], [0, 2, 0, 8]);
+    buf[keys - 2..keys].copy_from_slice(&12u16.to_be_bytes());
+
+    let findings = validate_xfbin_buf(buf).unwrap();
+
+    assert_eq!(findings.len(), 1, "{:?}", findings);
+    assert!(findings[0].message.starts_with("entry 0 track 0: the header's track size is 12 bytes"), "{}", findings[0]);
+}
+
+#[test]
+fn tracks_too_large_for_their_header_are_reported() {
+    let mut xfbin_file: XfbinFile = Cursor::new(anm_file(1234.5)).read_be().unwrap();
+
+    // 20000 float keys take 80000 bytes, which a u16 track size can't hold
+    let anm = xfbin_file.chunks.iter_mut().find(|chunk| chunk.data.windows(4).any(|w| w == 1234.5f32.to_be_bytes())).unwrap();
+    let mut data = anm.data.to_vec();
+    let keys = data.windows(4).position(|w| w == 1234.5f32.to_be_bytes()).unwrap();
+    data[keys - 4..keys - 2].copy_from_slice(&20000u16.to_be_bytes());
+    data.extend(1234.5f32.to_be_bytes().repeat(20000 - 2));
+    anm.data = data.into();
+
+    let mut writer = Cursor::new(Vec::new());
+    writer.write_be(&xfbin_file).unwrap();
+
+    let findings = validate_xfbin_buf(writer.into_inner()).unwrap();
+
+    assert!(
+        findings.iter().any(|finding| finding.severity == Severity::Error && finding.message.contains("don't fit in a track header")),
+        "{:?}",
+        findings
+    );
+}